@@ -0,0 +1,176 @@
+//! Exposes the board to screen readers through the accessibility tree of egui.
+
+use std::time::Duration;
+
+use egui::accesskit::{self, Live, Role};
+use egui::{Context, Id, Rect};
+
+use crate::translations::{fill, Translations};
+use crate::{FieldState, Game, PlayState, Visibility};
+
+pub fn board_id() -> Id {
+    Id::new("board")
+}
+
+/// The id of the node of the field, which is focused while the cursor is on it.
+pub fn cell_id(x: i16, y: i16) -> Id {
+    board_id().with((x, y))
+}
+
+/// Describes the field, like "row 3, column 7, hidden".
+pub fn cell_label(game: &Game, tr: &Translations, x: i16, y: i16) -> String {
+    let field = game[(x, y)];
+    let state = match (field.visibility(), field.state()) {
+        (Visibility::Hide, _) => tr.cell_hidden.to_string(),
+        (Visibility::Hint, _) => tr.cell_flagged.to_string(),
+        (Visibility::Show, FieldState::Mine) => tr.cell_mine.to_string(),
+        (Visibility::Show, FieldState::Free(n)) => fill(&tr.cell_revealed, n),
+    };
+    let position = fill(&fill(&tr.cell_position, y + 1), x + 1);
+    format!("{position}, {state}")
+}
+
+/// Adds the board as a grid of rows of cells, only the fields in view are added. Does nothing
+/// unless a screen reader is active.
+pub fn board(
+    ctx: &Context,
+    game: &Game,
+    tr: &Translations,
+    (min, max): ((i16, i16), (i16, i16)),
+    cell_rect: impl Fn(i16, i16) -> Rect,
+) {
+    let active = ctx.accesskit_node_builder(board_id(), |builder| {
+        builder.set_role(Role::Grid);
+        builder.set_name(&*tr.board);
+        builder.set_table_row_count(game.height as usize);
+        builder.set_table_column_count(game.width as usize);
+    });
+    if active.is_none() {
+        return;
+    }
+
+    ctx.with_accessibility_parent(board_id(), || {
+        for y in min.1.max(0)..=max.1.min(game.height - 1) {
+            let row_id = board_id().with(y);
+            ctx.accesskit_node_builder(row_id, |builder| {
+                builder.set_role(Role::Row);
+                builder.set_table_row_index(y as usize);
+            });
+            ctx.with_accessibility_parent(row_id, || {
+                for x in min.0.max(0)..=max.0.min(game.width - 1) {
+                    let label = cell_label(game, tr, x, y);
+                    let rect = cell_rect(x, y);
+                    ctx.accesskit_node_builder(cell_id(x, y), |builder| {
+                        builder.set_role(Role::Cell);
+                        builder.set_name(label);
+                        builder.set_table_cell_row_index(y as usize);
+                        builder.set_table_cell_column_index(x as usize);
+                        builder.set_bounds(accesskit::Rect {
+                            x0: rect.min.x.into(),
+                            y0: rect.min.y.into(),
+                            x1: rect.max.x.into(),
+                            y1: rect.max.y.into(),
+                        });
+                    });
+                }
+            });
+        }
+    });
+}
+
+/// Follows the game to announce what changed, like a live region of a web page.
+#[derive(Debug, Default)]
+pub struct Announcer {
+    /// The number of shown fields, the mine counter and whether the game was finished.
+    last: Option<(usize, i16, bool)>,
+    message: String,
+}
+
+impl Announcer {
+    /// Describes what changed since the last frame, a new game isn't announced.
+    pub fn update(&mut self, game: &Game, tr: &Translations) {
+        let finished = matches!(game.play_state, PlayState::Won(_) | PlayState::Lost(_));
+        let state = (game.num_shown(), game.mines_remaining(), finished);
+        let Some((shown, mines_remaining, was_finished)) = self.last.replace(state) else {
+            return;
+        };
+        if state == (shown, mines_remaining, was_finished) || state.0 < shown {
+            return;
+        }
+
+        self.message = match game.play_state {
+            PlayState::Won(duration) if !was_finished => {
+                fill(&tr.announce_won, spoken_duration(duration, tr))
+            }
+            PlayState::Lost(_) if !was_finished => tr.announce_lost.to_string(),
+            _ if state.0 == shown + 1 => tr.announce_opened_one.to_string(),
+            _ if state.0 > shown => fill(&tr.announce_opened, state.0 - shown),
+            _ if state.1 != mines_remaining => fill(&tr.announce_mine_counter, state.1),
+            _ => return,
+        };
+    }
+
+    /// Adds the last announcement, screen readers read it when it changes.
+    pub fn ui(&self, ctx: &Context) {
+        ctx.accesskit_node_builder(Id::new("announcement"), |builder| {
+            builder.set_role(Role::Status);
+            builder.set_live(Live::Polite);
+            builder.set_name(self.message.as_str());
+        });
+    }
+}
+
+/// Formats the duration in words, like "1 minute 42 seconds".
+fn spoken_duration(duration: Duration, tr: &Translations) -> String {
+    let secs = duration.as_secs();
+    let (mins, secs) = (secs / 60, secs % 60);
+    let seconds = match secs {
+        1 => tr.one_second.to_string(),
+        n => fill(&tr.seconds, n),
+    };
+    match mins {
+        0 => seconds,
+        1 => format!("{} {seconds}", tr.one_minute),
+        n => format!("{} {seconds}", fill(&tr.minutes, n)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Difficulty;
+
+    #[test]
+    fn labels() {
+        let tr = Translations::default();
+        let mut game = Game::new(3, 2, 0.0..1.0, Difficulty::Easy, false);
+        game[(0, 0)].set_state(FieldState::Free(2));
+        game.set_visibility(0, 0, Visibility::Show);
+        game.set_visibility(2, 1, Visibility::Hint);
+
+        assert_eq!(
+            cell_label(&game, &tr, 0, 0),
+            "row 1, column 1, revealed, 2 adjacent mines"
+        );
+        assert_eq!(cell_label(&game, &tr, 1, 0), "row 1, column 2, hidden");
+        assert_eq!(cell_label(&game, &tr, 2, 1), "row 2, column 3, flagged");
+    }
+
+    #[test]
+    fn announcements() {
+        let tr = Translations::default();
+        let mut announcer = Announcer::default();
+        let mut game = Game::new(3, 2, 0.0..1.0, Difficulty::Easy, false);
+        announcer.update(&game, &tr);
+        assert_eq!(announcer.message, "");
+
+        game.set_visibility(0, 0, Visibility::Show);
+        game.set_visibility(1, 0, Visibility::Show);
+        announcer.update(&game, &tr);
+        assert_eq!(announcer.message, "Opened 2 fields");
+
+        game.play_state = PlayState::Won(Duration::from_secs(102));
+        announcer.update(&game, &tr);
+        assert_eq!(announcer.message, "Game won in 1 minute 42 seconds");
+    }
+}
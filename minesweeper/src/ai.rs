@@ -0,0 +1,148 @@
+use std::fmt::Display;
+
+use rand::seq::SliceRandom;
+
+use crate::{Game, PlayState, Visibility};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Move {
+    pub x: i16,
+    pub y: i16,
+    pub action: Action,
+    pub reason: Reason,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    Reveal,
+    /// Toggle the hint of a field.
+    Hint,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Reason {
+    Opening,
+    SafeDeduction,
+    MineDeduction,
+    /// A hint was placed on a field that was deduced to be safe.
+    WrongHint,
+    /// Probability that the guessed field is a mine.
+    Guess(f32),
+    /// No revealed number borders the guessed field.
+    BlindGuess,
+}
+
+impl Display for Reason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Reason::Opening => write!(f, "Opening move"),
+            Reason::SafeDeduction => write!(f, "Safe by deduction"),
+            Reason::MineDeduction => write!(f, "Mine by deduction"),
+            Reason::WrongHint => write!(f, "Removing a hint that contradicts deduction"),
+            Reason::Guess(p) => write!(f, "Guess with {:.0}% mine risk", p * 100.0),
+            Reason::BlindGuess => write!(f, "Blind guess"),
+        }
+    }
+}
+
+/// State of the "watch the AI" mode.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct AutoPlay {
+    pub last_move_time: f64,
+    pub last_move: Option<Move>,
+}
+
+/// Picks the next move the solver would make, preferring certain deductions over guesses.
+pub fn next_move(game: &Game) -> Option<Move> {
+    match game.play_state {
+        PlayState::Init if !game.generated => {
+            let mv = Move {
+                x: game.width / 2,
+                y: game.height / 2,
+                action: Action::Reveal,
+                reason: Reason::Opening,
+            };
+            return Some(mv);
+        }
+        PlayState::Init | PlayState::Playing(_) => (),
+        PlayState::Won(_) | PlayState::Lost(_) => return None,
+    }
+
+    let analysis = game.analyze();
+    let mv = |(x, y): (i16, i16), action: Action, reason: Reason| Move {
+        x,
+        y,
+        action,
+        reason,
+    };
+
+    let wrong_hint = analysis
+        .safe
+        .iter()
+        .find(|&&p| game[p].visibility() == Visibility::Hint);
+    if let Some(&p) = wrong_hint {
+        return Some(mv(p, Action::Hint, Reason::WrongHint));
+    }
+    let missing_hint = analysis
+        .mines
+        .iter()
+        .find(|&&p| game[p].visibility() == Visibility::Hide);
+    if let Some(&p) = missing_hint {
+        return Some(mv(p, Action::Hint, Reason::MineDeduction));
+    }
+    if let Some(&p) = analysis.safe.first() {
+        return Some(mv(p, Action::Reveal, Reason::SafeDeduction));
+    }
+    if let Some((p, probability)) = analysis.best_guess() {
+        if game[p].visibility() == Visibility::Hint {
+            return Some(mv(p, Action::Hint, Reason::Guess(probability)));
+        }
+        return Some(mv(p, Action::Reveal, Reason::Guess(probability)));
+    }
+
+    let mut hidden = Vec::new();
+    for y in 0..game.height {
+        for x in 0..game.width {
+            if game[(x, y)].visibility() == Visibility::Hide {
+                hidden.push((x, y));
+            }
+        }
+    }
+    let &p = hidden.choose(&mut rand::thread_rng())?;
+    Some(mv(p, Action::Reveal, Reason::BlindGuess))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::FieldState;
+
+    #[test]
+    fn opening() {
+        let game = Game::new(5, 5, 0.0..1.0, crate::Difficulty::Easy, false);
+        let mv = next_move(&game).unwrap();
+        assert_eq!((mv.x, mv.y, mv.action), (2, 2, Action::Reveal));
+    }
+
+    #[test]
+    fn deductions_before_guesses() {
+        let mut game = Game::new(3, 2, 0.0..1.0, crate::Difficulty::Easy, false);
+        game.play_state = PlayState::Playing(instant::SystemTime::now());
+        game[(0, 1)].set_state(FieldState::Mine);
+        game[(0, 0)].set_state(FieldState::Free(1));
+        game[(1, 0)].set_state(FieldState::Free(1));
+        game[(1, 1)].set_state(FieldState::Free(1));
+        game.set_visibility(0, 0, Visibility::Show);
+        game.set_visibility(1, 0, Visibility::Show);
+        game.set_visibility(2, 0, Visibility::Show);
+
+        let mv = next_move(&game).unwrap();
+        assert_eq!((mv.x, mv.y, mv.action), (0, 1, Action::Hint));
+        assert_eq!(mv.reason, Reason::MineDeduction);
+
+        game.set_visibility(0, 1, Visibility::Hint);
+        let mv = next_move(&game).unwrap();
+        assert_eq!((mv.x, mv.y, mv.action), (1, 1, Action::Reveal));
+        assert_eq!(mv.reason, Reason::SafeDeduction);
+    }
+}
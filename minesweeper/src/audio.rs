@@ -0,0 +1,64 @@
+//! Plays the events of the game as short melodies of synthesized tones. They take the place of
+//! bundled sound files, which would grow the binary and the web build for a few blips. An app can
+//! play its own sounds by implementing [`Feedback`].
+
+use std::time::Duration;
+
+use rodio::source::{SineWave, Source};
+use rodio::{OutputStream, OutputStreamHandle, Sink};
+
+use crate::feedback::{Event, Feedback};
+
+const VOLUME: f32 = 0.2;
+
+/// The frequencies in Hz and lengths in milliseconds of the tones played for the event.
+fn melody(event: Event) -> &'static [(f32, u64)] {
+    match event {
+        Event::Reveal => &[(880.0, 25)],
+        Event::Cascade => &[(660.0, 30), (880.0, 30), (1100.0, 40)],
+        Event::Flag => &[(440.0, 40)],
+        Event::Chord => &[(740.0, 25), (990.0, 35)],
+        Event::Explosion => &[(220.0, 80), (146.8, 120), (98.0, 300)],
+        Event::Win => &[(523.3, 120), (659.3, 120), (784.0, 120), (1046.5, 300)],
+    }
+}
+
+/// Plays the default sounds on the default output device.
+pub struct Sounds {
+    /// Playback stops when the stream is dropped.
+    _stream: OutputStream,
+    handle: OutputStreamHandle,
+}
+
+impl Sounds {
+    /// `None` if there is no output device.
+    pub fn new() -> Option<Self> {
+        match OutputStream::try_default() {
+            Ok((_stream, handle)) => Some(Self { _stream, handle }),
+            Err(e) => {
+                log::warn!("failed to open audio output: {e}");
+                None
+            }
+        }
+    }
+}
+
+impl Feedback for Sounds {
+    fn event(&mut self, event: Event) {
+        let sink = match Sink::try_new(&self.handle) {
+            Ok(sink) => sink,
+            Err(e) => {
+                log::warn!("failed to play sound: {e}");
+                return;
+            }
+        };
+        for &(frequency, millis) in melody(event) {
+            let tone = SineWave::new(frequency)
+                .take_duration(Duration::from_millis(millis))
+                .fade_in(Duration::from_millis(5))
+                .amplify(VOLUME);
+            sink.append(tone);
+        }
+        sink.detach();
+    }
+}
@@ -0,0 +1,87 @@
+use std::time::{Duration, Instant};
+
+use crate::{Difficulty, Game};
+
+/// Measures `n` boards of every difficulty and prints the percentiles of the timings.
+pub fn run(n: usize, unambigous: bool) {
+    for difficulty in [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard] {
+        println!("{difficulty:?}, {n} boards");
+        measure(difficulty, unambigous, n).print();
+        println!();
+    }
+}
+
+/// Timings of the hot paths, measured on a number of random boards.
+#[derive(Clone, Debug, Default)]
+struct Report {
+    generation: Vec<Duration>,
+    flood_fill: Vec<Duration>,
+    solving: Vec<Duration>,
+}
+
+impl Report {
+    fn print(&mut self) {
+        println!(
+            "{:<12}{:>12}{:>12}{:>12}{:>12}",
+            "", "p50", "p90", "p99", "max"
+        );
+        let rows = [
+            ("generation", &mut self.generation),
+            ("flood fill", &mut self.flood_fill),
+            ("solving", &mut self.solving),
+        ];
+        for (name, timings) in rows {
+            timings.sort_unstable();
+            let [p50, p90, p99, max] = [0.5, 0.9, 0.99, 1.0].map(|p| percentile(timings, p));
+            println!("{name:<12}{p50:>12.2?}{p90:>12.2?}{p99:>12.2?}{max:>12.2?}");
+        }
+    }
+}
+
+/// The duration below which the share `p` of the sorted `timings` lies.
+fn percentile(timings: &[Duration], p: f64) -> Duration {
+    if timings.is_empty() {
+        return Duration::ZERO;
+    }
+    let idx = ((timings.len() - 1) as f64 * p).round() as usize;
+    timings[idx]
+}
+
+/// Generates `n` boards of the `difficulty`, opens them in the middle and analyzes the result.
+fn measure(difficulty: Difficulty, unambigous: bool, n: usize) -> Report {
+    let mut report = Report::default();
+    for _ in 0..n {
+        let mut game = match difficulty {
+            Difficulty::Easy => Game::easy(unambigous),
+            Difficulty::Medium => Game::medium(unambigous),
+            Difficulty::Hard => Game::hard(unambigous),
+        };
+        let (x, y) = (game.width / 2, game.height / 2);
+
+        let start = Instant::now();
+        game.gen_opening(x, y);
+        report.generation.push(start.elapsed());
+
+        let start = Instant::now();
+        game.show_neighbors(x, y);
+        report.flood_fill.push(start.elapsed());
+
+        let start = Instant::now();
+        std::hint::black_box(game.analyze());
+        report.solving.push(start.elapsed());
+    }
+    report
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn percentiles() {
+        let timings = (1..=100).map(Duration::from_millis).collect::<Vec<_>>();
+        assert_eq!(percentile(&timings, 0.5), Duration::from_millis(51));
+        assert_eq!(percentile(&timings, 1.0), Duration::from_millis(100));
+        assert_eq!(percentile(&[], 0.9), Duration::ZERO);
+    }
+}
@@ -0,0 +1,24 @@
+//! Measures board generation, flood fill and solving outside of the GUI.
+//!
+//! Usage: `cargo run --release --features bench --bin bench -- [boards] [--unambigous]`
+
+use minesweeper::bench;
+
+fn main() {
+    let mut n = 1000;
+    let mut unambigous = false;
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--unambigous" => unambigous = true,
+            _ => match arg.parse() {
+                Ok(boards) => n = boards,
+                Err(_) => {
+                    eprintln!("usage: bench [boards] [--unambigous]");
+                    std::process::exit(1);
+                }
+            },
+        }
+    }
+
+    bench::run(n, unambigous);
+}
@@ -0,0 +1,168 @@
+use serde::ser::SerializeSeq;
+
+use crate::Field;
+
+/// Width and height of a chunk in fields.
+pub const CHUNK_SIZE: i16 = 32;
+const CHUNK_LEN: usize = CHUNK_SIZE as usize * CHUNK_SIZE as usize;
+
+/// The fields of a board, stored in square chunks. Fields outside the board, in the chunks at the
+/// right and bottom edge, are never read.
+#[derive(Clone)]
+pub(crate) struct Board {
+    width: i16,
+    height: i16,
+    /// Number of chunks in a row.
+    chunks_x: i16,
+    chunks: Vec<[Field; CHUNK_LEN]>,
+}
+
+impl Board {
+    pub fn new(width: i16, height: i16, field: Field) -> Self {
+        let chunks_x = (width + CHUNK_SIZE - 1) / CHUNK_SIZE;
+        let chunks_y = (height + CHUNK_SIZE - 1) / CHUNK_SIZE;
+        Self {
+            width,
+            height,
+            chunks_x,
+            chunks: vec![[field; CHUNK_LEN]; chunks_x as usize * chunks_y as usize],
+        }
+    }
+
+    /// Builds a board from fields in row-major order, `None` if they don't fill it exactly.
+    pub fn from_rows(width: i16, height: i16, fields: &[Field]) -> Option<Self> {
+        if width <= 0 || height <= 0 || fields.len() != width as usize * height as usize {
+            return None;
+        }
+        let mut board = Self::new(width, height, Field::free(0));
+        for (i, &field) in fields.iter().enumerate() {
+            let (x, y) = board.pos(i);
+            board[(x, y)] = field;
+        }
+        Some(board)
+    }
+
+    /// The number of fields on the board.
+    pub fn len(&self) -> usize {
+        self.width as usize * self.height as usize
+    }
+
+    /// The position of the field at `idx` in row-major order.
+    pub fn pos(&self, idx: usize) -> (i16, i16) {
+        let width = self.width as usize;
+        ((idx % width) as i16, (idx / width) as i16)
+    }
+
+    /// The fields in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = &Field> + '_ {
+        (0..self.height).flat_map(move |y| (0..self.width).map(move |x| &self[(x, y)]))
+    }
+
+    /// All fields chunk by chunk, including the ones outside the board, so only for changes that
+    /// apply to every field.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Field> + '_ {
+        self.chunks.iter_mut().flat_map(|c| c.iter_mut())
+    }
+
+    /// The coordinates of the chunks which overlap the fields from `min` to `max` inclusive.
+    pub fn chunks_in(&self, min: (i16, i16), max: (i16, i16)) -> impl Iterator<Item = (i16, i16)> {
+        let chunk = |x: i16, y: i16| (x.max(0) / CHUNK_SIZE, y.max(0) / CHUNK_SIZE);
+        let (x_s, y_s) = chunk(min.0, min.1);
+        let (x_e, y_e) = chunk(max.0.min(self.width - 1), max.1.min(self.height - 1));
+        (y_s..=y_e).flat_map(move |cy| (x_s..=x_e).map(move |cx| (cx, cy)))
+    }
+
+    /// The positions of the fields in the chunk at (cx, cy), which lie on the board.
+    pub fn chunk_fields(&self, (cx, cy): (i16, i16)) -> impl Iterator<Item = (i16, i16)> {
+        let (x_s, y_s) = (cx * CHUNK_SIZE, cy * CHUNK_SIZE);
+        let x_e = (x_s + CHUNK_SIZE).min(self.width);
+        let y_e = (y_s + CHUNK_SIZE).min(self.height);
+        (y_s..y_e).flat_map(move |y| (x_s..x_e).map(move |x| (x, y)))
+    }
+
+    fn chunk_idx(&self, x: i16, y: i16) -> (usize, usize) {
+        let (cx, cy) = (x / CHUNK_SIZE, y / CHUNK_SIZE);
+        let (fx, fy) = (x % CHUNK_SIZE, y % CHUNK_SIZE);
+        let chunk = cy as usize * self.chunks_x as usize + cx as usize;
+        (chunk, fy as usize * CHUNK_SIZE as usize + fx as usize)
+    }
+}
+
+impl std::ops::Index<(i16, i16)> for Board {
+    type Output = Field;
+
+    fn index(&self, (x, y): (i16, i16)) -> &Self::Output {
+        debug_assert!(x >= 0 && x < self.width && y >= 0 && y < self.height);
+        let (chunk, idx) = self.chunk_idx(x, y);
+        &self.chunks[chunk][idx]
+    }
+}
+
+impl std::ops::IndexMut<(i16, i16)> for Board {
+    fn index_mut(&mut self, (x, y): (i16, i16)) -> &mut Self::Output {
+        debug_assert!(x >= 0 && x < self.width && y >= 0 && y < self.height);
+        let (chunk, idx) = self.chunk_idx(x, y);
+        &mut self.chunks[chunk][idx]
+    }
+}
+
+impl PartialEq for Board {
+    fn eq(&self, other: &Self) -> bool {
+        (self.width, self.height) == (other.width, other.height) && self.iter().eq(other.iter())
+    }
+}
+
+impl std::fmt::Debug for Board {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// Stored as a list of fields in row-major order, like before boards were chunked.
+impl serde::Serialize for Board {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut seq = serializer.serialize_seq(Some(self.len()))?;
+        for field in self.iter() {
+            seq.serialize_element(field)?;
+        }
+        seq.end()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::FieldState;
+
+    #[test]
+    fn row_major_order() {
+        let fields = (0..70 * 40)
+            .map(|i| Field::free((i % 9) as u8))
+            .collect::<Vec<_>>();
+        let board = Board::from_rows(70, 40, &fields).unwrap();
+
+        assert_eq!(board[(35, 1)], fields[70 + 35]);
+        assert!(board.iter().eq(fields.iter()));
+        assert_eq!(board.pos(70 * 33 + 65), (65, 33));
+
+        assert_eq!(Board::from_rows(0, 40, &[]), None);
+        assert_eq!(Board::from_rows(-70, -40, &fields), None);
+        assert_eq!(Board::from_rows(70, 39, &fields), None);
+    }
+
+    #[test]
+    fn chunks() {
+        let mut board = Board::new(70, 40, Field::free(0));
+        board[(69, 39)].set_state(FieldState::Mine);
+
+        let chunks = board.chunks_in((20, 20), (40, 100)).collect::<Vec<_>>();
+        assert_eq!(chunks, vec![(0, 0), (1, 0), (0, 1), (1, 1)]);
+        assert_eq!(board.chunk_fields((2, 1)).count(), 6 * 8);
+        assert!(board
+            .chunk_fields((2, 1))
+            .any(|p| board[p].state() == FieldState::Mine));
+    }
+}
@@ -0,0 +1,140 @@
+//! A single file with everything that is stored, to move it to another device or back it up.
+
+use std::collections::BTreeMap;
+
+use serde_derive::{Deserialize, Serialize};
+
+use crate::profiles::{Profiles, PROFILES_KEY};
+use crate::storage::Storage;
+use crate::Minesweeper;
+
+/// Incremented when bundles change in a way older versions can't read.
+pub const VERSION: u32 = 1;
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bundle {
+    pub version: u32,
+    /// The stored values by their key: the list of profiles and the settings, stats, records and
+    /// running game of every profile.
+    values: BTreeMap<String, String>,
+}
+
+impl Bundle {
+    pub fn collect(storage: &(impl Storage + ?Sized), profiles: &Profiles) -> Self {
+        let keys = std::iter::once(PROFILES_KEY.to_string()).chain(profiles.keys());
+        let values = keys
+            .filter_map(|key| Some((key.clone(), storage.get_string(&key)?)))
+            .collect();
+        Self {
+            version: VERSION,
+            values,
+        }
+    }
+
+    /// Reads a bundle, `None` if the text is something else.
+    pub fn parse(text: &str) -> Option<Self> {
+        ron::from_str(text).ok()
+    }
+
+    /// Whether the bundle was written by this or an older version.
+    pub fn is_supported(&self) -> bool {
+        self.version <= VERSION
+    }
+
+    pub fn to_ron(&self) -> String {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()).unwrap_or_default()
+    }
+
+    /// Reads every value like it's read when loaded, so a bundle that would lose or break a
+    /// profile isn't restored at all.
+    pub fn check(&self) -> Result<(), String> {
+        let profiles = self
+            .values
+            .get(PROFILES_KEY)
+            .ok_or_else(|| format!("{PROFILES_KEY} is missing"))?;
+        let profiles = ron::from_str::<Profiles>(profiles).map_err(|e| e.to_string())?;
+        let keys = profiles.keys().collect::<Vec<_>>();
+        for (key, value) in self.values.iter() {
+            if key == PROFILES_KEY {
+                continue;
+            }
+            if !keys.contains(key) {
+                return Err(format!("{key} isn't a profile"));
+            }
+            Minesweeper::check(value).map_err(|e| format!("{key}: {e}"))?;
+        }
+        Ok(())
+    }
+
+    /// Writes the values to `storage`, replacing the stored profiles of the same name.
+    pub fn restore(self, storage: &mut (impl Storage + ?Sized)) {
+        for (key, value) in self.values {
+            storage.set_string(&key, value);
+        }
+        storage.flush();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::profiles;
+    use crate::storage::{get_value, set_value};
+
+    impl Storage for HashMap<String, String> {
+        fn get_string(&self, key: &str) -> Option<String> {
+            self.get(key).cloned()
+        }
+
+        fn set_string(&mut self, key: &str, value: String) {
+            self.insert(key.to_string(), value);
+        }
+    }
+
+    #[test]
+    fn roundtrip() {
+        let mut profiles = Profiles::default();
+        profiles.add("Alice");
+        let mut storage = HashMap::new();
+        set_value(&mut storage, PROFILES_KEY, &profiles);
+        set_value(&mut storage, &profiles::key(None), &1u8);
+        set_value(&mut storage, &profiles::key(Some("Alice")), &2u8);
+        set_value(&mut storage, "unrelated", &3u8);
+
+        let bundle = Bundle::collect(&storage, &profiles);
+        let bundle = Bundle::parse(&bundle.to_ron()).unwrap();
+        assert!(bundle.is_supported());
+
+        let mut other = HashMap::new();
+        bundle.restore(&mut other);
+        assert_eq!(get_value(&other, PROFILES_KEY), Some(profiles));
+        assert_eq!(get_value(&other, &profiles::key(Some("Alice"))), Some(2u8));
+        assert_eq!(get_value::<u8>(&other, "unrelated"), None);
+
+        assert_eq!(Bundle::parse("Date,Level,Time"), None);
+    }
+
+    #[test]
+    fn check() {
+        let mut profiles = Profiles::default();
+        profiles.add("Alice");
+        let mut storage = HashMap::new();
+        set_value(&mut storage, PROFILES_KEY, &profiles);
+        set_value(&mut storage, &profiles::key(None), &Minesweeper::default());
+        let bundle = Bundle::collect(&storage, &profiles);
+        assert_eq!(bundle.check(), Ok(()));
+
+        let state = storage[&profiles::key(None)].clone();
+        assert!(state.contains("width:20"));
+        let mut broken = Bundle::collect(&storage, &profiles);
+        let value = state.replacen("width:20", "width:21", 1);
+        broken.values.insert(profiles::key(Some("Alice")), value);
+        assert!(broken.check().is_err());
+
+        let mut unrelated = Bundle::collect(&storage, &profiles);
+        unrelated.values.insert("unrelated".to_string(), state);
+        assert!(unrelated.check().is_err());
+    }
+}
@@ -0,0 +1,317 @@
+//! Statistics and the game history as CSV or JSON, to analyze them in other tools, and saving
+//! or copying the exported files and images.
+
+use egui::ColorImage;
+use serde_derive::Serialize;
+
+use crate::history::History;
+use crate::records::format_date;
+use crate::stats::Stats;
+use crate::Difficulty;
+
+const DIFFICULTIES: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Csv,
+    Json,
+}
+
+/// Returns the files to save, named by their suffix.
+pub fn export(format: Format, stats: &Stats, history: &History) -> Vec<(&'static str, String)> {
+    match format {
+        Format::Csv => vec![
+            ("stats.csv", stats_csv(stats)),
+            ("games.csv", games_csv(history)),
+        ],
+        Format::Json => vec![("stats.json", json(stats, history))],
+    }
+}
+
+fn stats_csv(stats: &Stats) -> String {
+    let mut csv =
+        "difficulty,played,won,lost,abandoned,win_rate,average_time_ms,bbbv_per_sec,ioe\n"
+            .to_string();
+    for difficulty in DIFFICULTIES {
+        let s = stats.get(difficulty);
+        let win_rate = s.win_rate().map(|r| format!("{r:.1}")).unwrap_or_default();
+        let average_time = s.average_time().map(|t| t.as_millis().to_string());
+        let bbbv_per_sec = s.average_bbbv_per_sec().map(|r| format!("{r:.3}"));
+        let ioe = s.average_ioe().map(|r| format!("{r:.3}"));
+        csv += &format!(
+            "{difficulty},{},{},{},{},{win_rate},{},{},{}\n",
+            s.played(),
+            s.won,
+            s.lost,
+            s.abandoned,
+            average_time.unwrap_or_default(),
+            bbbv_per_sec.unwrap_or_default(),
+            ioe.unwrap_or_default(),
+        );
+    }
+    csv
+}
+
+fn games_csv(history: &History) -> String {
+    let mut csv =
+        "date,difficulty,unambigous,result,time_ms,bbbv,clicks,wasted,solver_hints,seed\n"
+            .to_string();
+    for entry in history.entries() {
+        let m = entry.metrics;
+        let date = entry.date.map(format_date).unwrap_or_default();
+        let seed = entry.seed.map(|s| format!("{s:016x}"));
+        csv += &format!(
+            "{date},{},{},{},{},{},{},{},{},{}\n",
+            entry.difficulty,
+            entry.unambigous,
+            entry.result,
+            m.time.as_millis(),
+            m.bbbv,
+            m.clicks,
+            m.wasted,
+            m.solver_hints,
+            seed.unwrap_or_default(),
+        );
+    }
+    csv
+}
+
+#[derive(Serialize)]
+struct JsonExport {
+    stats: Vec<JsonStats>,
+    games: Vec<JsonGame>,
+}
+
+#[derive(Serialize)]
+struct JsonStats {
+    difficulty: String,
+    played: u32,
+    won: u32,
+    lost: u32,
+    abandoned: u32,
+    win_rate: Option<f32>,
+    average_time_ms: Option<u128>,
+    bbbv_per_sec: Option<f32>,
+    ioe: Option<f32>,
+}
+
+#[derive(Serialize)]
+struct JsonGame {
+    date: Option<String>,
+    difficulty: String,
+    unambigous: bool,
+    result: String,
+    time_ms: u128,
+    bbbv: u32,
+    clicks: u32,
+    wasted: u32,
+    solver_hints: u32,
+    seed: Option<String>,
+}
+
+fn json(stats: &Stats, history: &History) -> String {
+    let stats = DIFFICULTIES.map(|difficulty| {
+        let s = stats.get(difficulty);
+        JsonStats {
+            difficulty: difficulty.to_string(),
+            played: s.played(),
+            won: s.won,
+            lost: s.lost,
+            abandoned: s.abandoned,
+            win_rate: s.win_rate(),
+            average_time_ms: s.average_time().map(|t| t.as_millis()),
+            bbbv_per_sec: s.average_bbbv_per_sec(),
+            ioe: s.average_ioe(),
+        }
+    });
+    let games = history.entries().map(|entry| {
+        let m = entry.metrics;
+        JsonGame {
+            date: entry.date.map(format_date),
+            difficulty: entry.difficulty.to_string(),
+            unambigous: entry.unambigous,
+            result: entry.result.to_string(),
+            time_ms: m.time.as_millis(),
+            bbbv: m.bbbv,
+            clicks: m.clicks,
+            wasted: m.wasted,
+            solver_hints: m.solver_hints,
+            seed: entry.seed.map(|s| format!("{s:016x}")),
+        }
+    });
+    let export = JsonExport {
+        stats: stats.into(),
+        games: games.collect(),
+    };
+    serde_json::to_string(&export).expect("the export only contains strings and numbers")
+}
+
+/// Asks where to save the file, returns the path it was saved to, or `None` if the player
+/// cancelled.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save_file(data: &[u8], name: &str, _mime: &str) -> Result<Option<String>, String> {
+    let Some(path) = rfd::FileDialog::new().set_file_name(name).save_file() else {
+        return Ok(None);
+    };
+    std::fs::write(&path, data).map_err(|e| e.to_string())?;
+    Ok(Some(path.display().to_string()))
+}
+
+/// Downloads the file through a temporary link, returns its name.
+#[cfg(target_arch = "wasm32")]
+pub fn save_file(data: &[u8], name: &str, mime: &str) -> Result<Option<String>, String> {
+    use wasm_bindgen::closure::Closure;
+    use wasm_bindgen::JsCast;
+
+    let window = web_sys::window().ok_or("no window")?;
+    let document = window.document().ok_or("no document")?;
+    let res = blob(data, mime)
+        .and_then(|blob| web_sys::Url::create_object_url_with_blob(&blob))
+        .and_then(|url| {
+            let link = document.create_element("a")?;
+            link.set_attribute("href", &url)?;
+            link.set_attribute("download", name)?;
+            let click = web_sys::MouseEvent::new("click")?;
+            link.dispatch_event(&click)?;
+            // some browsers only start reading the blob after the click was handled
+            let revoke = Closure::once_into_js(move || {
+                let _ = web_sys::Url::revoke_object_url(&url);
+            });
+            window
+                .set_timeout_with_callback_and_timeout_and_arguments_0(
+                    revoke.unchecked_ref(),
+                    REVOKE_DELAY_MS,
+                )
+                .map(|_| ())
+        });
+    res.map(|()| Some(name.to_string()))
+        .map_err(|e| format!("{e:?}"))
+}
+
+/// How long the link of a download stays valid.
+#[cfg(target_arch = "wasm32")]
+const REVOKE_DELAY_MS: i32 = 40_000;
+
+#[cfg(target_arch = "wasm32")]
+fn blob(data: &[u8], mime: &str) -> Result<web_sys::Blob, wasm_bindgen::JsValue> {
+    let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(data));
+    let mut options = web_sys::BlobPropertyBag::new();
+    options.type_(mime);
+    web_sys::Blob::new_with_u8_array_sequence_and_options(&parts, &options)
+}
+
+/// Copies images to the clipboard of the system.
+#[derive(Default)]
+pub struct Clipboard {
+    /// On Linux the copied image is gone once the clipboard is closed, so it's kept open.
+    #[cfg(not(target_arch = "wasm32"))]
+    inner: Option<arboard::Clipboard>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Clipboard {
+    pub fn copy_image(&mut self, image: &ColorImage) -> Result<(), String> {
+        let clipboard = match &mut self.inner {
+            Some(clipboard) => clipboard,
+            None => {
+                let clipboard = arboard::Clipboard::new().map_err(|e| e.to_string())?;
+                self.inner.insert(clipboard)
+            }
+        };
+        let bytes = image.pixels.iter().flat_map(|c| c.to_srgba_unmultiplied());
+        let data = arboard::ImageData {
+            width: image.width(),
+            height: image.height(),
+            bytes: bytes.collect::<Vec<_>>().into(),
+        };
+        clipboard.set_image(data).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Clipboard {
+    /// Browsers only accept PNG images. The copy finishes in the background, failures are only
+    /// logged.
+    pub fn copy_image(&mut self, image: &ColorImage) -> Result<(), String> {
+        use wasm_bindgen::closure::Closure;
+        use wasm_bindgen::JsValue;
+
+        let png = crate::snapshot::encode_png(image);
+        let items = blob(&png, "image/png").and_then(|blob| {
+            let record = js_sys::Object::new();
+            js_sys::Reflect::set(&record, &"image/png".into(), &blob)?;
+            clipboard::ClipboardItem::new(&record)
+        });
+        let promise = items
+            .and_then(|item| clipboard::write(&js_sys::Array::of1(&item)))
+            .map_err(|e| format!("{e:?}"))?;
+        let on_error = Closure::once(|e: JsValue| log::warn!("failed to copy the image: {e:?}"));
+        let _ = promise.catch(&on_error);
+        on_error.forget();
+        Ok(())
+    }
+}
+
+/// The clipboard API of the browser, which `web_sys` only offers behind a flag.
+#[cfg(target_arch = "wasm32")]
+mod clipboard {
+    use wasm_bindgen::prelude::*;
+
+    #[wasm_bindgen]
+    extern "C" {
+        pub type ClipboardItem;
+
+        #[wasm_bindgen(constructor, catch)]
+        pub fn new(items: &js_sys::Object) -> Result<ClipboardItem, JsValue>;
+
+        #[wasm_bindgen(js_namespace = ["navigator", "clipboard"], catch)]
+        pub fn write(items: &js_sys::Array) -> Result<js_sys::Promise, JsValue>;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::history::{Entry, GameResult};
+    use crate::stats::{Metrics, Outcome};
+
+    #[test]
+    fn csv_and_json() {
+        let metrics = Metrics {
+            time: Duration::from_secs(30),
+            bbbv: 45,
+            clicks: 60,
+            wasted: 0,
+            solver_hints: 0,
+        };
+        let mut stats = Stats::default();
+        stats.record(Difficulty::Easy, Outcome::Won(metrics));
+        stats.record(Difficulty::Easy, Outcome::Lost);
+        let mut history = History::default();
+        history.push(Entry {
+            date: Some(0),
+            difficulty: Difficulty::Medium,
+            unambigous: true,
+            seed: Some(0xff),
+            result: GameResult::Won,
+            metrics,
+        });
+
+        let files = export(Format::Csv, &stats, &history);
+        let stats_csv = files[0].1.lines().collect::<Vec<_>>();
+        assert_eq!(stats_csv[1], "Easy,2,1,1,0,50.0,30000,1.500,0.750");
+        assert_eq!(stats_csv[2], "Medium,0,0,0,0,,,,");
+        let games_csv = files[1].1.lines().collect::<Vec<_>>();
+        assert_eq!(
+            games_csv[1],
+            "1970-01-01,Medium,true,Won,30000,45,60,0,0,00000000000000ff"
+        );
+
+        let json = &export(Format::Json, &stats, &history)[0].1;
+        assert!(json.contains(r#""win_rate":50.0,"average_time_ms":30000,"bbbv_per_sec":1.5"#));
+        assert!(json.contains(r#"{"difficulty":"Hard","played":0,"won":0,"lost":0,"abandoned":0,"win_rate":null,"average_time_ms":null,"bbbv_per_sec":null,"ioe":null}"#));
+        assert!(json.contains(r#""games":[{"date":"1970-01-01","difficulty":"Medium","unambigous":true,"result":"Won","time_ms":30000,"bbbv":45,"clicks":60,"wasted":0,"solver_hints":0,"seed":"00000000000000ff"}]"#));
+    }
+}
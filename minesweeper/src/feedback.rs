@@ -0,0 +1,87 @@
+//! Feedback on events of the game besides drawing them, which can be supplied by the embedding
+//! app.
+//!
+//! The events are the sound events of the game: they are emitted by the app after every move
+//! instead of by [`Game`](crate::Game) itself, since games are also played for replays, ghosts
+//! and the solver, which must stay silent. The same events drive the vibration and the sounds of
+//! the `audio` feature.
+
+use std::time::Duration;
+
+/// Something that happened in the game.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Event {
+    /// A single field was revealed.
+    Reveal,
+    /// Many fields were revealed at once, by opening an empty area.
+    Cascade,
+    /// A hint was placed or removed by the player.
+    Flag,
+    /// The neighbors of a number were revealed.
+    Chord,
+    /// A mine was revealed.
+    Explosion,
+    Win,
+}
+
+/// Revealing at least this many fields at once is a cascade.
+pub const CASCADE_FIELDS: usize = 10;
+
+impl Event {
+    /// How long the device vibrates, a short pulse for flags and a long one for explosions.
+    pub fn vibration(self) -> Option<Duration> {
+        match self {
+            Event::Flag => Some(Duration::from_millis(30)),
+            Event::Explosion => Some(Duration::from_millis(400)),
+            _ => None,
+        }
+    }
+}
+
+pub trait Feedback {
+    fn event(&mut self, event: Event);
+
+    /// Reads the text aloud, replacing what is still being read.
+    fn speak(&mut self, _text: &str) {}
+}
+
+/// Vibrates the device through the Vibration API of the browser, does nothing outside of it.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Vibration;
+
+impl Feedback for Vibration {
+    fn event(&mut self, event: Event) {
+        if let Some(duration) = event.vibration() {
+            vibrate(duration);
+        }
+    }
+}
+
+/// Reads the text aloud through the speech synthesis of the browser, does nothing outside of it.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn speak(_text: &str) {}
+
+#[cfg(target_arch = "wasm32")]
+pub fn speak(text: &str) {
+    let Some(window) = web_sys::window() else { return };
+    let Ok(synthesis) = window.speech_synthesis() else { return };
+    let Ok(utterance) = web_sys::SpeechSynthesisUtterance::new_with_text(text) else { return };
+    synthesis.cancel();
+    synthesis.speak(&utterance);
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn vibrate(_duration: Duration) {}
+
+#[cfg(target_arch = "wasm32")]
+fn vibrate(duration: Duration) {
+    let Some(window) = web_sys::window() else { return };
+    let navigator = window.navigator();
+    let Ok(user_agent) = navigator.user_agent() else { return };
+    let parser = woothee::parser::Parser::new();
+    let Some(res) = parser.parse(&user_agent) else { return };
+    if res.vendor != "Apple" {
+        navigator.vibrate_with_duration(duration.as_millis() as u32);
+        log::info!("{res:?}");
+    }
+}
@@ -1,11 +1,14 @@
-use rand::Rng;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+#[cfg(feature = "rayon")]
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 
 use crate::combination_iter::CombinationIter;
 use crate::stackvec::StackVec;
 use crate::{FieldState, Game, Visibility};
 
 #[cfg(test)]
-mod test;
+pub(crate) mod test;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 enum Error {
@@ -44,13 +47,13 @@ impl std::error::Error for Error {}
 //             if x == mx && y == my {
 //                 write!(f, "\x1b[1;7;34m")?;
 //             } else {
-//                 match field.visibility {
+//                 match field.visibility() {
 //                     Visibility::Hide => write!(f, "\x1b[1;7;90m")?,
 //                     Visibility::Hint => write!(f, "\x1b[1;7;33m")?,
 //                     Visibility::Show => write!(f, "\x1b[1;7;92m")?,
 //                 };
 //             }
-//             match field.state {
+//             match field.state() {
 //                 FieldState::Free(n) if n == 0 => write!(f, "  ")?,
 //                 FieldState::Free(n) => write!(f, " {n}")?,
 //                 FieldState::Mine => write!(f, " *")?,
@@ -71,39 +74,22 @@ enum Solve {
 
 impl Game {
     pub fn is_solved(&self) -> bool {
-        for f in self.fields.iter() {
-            if let FieldState::Free(_) = f.state {
-                if f.visibility != Visibility::Show {
-                    return false;
-                }
-            }
-        }
-
-        true
+        self.counts().hidden_free == 0
     }
 
+    /// Places the mines at random, the same board is generated for the same seed and size.
     pub fn gen_board(&mut self) {
-        let mut rng = rand::thread_rng();
+        let seed = *self.seed.get_or_insert_with(rand::random);
+        let mut rng = StdRng::seed_from_u64(seed);
         let mut available_indices = self.fields.len();
 
         for _ in 0..self.num_mines {
             let mut available_idx = rng.gen_range(0..available_indices);
-            for (actual_index, f) in self.fields.iter_mut().enumerate() {
-                if f.state != FieldState::Mine {
+            for actual_index in 0..self.fields.len() {
+                let (x, y) = self.fields.pos(actual_index);
+                if self[(x, y)].state() != FieldState::Mine {
                     if available_idx == 0 {
-                        f.state = FieldState::Mine;
-
-                        let x = (actual_index % self.width as usize) as i16;
-                        let y = (actual_index / self.width as usize) as i16;
-
-                        self.increment_field(x - 1, y - 1);
-                        self.increment_field(x - 1, y + 0);
-                        self.increment_field(x - 1, y + 1);
-                        self.increment_field(x + 0, y - 1);
-                        self.increment_field(x + 0, y + 1);
-                        self.increment_field(x + 1, y - 1);
-                        self.increment_field(x + 1, y + 0);
-                        self.increment_field(x + 1, y + 1);
+                        self.place_mine(x, y);
                         break;
                     }
                     available_idx -= 1;
@@ -114,6 +100,85 @@ impl Game {
         }
     }
 
+    /// Moves the mines on and around (x, y) to random fields further away, in a single pass. On
+    /// boards too dense for that some mines are left in place, but never the one on (x, y).
+    pub fn clear_opening(&mut self, x: i16, y: i16) {
+        let seed = self.seed.unwrap_or_default();
+        let mut rng = StdRng::seed_from_u64(seed.rotate_left(32));
+        let near = |(fx, fy): (i16, i16)| (fx - x).abs() <= 1 && (fy - y).abs() <= 1;
+        let mut free = (0..self.fields.len())
+            .map(|i| self.fields.pos(i))
+            .filter(|&pos| self[pos].state() != FieldState::Mine && !near(pos))
+            .collect::<Vec<_>>();
+
+        'outer: for ny in y - 1..=y + 1 {
+            for nx in x - 1..=x + 1 {
+                if !self.is_in_bounds(nx, ny) || self[(nx, ny)].state() != FieldState::Mine {
+                    continue;
+                }
+                if free.is_empty() {
+                    break 'outer;
+                }
+
+                let (fx, fy) = free.swap_remove(rng.gen_range(0..free.len()));
+                self.remove_mine(nx, ny);
+                self.place_mine(fx, fy);
+            }
+        }
+
+        // the mine of the clicked field is swapped with a neighbor if nothing else is free
+        if self[(x, y)].state() == FieldState::Mine {
+            let cleared = (0..self.fields.len())
+                .map(|i| self.fields.pos(i))
+                .filter(|&pos| near(pos) && self[pos].state() != FieldState::Mine)
+                .collect::<Vec<_>>();
+            if !cleared.is_empty() {
+                let (fx, fy) = cleared[rng.gen_range(0..cleared.len())];
+                self.remove_mine(x, y);
+                self.place_mine(fx, fy);
+            }
+        }
+    }
+
+    /// Checks random boards on all cores, until one that can be solved from (x, y) without
+    /// guessing is found. Returns false if `cancel` was set before.
+    #[cfg(feature = "rayon")]
+    pub fn gen_unambigous(
+        &mut self,
+        x: i16,
+        y: i16,
+        checked: &AtomicU32,
+        cancel: &AtomicBool,
+    ) -> bool {
+        use rayon::prelude::*;
+
+        let batch = 4 * rayon::current_num_threads();
+        loop {
+            if cancel.load(Ordering::Relaxed) {
+                return false;
+            }
+            let found = (0..batch)
+                .into_par_iter()
+                .map(|_| {
+                    let mut board = self.clone();
+                    board.clear_board();
+                    board.seed = Some(rand::random());
+                    board.gen_board();
+                    board.clear_opening(x, y);
+                    board
+                })
+                .find_any(|board| {
+                    checked.fetch_add(1, Ordering::Relaxed);
+                    board.is_unambigous(x, y)
+                });
+
+            if let Some(board) = found {
+                *self = board;
+                return true;
+            }
+        }
+    }
+
     pub fn is_unambigous(&self, x: i16, y: i16) -> bool {
         let mut board = self.clone();
         board.validate_board(x, y) == Ok(())
@@ -144,7 +209,7 @@ impl Game {
             loop {
                 for y in 0..board.height {
                     for x in 0..board.width {
-                        if board[(x, y)].visibility == Visibility::Show {
+                        if board[(x, y)].visibility() == Visibility::Show {
                             board.solve_board(x, y, true)?;
                             if board.is_solved() {
                                 return Ok(());
@@ -174,8 +239,8 @@ impl Game {
         for y in y_s..y_e {
             for x in x_s..x_e {
                 let field = self[(x, y)];
-                if field.visibility == Visibility::Show {
-                    if let FieldState::Free(neighbors) = field.state {
+                if field.visibility() == Visibility::Show {
+                    if let FieldState::Free(neighbors) = field.state() {
                         let hidden_adjacents = self.hidden_adjacents(x, y);
                         let hinted_adjacents = self.hinted_adjacents(x, y);
                         let num_missing_neighbors = neighbors - hinted_adjacents.num();
@@ -209,7 +274,7 @@ impl Game {
                 for fi in 0..num_hidden {
                     if combination[fi as usize] {
                         let (x_off, y_off) = offsets[fi as usize];
-                        board[(x + x_off, y + y_off)].visibility = Visibility::Hint;
+                        board.set_visibility(x + x_off, y + y_off, Visibility::Hint);
                     }
                 }
 
@@ -222,8 +287,8 @@ impl Game {
                 for fy in y_s..y_e {
                     for fx in x_s..x_e {
                         let field = board[(fx, fy)];
-                        if field.visibility == Visibility::Show {
-                            if let FieldState::Free(neighbors) = field.state {
+                        if field.visibility() == Visibility::Show {
+                            if let FieldState::Free(neighbors) = field.state() {
                                 let hinted_adjacents = board.hinted_adjacents(fx, fy);
                                 if hinted_adjacents.num() > neighbors {
                                     // println!("invalid");
@@ -234,7 +299,7 @@ impl Game {
                     }
                 }
 
-                if board.open_mine_count() == 0 {
+                if board.mines_remaining() == 0 {
                     // If there are no mines left there should be no missing neighbors
                     for y in 0..board.height {
                         for x in 0..board.width {
@@ -243,8 +308,8 @@ impl Game {
                             }
 
                             let field = board[(x, y)];
-                            if field.visibility == Visibility::Show {
-                                if let FieldState::Free(neighbors) = field.state {
+                            if field.visibility() == Visibility::Show {
+                                if let FieldState::Free(neighbors) = field.state() {
                                     let hinted_adjacents = board.hinted_adjacents(x, y);
                                     if hinted_adjacents.num() < neighbors {
                                         continue 'combinations;
@@ -301,20 +366,20 @@ impl Game {
             return Ok(());
         }
 
-        let field = &mut self[(x, y)];
-        match field.visibility {
+        let field = self[(x, y)];
+        match field.visibility() {
             Visibility::Hide => {
-                if field.state == FieldState::Mine {
+                if field.state() == FieldState::Mine {
                     return Err(Error::Invalid);
                 }
-                field.visibility = Visibility::Show;
+                self.set_visibility(x, y, Visibility::Show);
             }
             Visibility::Hint => return Ok(()),
             Visibility::Show if force => (),
             Visibility::Show => return Ok(()),
         }
 
-        match field.state {
+        match field.state() {
             FieldState::Free(0) => {
                 self.solve_board(x - 1, y - 1, false)?;
                 self.solve_board(x + 0, y - 1, false)?;
@@ -363,17 +428,56 @@ impl Game {
             return;
         }
 
-        let field = &mut self[(x, y)];
+        if self[(x, y)].visibility() == Visibility::Hide {
+            self.set_visibility(x, y, Visibility::Hint);
+        }
+    }
 
-        if field.visibility == Visibility::Hide {
-            field.visibility = Visibility::Hint;
+    pub fn place_mine(&mut self, x: i16, y: i16) {
+        self.counts = None;
+        self[(x, y)].set_state(FieldState::Mine);
+        for ny in y - 1..=y + 1 {
+            for nx in x - 1..=x + 1 {
+                if (nx, ny) != (x, y) {
+                    self.increment_field(nx, ny);
+                }
+            }
+        }
+    }
+
+    fn remove_mine(&mut self, x: i16, y: i16) {
+        self.counts = None;
+        let mut neighbors = 0;
+        for ny in y - 1..=y + 1 {
+            for nx in x - 1..=x + 1 {
+                if self.is_in_bounds(nx, ny) && self[(nx, ny)].state() == FieldState::Mine {
+                    neighbors += 1;
+                }
+            }
+        }
+        // the mine itself was counted as well
+        self[(x, y)].set_state(FieldState::Free(neighbors - 1));
+        for ny in y - 1..=y + 1 {
+            for nx in x - 1..=x + 1 {
+                if (nx, ny) != (x, y) {
+                    self.decrement_field(nx, ny);
+                }
+            }
         }
     }
 
     fn increment_field(&mut self, x: i16, y: i16) {
         if self.is_in_bounds(x, y) {
-            if let FieldState::Free(neighbors) = &mut self[(x, y)].state {
-                *neighbors += 1;
+            if let FieldState::Free(neighbors) = self[(x, y)].state() {
+                self[(x, y)].set_state(FieldState::Free(neighbors + 1));
+            }
+        }
+    }
+
+    fn decrement_field(&mut self, x: i16, y: i16) {
+        if self.is_in_bounds(x, y) {
+            if let FieldState::Free(neighbors) = self[(x, y)].state() {
+                self[(x, y)].set_state(FieldState::Free(neighbors - 1));
             }
         }
     }
@@ -396,7 +500,7 @@ impl Game {
             return false;
         }
 
-        self[(x, y)].visibility == Visibility::Hint
+        self[(x, y)].visibility() == Visibility::Hint
     }
 
     pub fn hidden_adjacents(&self, x: i16, y: i16) -> Adjacents {
@@ -417,7 +521,7 @@ impl Game {
             return false;
         }
 
-        self[(x, y)].visibility == Visibility::Hide
+        self[(x, y)].visibility() == Visibility::Hide
     }
 }
 
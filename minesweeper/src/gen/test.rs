@@ -1,18 +1,10 @@
 use super::*;
 
 fn place_mine(game: &mut Game, x: i16, y: i16) {
-    game[(x, y)].state = FieldState::Mine;
-    game.increment_field(x - 1, y - 1);
-    game.increment_field(x - 1, y + 0);
-    game.increment_field(x - 1, y + 1);
-    game.increment_field(x + 0, y - 1);
-    game.increment_field(x + 0, y + 1);
-    game.increment_field(x + 1, y - 1);
-    game.increment_field(x + 1, y + 0);
-    game.increment_field(x + 1, y + 1);
-}
-
-fn game(width: i16, height: i16) -> Game {
+    game.place_mine(x, y);
+}
+
+pub(crate) fn game(width: i16, height: i16) -> Game {
     Game::new(width, height, 0.0..1.0, crate::Difficulty::Easy, false)
 }
 
@@ -91,7 +83,7 @@ fn hidden_adjacents_1() {
 #[test]
 fn hidden_adjacents_2() {
     let mut game = game(5, 5);
-    game[(1, 1)].visibility = Visibility::Hint;
+    game.set_visibility(1, 1, Visibility::Hint);
 
     let hidden_adjacents = game.hidden_adjacents(0, 0);
     let values = hidden_adjacents.offsets();
@@ -119,7 +111,7 @@ fn hidden_adjacents_3() {
 #[test]
 fn hidden_adjacents_4() {
     let mut game = game(5, 5);
-    game[(3, 1)].visibility = Visibility::Hint;
+    game.set_visibility(3, 1, Visibility::Hint);
 
     let hidden_adjacents = game.hidden_adjacents(4, 0);
     let values = hidden_adjacents.offsets();
@@ -147,7 +139,7 @@ fn hidden_adjacents_5() {
 #[test]
 fn hidden_adjacents_6() {
     let mut game = game(5, 5);
-    game[(3, 3)].visibility = Visibility::Hint;
+    game.set_visibility(3, 3, Visibility::Hint);
 
     let hidden_adjacents = game.hidden_adjacents(4, 4);
     let values = hidden_adjacents.offsets();
@@ -175,7 +167,7 @@ fn hidden_adjacents_7() {
 #[test]
 fn hidden_adjacents_8() {
     let mut game = game(5, 5);
-    game[(1, 3)].visibility = Visibility::Hint;
+    game.set_visibility(1, 3, Visibility::Hint);
 
     let hidden_adjacents = game.hidden_adjacents(0, 4);
     let values = hidden_adjacents.offsets();
@@ -185,3 +177,74 @@ fn hidden_adjacents_8() {
     expected.push((1, 0));
     assert_eq!(values, expected);
 }
+
+#[test]
+fn same_seed_same_board() {
+    let mut a = game(16, 16);
+    a.num_mines = 40;
+    a.gen_board();
+
+    let mut b = game(16, 16);
+    b.num_mines = 40;
+    b.seed = a.seed;
+    b.gen_board();
+
+    assert_eq!(a.fields, b.fields);
+}
+
+#[test]
+fn counts_follow_visibility() {
+    let mut game = game(5, 5);
+    place_mine(&mut game, 2, 2);
+    game.num_mines = 1;
+    game.show_neighbors(0, 0);
+    game.hint_(2, 2);
+    game.hint_(4, 4);
+    game.hint_(4, 4);
+
+    let counted = game.counts.take();
+    assert_eq!(counted, Some(game.counts()));
+    assert_eq!(game.mines_remaining(), 0);
+    assert_eq!(game.num_shown(), 24);
+}
+
+#[test]
+fn clear_opening() {
+    let mut game = game(5, 5);
+    place_mine(&mut game, 0, 0);
+    place_mine(&mut game, 1, 1);
+    place_mine(&mut game, 4, 4);
+    game.num_mines = 3;
+
+    game.clear_opening(0, 0);
+    assert_eq!(game[(0, 0)].state(), FieldState::Free(0));
+    let mines = game.fields.iter().filter(|f| f.state() == FieldState::Mine);
+    assert_eq!(mines.count(), 3);
+
+    let mut expected = self::game(5, 5);
+    for (i, f) in game.fields.iter().enumerate() {
+        if f.state() == FieldState::Mine {
+            let (x, y) = game.fields.pos(i);
+            place_mine(&mut expected, x, y);
+        }
+    }
+    assert_eq!(game.fields, expected.fields);
+}
+
+#[test]
+fn clear_opening_dense() {
+    let mut game = game(3, 3);
+    for y in 0..3 {
+        for x in 0..3 {
+            if (x, y) != (0, 0) {
+                place_mine(&mut game, x, y);
+            }
+        }
+    }
+    game.num_mines = 8;
+
+    game.clear_opening(1, 1);
+    assert_eq!(game[(1, 1)].state(), FieldState::Free(8));
+    let mines = game.fields.iter().filter(|f| f.state() == FieldState::Mine);
+    assert_eq!(mines.count(), 8);
+}
@@ -0,0 +1,272 @@
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+
+use egui::{ComboBox, FontId, Grid, RichText, ScrollArea, Ui};
+use serde_derive::{Deserialize, Serialize};
+
+use crate::records::{format_date, hints_text};
+use crate::stats::Metrics;
+use crate::translations::Translations;
+use crate::{format_duration_precise, Difficulty};
+
+/// Number of games kept, the oldest ones are dropped first.
+const MAX_ENTRIES: usize = 1000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GameResult {
+    Won,
+    Lost,
+    Abandoned,
+}
+
+impl std::fmt::Display for GameResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameResult::Won => write!(f, "Won"),
+            GameResult::Lost => write!(f, "Lost"),
+            GameResult::Abandoned => write!(f, "Abandoned"),
+        }
+    }
+}
+
+/// A finished or abandoned game.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entry {
+    /// When the game ended in seconds since the unix epoch.
+    pub date: Option<u64>,
+    pub difficulty: Difficulty,
+    pub unambigous: bool,
+    pub seed: Option<u64>,
+    pub result: GameResult,
+    pub metrics: Metrics,
+}
+
+/// The last games of a single player, oldest first.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct History {
+    entries: VecDeque<Entry>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SortBy {
+    #[default]
+    Date,
+    Time,
+    BbbvPerSec,
+    Efficiency,
+}
+
+/// How the history table is sorted and filtered.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HistoryView {
+    pub sort_by: SortBy,
+    pub ascending: bool,
+    pub difficulty: Option<Difficulty>,
+    pub result: Option<GameResult>,
+}
+
+impl History {
+    pub fn push(&mut self, entry: Entry) {
+        if self.entries.len() >= MAX_ENTRIES {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+
+    pub fn entries(&self) -> impl DoubleEndedIterator<Item = &Entry> {
+        self.entries.iter()
+    }
+
+    /// The entries matching the filters of `view`, in its order.
+    pub fn view(&self, view: &HistoryView) -> Vec<&Entry> {
+        let mut entries = self
+            .entries
+            .iter()
+            .filter(|e| view.difficulty.is_none_or(|d| e.difficulty == d))
+            .filter(|e| view.result.is_none_or(|r| e.result == r))
+            .collect::<Vec<_>>();
+
+        fn cmp_f32(a: Option<f32>, b: Option<f32>) -> Ordering {
+            let (a, b) = (a.unwrap_or(-1.0), b.unwrap_or(-1.0));
+            a.total_cmp(&b)
+        }
+        entries.sort_by(|a, b| match view.sort_by {
+            SortBy::Date => a.date.cmp(&b.date),
+            SortBy::Time => a.metrics.time.cmp(&b.metrics.time),
+            SortBy::BbbvPerSec => cmp_f32(a.metrics.bbbv_per_sec(), b.metrics.bbbv_per_sec()),
+            SortBy::Efficiency => cmp_f32(a.metrics.efficiency(), b.metrics.efficiency()),
+        });
+        if !view.ascending {
+            entries.reverse();
+        }
+        entries
+    }
+
+    pub fn ui(&self, ui: &mut Ui, tr: &Translations, view: &mut HistoryView) {
+        let result_text = |result: Option<GameResult>| match result {
+            None => &*tr.all,
+            Some(GameResult::Won) => &*tr.won,
+            Some(GameResult::Lost) => &*tr.lost,
+            Some(GameResult::Abandoned) => &*tr.abandoned,
+        };
+
+        ui.horizontal(|ui| {
+            let selected = view.difficulty.map_or(&*tr.all, |d| tr.difficulty(d));
+            ComboBox::from_id_source("history_difficulty")
+                .selected_text(selected)
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut view.difficulty, None, &*tr.all);
+                    for d in [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard] {
+                        ui.selectable_value(&mut view.difficulty, Some(d), tr.difficulty(d));
+                    }
+                });
+            ComboBox::from_id_source("history_result")
+                .selected_text(result_text(view.result))
+                .show_ui(ui, |ui| {
+                    let results = [
+                        None,
+                        Some(GameResult::Won),
+                        Some(GameResult::Lost),
+                        Some(GameResult::Abandoned),
+                    ];
+                    for result in results {
+                        ui.selectable_value(&mut view.result, result, result_text(result));
+                    }
+                });
+        });
+        ui.add_space(8.0);
+
+        let entries = self.view(view);
+        ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+            Grid::new("history")
+                .num_columns(9)
+                .spacing([20.0, 8.0])
+                .striped(true)
+                .show(ui, |ui| {
+                    let headers = [
+                        (&*tr.date, Some(SortBy::Date)),
+                        ("", None),
+                        (&*tr.result, None),
+                        (&*tr.time, Some(SortBy::Time)),
+                        (&*tr.solver_hints, None),
+                        ("3BV", None),
+                        ("3BV/s", Some(SortBy::BbbvPerSec)),
+                        (&*tr.efficiency, Some(SortBy::Efficiency)),
+                        (&*tr.seed, None),
+                    ];
+                    for (header, sort_by) in headers {
+                        let mut text = header.to_string();
+                        if sort_by == Some(view.sort_by) {
+                            text += if view.ascending { " ⏶" } else { " ⏷" };
+                        }
+                        let text = RichText::new(text)
+                            .font(FontId::proportional(16.0))
+                            .strong();
+                        let Some(sort_by) = sort_by else {
+                            ui.label(text);
+                            continue;
+                        };
+                        let selected = view.sort_by == sort_by;
+                        if ui.selectable_label(selected, text).clicked() {
+                            if selected {
+                                view.ascending = !view.ascending;
+                            } else {
+                                view.sort_by = sort_by;
+                                // the fastest time is the best, but the highest rate
+                                view.ascending = sort_by == SortBy::Time;
+                            }
+                        }
+                    }
+                    ui.end_row();
+
+                    for entry in entries {
+                        let metrics = entry.metrics;
+                        let optional = |value: Option<f32>, suffix: &str| match value {
+                            Some(value) => format!("{value:.2}{suffix}"),
+                            None => "-".to_string(),
+                        };
+                        let cells = [
+                            entry.date.map_or("-".to_string(), format_date),
+                            tr.difficulty(entry.difficulty).to_string(),
+                            result_text(Some(entry.result)).to_string(),
+                            format_duration_precise(metrics.time),
+                            hints_text(metrics.solver_hints),
+                            metrics.bbbv.to_string(),
+                            optional(metrics.bbbv_per_sec(), ""),
+                            match metrics.efficiency() {
+                                Some(efficiency) => format!("{efficiency:.0}%"),
+                                None => "-".to_string(),
+                            },
+                            entry.seed.map_or("-".to_string(), |s| format!("{s:016x}")),
+                        ];
+                        for cell in cells {
+                            ui.label(RichText::new(cell).font(FontId::proportional(16.0)));
+                        }
+                        ui.end_row();
+                    }
+                });
+        });
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use std::time::Duration;
+
+    use super::*;
+
+    /// A game without a seed, which the tests of the modules that read the history share.
+    pub(crate) fn entry(date: u64, difficulty: Difficulty, result: GameResult, secs: u64) -> Entry {
+        Entry {
+            date: Some(date),
+            difficulty,
+            unambigous: false,
+            seed: None,
+            result,
+            metrics: Metrics {
+                time: Duration::from_secs(secs),
+                ..Metrics::default()
+            },
+        }
+    }
+
+    #[test]
+    fn sort_and_filter() {
+        let mut history = History::default();
+        history.push(entry(1, Difficulty::Easy, GameResult::Won, 30));
+        history.push(entry(2, Difficulty::Easy, GameResult::Lost, 10));
+        history.push(entry(3, Difficulty::Hard, GameResult::Won, 20));
+
+        let dates = |view| {
+            history
+                .view(&view)
+                .iter()
+                .map(|e| e.date.unwrap())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(dates(HistoryView::default()), [3, 2, 1]);
+        let view = HistoryView {
+            sort_by: SortBy::Time,
+            ascending: true,
+            result: Some(GameResult::Won),
+            ..HistoryView::default()
+        };
+        assert_eq!(dates(view), [3, 1]);
+        let view = HistoryView {
+            difficulty: Some(Difficulty::Easy),
+            ..HistoryView::default()
+        };
+        assert_eq!(dates(view), [2, 1]);
+    }
+
+    #[test]
+    fn rolling() {
+        let mut history = History::default();
+        for date in 0..MAX_ENTRIES as u64 + 5 {
+            history.push(entry(date, Difficulty::Easy, GameResult::Won, 1));
+        }
+        assert_eq!(history.entries().count(), MAX_ENTRIES);
+        assert_eq!(history.entries().next().unwrap().date, Some(5));
+    }
+}
@@ -0,0 +1,41 @@
+use egui::Color32;
+use serde_derive::{Deserialize, Serialize};
+
+pub const PLAYER_COLORS: [Color32; 2] = [
+    Color32::from_rgb(0xf0, 0xc0, 0x30),
+    Color32::from_rgb(0x30, 0xa0, 0xf0),
+];
+
+/// Two players alternating moves on the same board.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HotSeat {
+    /// The player whose turn it is, which is the loser once a mine was hit.
+    pub turn: u8,
+    /// The player that placed the hint on each field.
+    hint_owners: Vec<u8>,
+}
+
+impl HotSeat {
+    pub fn new(len: usize) -> Self {
+        Self {
+            turn: 0,
+            hint_owners: vec![0; len],
+        }
+    }
+
+    pub fn next_turn(&mut self) {
+        self.turn = 1 - self.turn;
+    }
+
+    pub fn set_hint_owner(&mut self, idx: usize) {
+        self.hint_owners[idx] = self.turn;
+    }
+
+    pub fn hint_color(&self, idx: usize) -> Color32 {
+        PLAYER_COLORS[self.hint_owners[idx] as usize]
+    }
+
+    pub fn turn_color(&self) -> Color32 {
+        PLAYER_COLORS[self.turn as usize]
+    }
+}
@@ -0,0 +1,163 @@
+//! Reads the bests of other minesweeper clients, so players switching to this app keep them.
+//!
+//! Supported are the text replays of Viennasweeper (RAWVF) and stats exported as CSV, like the
+//! statistics of Minesweeper Arbiter. Only games on a board with the size and mine density of a
+//! difficulty of this app are imported, the classic beginner, intermediate and expert boards are
+//! smaller and would make for unfair bests.
+
+use std::time::Duration;
+
+use crate::records::{parse_date, Record};
+use crate::Difficulty;
+
+/// Parses the won games in the file.
+pub fn parse(text: &str) -> Vec<(Difficulty, Record)> {
+    if text.trim_start().starts_with("RawVF_Version") {
+        rawvf(text).into_iter().collect()
+    } else {
+        csv(text)
+    }
+}
+
+/// The difficulty with the size and mine density of the board.
+fn difficulty(width: i16, height: i16, num_mines: u16) -> Option<Difficulty> {
+    let difficulty = Difficulty::from_size(width, height)?;
+    let density = num_mines as f64 / (width as f64 * height as f64);
+    difficulty
+        .mine_density()
+        .contains(&density)
+        .then_some(difficulty)
+}
+
+/// Parses a time in seconds, like `12.345`.
+fn time(text: &str) -> Option<Duration> {
+    let secs = text.trim().parse::<f64>().ok()?;
+    if secs <= 0.0 {
+        return None;
+    }
+    Duration::try_from_secs_f64(secs).ok()
+}
+
+fn record(time: Duration, date: Option<u64>, bbbv: Option<u32>) -> Record {
+    Record {
+        date,
+        bbbv: bbbv.unwrap_or(0),
+        ..Record::new(time)
+    }
+}
+
+/// A RAWVF replay starts with `Key: Value` lines, which end at the board.
+fn rawvf(text: &str) -> Option<(Difficulty, Record)> {
+    let header = text.lines().take_while(|l| !l.starts_with("Board:"));
+    let (mut width, mut height, mut num_mines) = (None, None, None);
+    let mut duration = None;
+    let mut date = None;
+    let mut bbbv = None;
+    for line in header {
+        let Some((key, value)) = line.split_once(':') else { continue };
+        match key.trim() {
+            "Width" => width = value.trim().parse().ok(),
+            "Height" => height = value.trim().parse().ok(),
+            "Mines" => num_mines = value.trim().parse().ok(),
+            "Time" => duration = time(value),
+            "Timestamp" => date = parse_date(value.trim()),
+            "BBBV" => bbbv = value.trim().parse().ok(),
+            _ => (),
+        }
+    }
+    let difficulty = difficulty(width?, height?, num_mines?)?;
+    Some((difficulty, record(duration?, date, bbbv)))
+}
+
+/// Finds the columns by the names in the first line, rows of lost games are skipped.
+fn csv(text: &str) -> Vec<(Difficulty, Record)> {
+    let mut lines = text.lines().filter(|l| !l.trim().is_empty());
+    let Some(header) = lines.next() else { return Vec::new() };
+    let separator = [',', ';', '\t']
+        .into_iter()
+        .max_by_key(|&c| header.matches(c).count())
+        .unwrap_or(',');
+    let columns = header
+        .split(separator)
+        .map(|c| c.trim().trim_matches('"').to_lowercase())
+        .collect::<Vec<_>>();
+    let column = |names: &[&str]| columns.iter().position(|c| names.contains(&c.as_str()));
+
+    let (Some(width_col), Some(height_col), Some(mines_col), Some(time_col)) = (
+        column(&["width", "w"]),
+        column(&["height", "h"]),
+        column(&["mines", "bombs"]),
+        column(&["time", "seconds", "time (s)"]),
+    ) else {
+        return Vec::new();
+    };
+    let date_col = column(&["date", "timestamp"]);
+    let bbbv_col = column(&["3bv", "bbbv"]);
+    let won_col = column(&["won", "result", "status"]);
+
+    lines
+        .filter_map(|line| {
+            let cells = line
+                .split(separator)
+                .map(|c| c.trim().trim_matches('"'))
+                .collect::<Vec<_>>();
+            let cell = |col: Option<usize>| col.and_then(|c| cells.get(c).copied());
+            if let Some(won) = cell(won_col) {
+                let won = won.to_lowercase();
+                if !["won", "win", "yes", "true", "1"].contains(&won.as_str()) {
+                    return None;
+                }
+            }
+            let width = cell(Some(width_col))?.parse().ok()?;
+            let height = cell(Some(height_col))?.parse().ok()?;
+            let num_mines = cell(Some(mines_col))?.parse().ok()?;
+            let difficulty = difficulty(width, height, num_mines)?;
+            let duration = time(cell(Some(time_col))?)?;
+            let date = cell(date_col).and_then(parse_date);
+            let bbbv = cell(bbbv_col).and_then(|b| b.parse().ok());
+            Some((difficulty, record(duration, date, bbbv)))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rawvf_header() {
+        let text = "RawVF_Version: Rev6\nProgram: Viennasweeper\nLevel: Custom\nWidth: 40\n\
+            Height: 24\nMines: 205\nTimestamp: 2023-11-14 10:00:00\nTime: 95.12\nBBBV: 160\n\
+            Board:\n**...";
+        let records = parse(text);
+        assert_eq!(records.len(), 1);
+        let (difficulty, record) = &records[0];
+        assert_eq!(*difficulty, Difficulty::Hard);
+        assert_eq!(record.time, Duration::from_millis(95120));
+        assert_eq!(record.date, Some(1_699_920_000));
+        assert_eq!(record.bbbv, 160);
+
+        let expert = text
+            .replace("Width: 40", "Width: 30")
+            .replace("Height: 24", "Height: 16");
+        assert!(parse(&expert).is_empty());
+    }
+
+    #[test]
+    fn csv_stats() {
+        let text = "Date;Width;Height;Mines;Result;Time;3BV\n\
+            2023-11-14;20;14;35;Won;5.5;12\n\
+            2023-11-15;30;18;88;Lost;20.0;40\n\
+            2023-11-16;30;18;88;Won;30.25;45\n\
+            2023-11-17;30;16;99;Won;10.0;20\n\
+            2023-11-18;20;14;35;Won;1e30;20\n";
+        let records = parse(text);
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].0, Difficulty::Easy);
+        assert_eq!(records[0].1.time, Duration::from_millis(5500));
+        assert_eq!(records[1].0, Difficulty::Medium);
+        assert_eq!(records[1].1.bbbv, 45);
+
+        assert!(parse("no,columns\n1,2").is_empty());
+    }
+}
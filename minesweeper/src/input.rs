@@ -0,0 +1,605 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::time::Duration;
+
+use egui::{ComboBox, Event, FontId, Grid, InputState, Key, RichText, Ui};
+use serde_derive::{Deserialize, Serialize};
+
+use crate::translations::Translations;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Action {
+    MoveUp,
+    MoveRight,
+    MoveDown,
+    MoveLeft,
+    MoveUpLeft,
+    MoveUpRight,
+    MoveDownLeft,
+    MoveDownRight,
+    JumpLeft,
+    JumpRight,
+    JumpTop,
+    JumpBottom,
+    JumpCenter,
+    Reveal,
+    Hint,
+    Chord,
+    Restart,
+    Fullscreen,
+    Easy,
+    Medium,
+    Hard,
+}
+
+impl Action {
+    pub const ALL: [Action; 21] = [
+        Action::MoveUp,
+        Action::MoveRight,
+        Action::MoveDown,
+        Action::MoveLeft,
+        Action::MoveUpLeft,
+        Action::MoveUpRight,
+        Action::MoveDownLeft,
+        Action::MoveDownRight,
+        Action::JumpLeft,
+        Action::JumpRight,
+        Action::JumpTop,
+        Action::JumpBottom,
+        Action::JumpCenter,
+        Action::Reveal,
+        Action::Hint,
+        Action::Chord,
+        Action::Restart,
+        Action::Fullscreen,
+        Action::Easy,
+        Action::Medium,
+        Action::Hard,
+    ];
+}
+
+impl Action {
+    /// Whether the action repeats while its key is held.
+    fn repeats(self) -> bool {
+        matches!(
+            self,
+            Action::MoveUp
+                | Action::MoveRight
+                | Action::MoveDown
+                | Action::MoveLeft
+                | Action::MoveUpLeft
+                | Action::MoveUpRight
+                | Action::MoveDownLeft
+                | Action::MoveDownRight
+        )
+    }
+}
+
+/// Bindings for other hands and keyboards, which are added to the default ones.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Preset {
+    Arrows,
+    Ijkl,
+    Numpad,
+    LeftHand,
+    /// The keys of [`Preset::LeftHand`] on an AZERTY keyboard.
+    Zqsd,
+    /// The keys of [`Preset::Ijkl`] on a Dvorak keyboard.
+    Dvorak,
+}
+
+impl Preset {
+    pub const ALL: [Preset; 6] = [
+        Preset::Arrows,
+        Preset::Ijkl,
+        Preset::Numpad,
+        Preset::LeftHand,
+        Preset::Zqsd,
+        Preset::Dvorak,
+    ];
+
+    fn layout(self) -> Vec<(Binding, Action)> {
+        use Binding as B;
+
+        match self {
+            Preset::Arrows => vec![
+                (B::new(Key::Enter), Action::Reveal),
+                (B::new(Key::Insert), Action::Hint),
+                (B::new(Key::Delete), Action::Chord),
+            ],
+            Preset::Ijkl => vec![
+                (B::new(Key::I), Action::MoveUp),
+                (B::new(Key::L), Action::MoveRight),
+                (B::new(Key::K), Action::MoveDown),
+                (B::new(Key::J), Action::MoveLeft),
+                (B::new(Key::U), Action::MoveUpLeft),
+                (B::new(Key::O), Action::MoveUpRight),
+                (B::new(Key::N), Action::MoveDownLeft),
+                (B::new(Key::M), Action::MoveDownRight),
+                (B::new(Key::Space), Action::Reveal),
+                (B::new(Key::F), Action::Hint),
+                (B::new(Key::D), Action::Chord),
+            ],
+            Preset::Numpad => vec![
+                (B::new(Key::Num1), Action::MoveDownLeft),
+                (B::new(Key::Num2), Action::MoveDown),
+                (B::new(Key::Num3), Action::MoveDownRight),
+                (B::new(Key::Num4), Action::MoveLeft),
+                (B::new(Key::Num5), Action::Reveal),
+                (B::new(Key::Num6), Action::MoveRight),
+                (B::new(Key::Num7), Action::MoveUpLeft),
+                (B::new(Key::Num8), Action::MoveUp),
+                (B::new(Key::Num9), Action::MoveUpRight),
+                (B::new(Key::Num0), Action::Hint),
+                (B::new(Key::Minus), Action::Chord),
+            ],
+            Preset::LeftHand => vec![
+                (B::new(Key::W), Action::MoveUp),
+                (B::new(Key::D), Action::MoveRight),
+                (B::new(Key::S), Action::MoveDown),
+                (B::new(Key::A), Action::MoveLeft),
+                (B::new(Key::Q), Action::Reveal),
+                (B::new(Key::E), Action::Hint),
+                (B::new(Key::Q).shift(), Action::Chord),
+            ],
+            Preset::Zqsd => vec![
+                (B::new(Key::Z), Action::MoveUp),
+                (B::new(Key::D), Action::MoveRight),
+                (B::new(Key::S), Action::MoveDown),
+                (B::new(Key::Q), Action::MoveLeft),
+                (B::new(Key::A), Action::Reveal),
+                (B::new(Key::E), Action::Hint),
+                (B::new(Key::A).shift(), Action::Chord),
+            ],
+            Preset::Dvorak => vec![
+                (B::new(Key::C), Action::MoveUp),
+                (B::new(Key::N), Action::MoveRight),
+                (B::new(Key::T), Action::MoveDown),
+                (B::new(Key::H), Action::MoveLeft),
+                (B::new(Key::G), Action::MoveUpLeft),
+                (B::new(Key::R), Action::MoveUpRight),
+                (B::new(Key::B), Action::MoveDownLeft),
+                (B::new(Key::M), Action::MoveDownRight),
+                (B::new(Key::Space), Action::Reveal),
+                (B::new(Key::U), Action::Hint),
+                (B::new(Key::E), Action::Chord),
+            ],
+        }
+    }
+}
+
+/// A set of actions triggered in one frame.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Actions(u64);
+
+impl Actions {
+    pub fn contains(&self, action: Action) -> bool {
+        self.0 & (1 << action as u64) != 0
+    }
+
+    pub fn insert(&mut self, action: Action) {
+        self.0 |= 1 << action as u64;
+    }
+}
+
+/// A key with modifiers. Keys are matched by what they produce in the active keyboard layout,
+/// egui doesn't report the physical position of keys yet. Players of AZERTY or Dvorak keyboards
+/// load the [`Preset::Zqsd`] or [`Preset::Dvorak`] preset, which put the movement keys back where
+/// the other presets have them, other layouts have to rebind them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Binding {
+    pub key: Key,
+    #[serde(default)]
+    pub shift: bool,
+    #[serde(default)]
+    pub ctrl: bool,
+    #[serde(default)]
+    pub alt: bool,
+    /// The key has to be pressed twice in a row, like vim's `gg`.
+    #[serde(default)]
+    pub twice: bool,
+}
+
+impl Binding {
+    pub fn new(key: Key) -> Self {
+        Self {
+            key,
+            shift: false,
+            ctrl: false,
+            alt: false,
+            twice: false,
+        }
+    }
+
+    pub fn shift(mut self) -> Self {
+        self.shift = true;
+        self
+    }
+
+    pub fn ctrl(mut self) -> Self {
+        self.ctrl = true;
+        self
+    }
+
+    pub fn twice(mut self) -> Self {
+        self.twice = true;
+        self
+    }
+
+    /// Key repeats of the system don't count, held keys are repeated by the input map.
+    fn matches(&self, i: &InputState, last_key: Option<Key>) -> bool {
+        let pressed = i.events.iter().any(|e| match e {
+            Event::Key {
+                key,
+                pressed: true,
+                repeat: false,
+                ..
+            } => *key == self.key,
+            _ => false,
+        });
+        pressed && self.modifiers_match(i) && (!self.twice || last_key == Some(self.key))
+    }
+
+    fn is_held(&self, i: &InputState) -> bool {
+        i.key_down(self.key) && self.modifiers_match(i)
+    }
+
+    fn modifiers_match(&self, i: &InputState) -> bool {
+        let m = i.modifiers;
+        m.shift == self.shift && (m.ctrl || m.command) == self.ctrl && m.alt == self.alt
+    }
+}
+
+impl Display for Binding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.ctrl {
+            write!(f, "Ctrl+")?;
+        }
+        if self.alt {
+            write!(f, "Alt+")?;
+        }
+        if self.shift {
+            write!(f, "Shift+")?;
+        }
+        write!(f, "{:?}", self.key)?;
+        if self.twice {
+            write!(f, " {:?}", self.key)?;
+        }
+        Ok(())
+    }
+}
+
+/// Maps actions to any number of key bindings.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InputMap {
+    bindings: BTreeMap<Action, Vec<Binding>>,
+    /// The action for which a new binding is being recorded.
+    recording: Option<Action>,
+    /// The last pressed key, to detect keys that are pressed twice.
+    last_key: Option<Key>,
+    /// The held action and the time in seconds when it's repeated next.
+    repeat: Option<(Action, f64)>,
+}
+
+impl Default for InputMap {
+    fn default() -> Self {
+        use Binding as B;
+
+        let bindings = BTreeMap::from([
+            (
+                Action::MoveUp,
+                vec![B::new(Key::ArrowUp), B::new(Key::W), B::new(Key::K)],
+            ),
+            (
+                Action::MoveRight,
+                vec![B::new(Key::ArrowRight), B::new(Key::D), B::new(Key::L)],
+            ),
+            (
+                Action::MoveDown,
+                vec![B::new(Key::ArrowDown), B::new(Key::S), B::new(Key::J)],
+            ),
+            (
+                Action::MoveLeft,
+                vec![B::new(Key::ArrowLeft), B::new(Key::A), B::new(Key::H)],
+            ),
+            (Action::MoveUpLeft, vec![B::new(Key::Y)]),
+            (Action::MoveUpRight, vec![B::new(Key::U)]),
+            (Action::MoveDownLeft, vec![B::new(Key::B)]),
+            (Action::MoveDownRight, vec![B::new(Key::N)]),
+            (Action::JumpLeft, vec![B::new(Key::Home), B::new(Key::Num0)]),
+            (
+                Action::JumpRight,
+                vec![B::new(Key::End), B::new(Key::Num4).shift()],
+            ),
+            (
+                Action::JumpTop,
+                vec![B::new(Key::PageUp), B::new(Key::G).twice()],
+            ),
+            (
+                Action::JumpBottom,
+                vec![B::new(Key::PageDown), B::new(Key::G).shift()],
+            ),
+            (Action::JumpCenter, vec![B::new(Key::M)]),
+            (Action::Reveal, vec![B::new(Key::Enter), B::new(Key::Space)]),
+            (
+                Action::Hint,
+                vec![
+                    B::new(Key::Enter).ctrl(),
+                    B::new(Key::Space).ctrl(),
+                    B::new(Key::F),
+                ],
+            ),
+            (
+                Action::Chord,
+                vec![
+                    B::new(Key::Enter).shift(),
+                    B::new(Key::Space).shift(),
+                    B::new(Key::C),
+                ],
+            ),
+            (Action::Restart, vec![B::new(Key::R)]),
+            (Action::Fullscreen, vec![B::new(Key::F11)]),
+            (Action::Easy, vec![B::new(Key::Num1)]),
+            (Action::Medium, vec![B::new(Key::Num2)]),
+            (Action::Hard, vec![B::new(Key::Num3)]),
+        ]);
+
+        Self {
+            bindings,
+            recording: None,
+            last_key: None,
+            repeat: None,
+        }
+    }
+}
+
+impl serde::Serialize for InputMap {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.bindings.serialize(serializer)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for InputMap {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        // Actions that were added after the map was saved keep their default bindings.
+        let bindings = BTreeMap::<Action, Vec<Binding>>::deserialize(deserializer)?;
+        let mut map = InputMap::default();
+        map.bindings.extend(bindings);
+        Ok(map)
+    }
+}
+
+impl InputMap {
+    pub fn bindings(&self, action: Action) -> &[Binding] {
+        self.bindings
+            .get(&action)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    /// Returns the actions triggered in this frame. Movement is repeated after `delay` every
+    /// `interval` while its key is held.
+    pub fn actions(&mut self, i: &InputState, delay: Duration, interval: Duration) -> Actions {
+        let mut actions = Actions::default();
+        for (&action, bindings) in self.bindings.iter() {
+            if bindings.iter().any(|b| b.matches(i, self.last_key)) {
+                actions.insert(action);
+            }
+        }
+
+        match self.repeat {
+            Some((action, next)) if self.bindings(action).iter().any(|b| b.is_held(i)) => {
+                if i.time >= next {
+                    actions.insert(action);
+                    self.repeat = Some((action, i.time + interval.as_secs_f64()));
+                }
+            }
+            _ => self.repeat = None,
+        }
+        let pressed = Action::ALL
+            .into_iter()
+            .rfind(|&a| a.repeats() && actions.contains(a));
+        if let (Some(action), true) = (pressed, self.repeat.map(|r| r.0) != pressed) {
+            self.repeat = Some((action, i.time + delay.as_secs_f64()));
+        }
+
+        let pressed = i.events.iter().rev().find_map(|e| match e {
+            Event::Key {
+                key,
+                pressed: true,
+                repeat: false,
+                ..
+            } => Some(*key),
+            _ => None,
+        });
+        if let Some(key) = pressed {
+            // a key pressed the third time doesn't count as pressed twice again
+            self.last_key = if self.last_key == Some(key) {
+                None
+            } else {
+                Some(key)
+            };
+        }
+
+        actions
+    }
+
+    /// The default bindings with the ones of the preset, which replace other uses of their keys.
+    pub fn preset(preset: Preset) -> Self {
+        let mut map = Self::default();
+        let layout = preset.layout();
+        for bindings in map.bindings.values_mut() {
+            bindings.retain(|b| !layout.iter().any(|(l, _)| l == b));
+        }
+        for (binding, action) in layout {
+            map.bindings.entry(action).or_default().push(binding);
+        }
+        map
+    }
+
+    /// The time until a held key is repeated.
+    pub fn repeat_delay(&self, time: f64) -> Option<Duration> {
+        let (_, next) = self.repeat?;
+        Some(Duration::from_secs_f64((next - time).max(0.0)))
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui, tr: &Translations) {
+        if let Some(action) = self.recording {
+            // the key is taken out of the input, so it doesn't trigger its action right away
+            let pressed = ui.input_mut(|i| {
+                let index = i
+                    .events
+                    .iter()
+                    .position(|e| matches!(e, Event::Key { pressed: true, .. }))?;
+                match i.events.remove(index) {
+                    Event::Key { key, modifiers, .. } => Some((key, modifiers)),
+                    _ => None,
+                }
+            });
+            if let Some((key, modifiers)) = pressed {
+                if key != Key::Escape {
+                    let binding = Binding {
+                        key,
+                        shift: modifiers.shift,
+                        ctrl: modifiers.ctrl || modifiers.command,
+                        alt: modifiers.alt,
+                        twice: false,
+                    };
+                    let bindings = self.bindings.entry(action).or_default();
+                    if !bindings.contains(&binding) {
+                        bindings.push(binding);
+                    }
+                }
+                self.recording = None;
+            }
+        }
+
+        Grid::new("input_map").striped(true).show(ui, |ui| {
+            for action in Action::ALL {
+                ui.label(RichText::new(tr.action(action)).font(FontId::proportional(16.0)));
+                ui.horizontal(|ui| {
+                    let bindings = self.bindings.entry(action).or_default();
+                    let mut remove = None;
+                    for (i, binding) in bindings.iter().enumerate() {
+                        let button = ui.button(binding.to_string());
+                        if button.on_hover_text(&*tr.remove_binding).clicked() {
+                            remove = Some(i);
+                        }
+                    }
+                    if let Some(i) = remove {
+                        bindings.remove(i);
+                    }
+
+                    let recording = self.recording == Some(action);
+                    let text = if recording { &*tr.press_a_key } else { "+" };
+                    if ui.selectable_label(recording, text).clicked() {
+                        self.recording = (!recording).then_some(action);
+                    }
+                });
+                ui.end_row();
+            }
+        });
+
+        ui.horizontal(|ui| {
+            if ui.button(&*tr.reset_key_bindings).clicked() {
+                self.bindings = InputMap::default().bindings;
+            }
+            ComboBox::from_id_source("input_preset")
+                .selected_text(&*tr.load_preset)
+                .show_ui(ui, |ui| {
+                    for preset in Preset::ALL {
+                        let label = ui.selectable_label(false, tr.preset(preset));
+                        if label.on_hover_text(tr.preset_keys(preset)).clicked() {
+                            self.bindings = InputMap::preset(preset).bindings;
+                        }
+                    }
+                });
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn all_actions_have_default_bindings() {
+        let map = InputMap::default();
+        for action in Action::ALL {
+            assert!(!map.bindings(action).is_empty(), "{action:?}");
+        }
+    }
+
+    #[test]
+    fn actions_set() {
+        let mut actions = Actions::default();
+        actions.insert(Action::Chord);
+        actions.insert(Action::MoveUp);
+        assert!(actions.contains(Action::Chord));
+        assert!(actions.contains(Action::MoveUp));
+        assert!(!actions.contains(Action::Hint));
+    }
+
+    #[test]
+    fn flag_key() {
+        let map = InputMap::default();
+        let bound = Action::ALL
+            .into_iter()
+            .filter(|&a| map.bindings(a).contains(&Binding::new(Key::F)))
+            .collect::<Vec<_>>();
+        assert_eq!(bound, [Action::Hint]);
+    }
+
+    #[test]
+    fn presets() {
+        let map = InputMap::preset(Preset::Numpad);
+        let bound = |map: &InputMap, key| {
+            Action::ALL
+                .into_iter()
+                .filter(|&a| map.bindings(a).contains(&Binding::new(key)))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(bound(&map, Key::Num1), [Action::MoveDownLeft]);
+        assert_eq!(bound(&map, Key::Num5), [Action::Reveal]);
+        assert_eq!(bound(&map, Key::Num0), [Action::Hint]);
+        assert!(map
+            .bindings(Action::JumpLeft)
+            .contains(&Binding::new(Key::Home)));
+        // shifted digits keep their actions
+        assert!(map
+            .bindings(Action::JumpRight)
+            .contains(&Binding::new(Key::Num4).shift()));
+
+        let map = InputMap::preset(Preset::Ijkl);
+        assert_eq!(bound(&map, Key::J), [Action::MoveLeft]);
+        assert_eq!(bound(&map, Key::M), [Action::MoveDownRight]);
+        assert!(map
+            .bindings(Action::MoveLeft)
+            .contains(&Binding::new(Key::ArrowLeft)));
+
+        let map = InputMap::preset(Preset::Zqsd);
+        assert_eq!(bound(&map, Key::Z), [Action::MoveUp]);
+        assert_eq!(bound(&map, Key::Q), [Action::MoveLeft]);
+        assert_eq!(bound(&map, Key::A), [Action::Reveal]);
+
+        let map = InputMap::preset(Preset::Dvorak);
+        assert_eq!(bound(&map, Key::H), [Action::MoveLeft]);
+        assert_eq!(bound(&map, Key::T), [Action::MoveDown]);
+    }
+
+    #[test]
+    fn binding_display() {
+        assert_eq!(Binding::new(Key::Space).ctrl().to_string(), "Ctrl+Space");
+        assert_eq!(Binding::new(Key::G).twice().to_string(), "G G");
+    }
+}
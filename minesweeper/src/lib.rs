@@ -1,28 +1,334 @@
-use instant::SystemTime;
-use rand::Rng;
+use instant::{Instant, SystemTime};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use serde_derive::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
 use std::fmt::Display;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{mpsc, Arc};
 use std::time::Duration;
 
 use egui::{
-    Align, Align2, Button, Color32, ComboBox, FontId, Key, Layout, Pos2, Rect, RichText, Rounding,
-    Sense, Stroke, TextStyle, Ui, Vec2, Visuals,
+    Align, Align2, Button, Color32, ComboBox, EventFilter, FontFamily, FontId, Galley, Id, Key,
+    Layout, Mesh, Painter, PointerButton, Pos2, Rect, Response, Rgba, RichText, Rounding, Sense,
+    Shape, Stroke, TextStyle, Ui, Vec2, Visuals, WidgetInfo, WidgetText, WidgetType,
 };
 
+use crate::ai::AutoPlay;
+use crate::board::Board;
+use crate::bundle::Bundle;
+use crate::feedback::{Event, Feedback, Vibration, CASCADE_FIELDS};
+use crate::history::{Entry, GameResult, History, HistoryView};
+use crate::hot_seat::HotSeat;
+use crate::input::Action;
+use crate::profiles::Profiles;
+use crate::records::{today, BoardSeed, Record, Records, SeedBests};
+use crate::replay::{Ghost, Playback, Step, StepKind};
+use crate::session::Session;
+use crate::settings::{Settings, UnsatisfiedChord};
+use crate::solver::Analysis;
+use crate::stats::{Clicks, ClicksSerde, Metrics, Outcome, Stats};
+use crate::storage::Storage;
+use crate::theme::{Backdrop, CellState, CellStyle, CellView, Glyphs, Sprite, Theme};
+use crate::translations::{fill, Language, Translations};
+use crate::view::View;
+
+mod a11y;
+mod ai;
+#[cfg(all(feature = "audio", not(target_arch = "wasm32")))]
+mod audio;
+#[cfg(feature = "bench")]
+pub mod bench;
+mod board;
+mod bundle;
 pub mod combination_iter;
+mod export;
+pub mod feedback;
 mod gen;
+mod history;
+mod hot_seat;
+mod import;
+mod input;
+#[cfg(feature = "multiplayer")]
+pub mod multiplayer;
+#[cfg(feature = "online")]
+mod online;
+mod profiles;
+mod progress;
+mod records;
+mod replay;
+mod session;
+mod settings;
+mod snapshot;
+mod solver;
 pub mod stackvec;
+mod stats;
+pub mod storage;
+pub mod theme;
+pub mod translations;
+mod view;
+
+/// Duration of the blast on the mine that was hit in seconds.
+const EXPLOSION_DURATION: f32 = 0.5;
+/// Delay in seconds per field of distance to the mine that was hit, until another mine is
+/// revealed.
+const MINE_REVEAL_DELAY: f32 = 0.04;
+/// Duration of numbers growing to their full size after their field was revealed in seconds.
+const NUMBER_POP_DURATION: f32 = 0.1;
+/// Number of laid out texts kept, until the cache is cleared.
+const MAX_CACHED_GALLEYS: usize = 64;
+/// Duration of the board shaking after a mine was hit in seconds.
+const SHAKE_DURATION: f32 = 0.3;
+/// Largest offset of the shaking board in points.
+const SHAKE_AMPLITUDE: f32 = 6.0;
+/// Boards with at least as many fields as the hard ones paint flat cells as a single mesh.
+const MESH_MIN_FIELDS: usize = 40 * 24;
+/// Smallest size of fields in points, before the board has to be scrolled.
+const MIN_CELL_SIZE: f32 = 16.0;
+/// Smallest size of fields in points in the large mode, independent of the UI scale.
+const LARGE_CELL_SIZE: f32 = 64.0;
+const SCROLL_INDICATOR_WIDTH: f32 = 4.0;
+/// Shortest time between writes of the persisted state, so moves don't wait for the disk.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(5);
+/// Longest duration of a two finger tap in seconds.
+const TWO_FINGER_TAP_DURATION: f64 = 0.3;
+/// Farthest the fingers of a two finger tap may move in points.
+const TWO_FINGER_TAP_DISTANCE: f32 = 10.0;
+const COLLAPSED_TOP_BAR_HEIGHT: f32 = 8.0;
+const MINIMAP_WIDTH: f32 = 160.0;
+/// Number of fields between guide lines.
+const GUIDE_SPACING: i16 = 5;
+/// Duration of the keyboard cursor moving between fields in seconds.
+const CURSOR_MOVE_DURATION: f32 = 0.08;
+/// Duration of one pulse of the keyboard cursor in seconds.
+const CURSOR_PULSE_PERIOD: f32 = 1.2;
+/// Duration of the confetti falling over the board after a win in seconds.
+const CONFETTI_DURATION: f32 = 2.0;
+const CONFETTI_COUNT: usize = 120;
+const CONFETTI_COLORS: [Color32; 5] = [
+    Color32::from_rgb(0xf0, 0x40, 0x40),
+    Color32::from_rgb(0xf0, 0xc0, 0x30),
+    Color32::from_rgb(0x40, 0xc0, 0x60),
+    Color32::from_rgb(0x30, 0xa0, 0xf0),
+    Color32::from_rgb(0xc0, 0x60, 0xf0),
+];
 
 #[derive(Serialize, Deserialize)]
+#[serde(default)]
 pub struct Minesweeper {
+    #[serde(deserialize_with = "game_or_new")]
     game: Game,
     long_press: bool,
+    /// Set while both buttons are held to chord, whether the chord already happened.
+    #[serde(skip)]
+    both_buttons: Option<bool>,
+    #[serde(skip)]
+    two_finger_tap: Option<TwoFingerTap>,
+    /// Set while the board is dragged with space held, which pans instead of clicking.
+    #[serde(skip)]
+    space_drag: bool,
     cursor_visible: bool,
     cursor_x: i16,
     cursor_y: i16,
+    /// The field under the mouse, which teammates in co-op see instead of the cursor.
+    #[serde(skip)]
+    hovered: Option<(i16, i16)>,
+    /// Whether the game is played together with others, which isn't a personal result.
+    #[serde(skip)]
+    coop: bool,
     difficulty: Difficulty,
     unambigous: bool,
-    highscores: [Vec<Duration>; 6],
+    highscores: Records,
+    seed_bests: SeedBests,
+    stats: Stats,
+    history: History,
+    #[serde(skip)]
+    session: Session,
+    /// Show the stats of the current session beside the board.
+    session_open: bool,
+    /// Two players alternate moves.
+    hot_seat: bool,
+    settings: Settings,
+    #[serde(skip)]
+    settings_open: bool,
+    #[serde(skip)]
+    stats_open: bool,
+    #[serde(skip)]
+    history_open: bool,
+    #[serde(skip)]
+    history_view: HistoryView,
+    #[serde(skip)]
+    progress_open: bool,
+    #[serde(skip)]
+    progress_difficulty: Difficulty,
+    #[serde(skip)]
+    leaderboard_open: bool,
+    #[cfg(feature = "multiplayer")]
+    multiplayer_open: bool,
+    /// A board ID entered in the leaderboard, to play that board.
+    #[serde(skip)]
+    board_id_input: String,
+    /// The difficulty and mode shown in the leaderboard.
+    #[serde(skip)]
+    leaderboard_mode: (Difficulty, bool),
+    /// The outcome of the last import of records from other clients.
+    #[serde(skip)]
+    import_message: Option<String>,
+    /// The outcome of the last import of a bundle.
+    #[serde(skip)]
+    bundle_message: Option<String>,
+    /// A dropped bundle waiting for the player to confirm that it replaces all profiles.
+    #[serde(skip)]
+    pending_bundle: Option<Bundle>,
+    /// Where the last exported files were saved or whether the board image was copied, shown on
+    /// the board for a few seconds.
+    #[serde(skip)]
+    save_message: Option<(String, Instant)>,
+    #[serde(skip)]
+    clipboard: export::Clipboard,
+    #[serde(skip)]
+    prompt: Option<Prompt>,
+    /// Analysis of the position before the move that lost the game.
+    #[serde(skip)]
+    verdict: Option<Analysis>,
+    #[serde(skip)]
+    auto_play: Option<AutoPlay>,
+    /// A field the solver revealed to be safe.
+    #[serde(skip)]
+    solver_hint: Option<(i16, i16)>,
+    /// Flash the mine counter, because a hint was denied.
+    #[serde(skip)]
+    counter_flash: bool,
+    /// When the game was lost, to animate the explosion.
+    #[serde(skip)]
+    lost_at: Option<Instant>,
+    /// When the game was won, to animate the confetti.
+    #[serde(skip)]
+    won_at: Option<Instant>,
+    /// When each field was revealed in seconds of ui time, to grow the numbers.
+    #[serde(skip)]
+    revealed_at: Vec<Option<f64>>,
+    /// When the last input arrived, to pause the timer when idle.
+    #[serde(skip)]
+    last_input: Option<Instant>,
+    /// Describes changes of the game to screen readers.
+    #[serde(skip)]
+    announcer: a11y::Announcer,
+    /// The field under the cursor that was read aloud last, with its position and the language, so
+    /// it is only described again when one of them changes.
+    #[serde(skip)]
+    spoken: Option<((i16, i16), Field, Language)>,
+    /// When the state was last persisted, to save a running game periodically.
+    #[serde(skip)]
+    saved_at: Option<Instant>,
+    /// When the persisted state was last written out.
+    #[serde(skip)]
+    flushed_at: Option<Instant>,
+    #[serde(skip)]
+    galleys: GalleyCache,
+    #[serde(skip)]
+    labels: LabelCache,
+    /// The summary of a finished game was closed to look at the board.
+    #[serde(skip)]
+    summary_hidden: bool,
+    #[serde(skip)]
+    view: View,
+    #[serde(skip)]
+    fullscreen: bool,
+    /// The collapsed top bar was opened with escape.
+    #[serde(skip)]
+    top_bar_peek: bool,
+    #[serde(skip)]
+    top_bar_hovered: bool,
+    /// Theme set by the embedding app, which replaces the palette chosen in the settings.
+    #[serde(skip)]
+    theme: Option<Theme>,
+    /// Translations set by the embedding app, which replace the language chosen in the settings.
+    #[serde(skip)]
+    translations: Option<Arc<Translations>>,
+    /// The built-in translations and their language, which are only built again when the
+    /// language changes.
+    #[serde(skip)]
+    builtin_translations: Option<(Language, Arc<Translations>)>,
+    #[serde(skip)]
+    generation: Option<Generation>,
+    /// A recorded game that is watched, the user can't make moves meanwhile.
+    #[serde(skip)]
+    playback: Option<Playback>,
+    /// The best time on the board before the game was won, to compare rematches.
+    #[serde(skip)]
+    previous_seed_best: Option<Duration>,
+    /// The best run on the same board, shown while playing.
+    #[serde(skip)]
+    ghost: Option<Ghost>,
+    #[cfg(feature = "online")]
+    #[serde(skip)]
+    online: online::Client,
+    /// The last day the daily challenge was started on, only the first attempt is submitted.
+    #[cfg(feature = "online")]
+    daily_started: Option<u64>,
+    /// Whether the running game is the first attempt of the day at the daily challenge.
+    #[cfg(feature = "online")]
+    first_daily: bool,
+    /// Storage set by the embedding app, which replaces the one of eframe.
+    #[serde(skip)]
+    storage: Option<Box<dyn Storage>>,
+    /// Feedback set by the embedding app, which replaces the vibration and the sounds.
+    #[serde(skip)]
+    feedback: Option<Box<dyn Feedback>>,
+    /// Opened on the first sound that is played, `Some(None)` if there is no output device, so
+    /// it isn't tried again on every event.
+    #[cfg(all(feature = "audio", not(target_arch = "wasm32")))]
+    #[serde(skip)]
+    sounds: Option<Option<audio::Sounds>>,
+    #[cfg(feature = "multiplayer")]
+    #[serde(skip)]
+    multiplayer: multiplayer::Multiplayer,
+    /// The profiles are stored on their own, the rest of the state belongs to the active one.
+    #[serde(skip)]
+    profiles: Profiles,
+    /// The name of a profile that is about to be added.
+    #[serde(skip)]
+    profile_name_input: String,
+}
+
+/// Two fingers touching the board, which chord if they are lifted quickly without moving.
+#[derive(Clone, Copy, Debug)]
+struct TwoFingerTap {
+    start_time: f64,
+    /// The point between the fingers when they touched the board.
+    pos: Pos2,
+    /// How far the point between the fingers moved.
+    moved: f32,
+    zoom: f32,
+    /// Set when a finger was lifted, the other one is ignored until it is lifted as well.
+    ended: bool,
+}
+
+impl TwoFingerTap {
+    fn is_tap(&self, time: f64) -> bool {
+        time - self.start_time <= TWO_FINGER_TAP_DURATION
+            && self.moved <= TWO_FINGER_TAP_DISTANCE
+            && (self.zoom - 1.0).abs() < 0.1
+    }
+}
+
+/// An unambigous board that is generated on another thread, before the first click is made.
+struct Generation {
+    receiver: mpsc::Receiver<Game>,
+    /// The number of boards that were checked so far.
+    checked: Arc<AtomicU32>,
+    /// Set when the generation is dropped, which stops the search.
+    cancel: Arc<AtomicBool>,
+    click: (i16, i16),
+}
+
+impl Drop for Generation {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
 }
 
 impl Default for Minesweeper {
@@ -37,35 +343,507 @@ impl Minesweeper {
         Self {
             game: Game::easy(unambigous),
             long_press: false,
+            both_buttons: None,
+            two_finger_tap: None,
+            space_drag: false,
             cursor_visible: false,
             cursor_x: 0,
             cursor_y: 0,
+            hovered: None,
+            coop: false,
             difficulty: Difficulty::Easy,
             unambigous,
-            highscores: [
-                Vec::new(),
-                Vec::new(),
-                Vec::new(),
-                Vec::new(),
-                Vec::new(),
-                Vec::new(),
-            ],
+            highscores: Records::default(),
+            seed_bests: SeedBests::default(),
+            stats: Stats::default(),
+            history: History::default(),
+            session: Session::default(),
+            session_open: false,
+            hot_seat: false,
+            settings: Settings::default(),
+            settings_open: false,
+            stats_open: false,
+            history_open: false,
+            history_view: HistoryView::default(),
+            progress_open: false,
+            progress_difficulty: Difficulty::Easy,
+            leaderboard_open: false,
+            #[cfg(feature = "multiplayer")]
+            multiplayer_open: false,
+            board_id_input: String::new(),
+            leaderboard_mode: (Difficulty::Easy, unambigous),
+            import_message: None,
+            bundle_message: None,
+            pending_bundle: None,
+            save_message: None,
+            clipboard: export::Clipboard::default(),
+            prompt: None,
+            verdict: None,
+            auto_play: None,
+            solver_hint: None,
+            counter_flash: false,
+            lost_at: None,
+            won_at: None,
+            revealed_at: Vec::new(),
+            last_input: None,
+            announcer: a11y::Announcer::default(),
+            spoken: None,
+            saved_at: None,
+            flushed_at: None,
+            galleys: GalleyCache::default(),
+            labels: LabelCache::default(),
+            summary_hidden: false,
+            view: View::default(),
+            fullscreen: false,
+            top_bar_peek: false,
+            top_bar_hovered: false,
+            theme: None,
+            translations: None,
+            builtin_translations: None,
+            generation: None,
+            playback: None,
+            previous_seed_best: None,
+            ghost: None,
+            #[cfg(feature = "online")]
+            online: online::Client::default(),
+            #[cfg(feature = "online")]
+            daily_started: None,
+            #[cfg(feature = "online")]
+            first_daily: false,
+            storage: None,
+            feedback: None,
+            #[cfg(feature = "multiplayer")]
+            multiplayer: multiplayer::Multiplayer::default(),
+            #[cfg(all(feature = "audio", not(target_arch = "wasm32")))]
+            sounds: None,
+            profiles: Profiles::default(),
+            profile_name_input: String::new(),
+        }
+    }
+
+    /// Restores the game state from `storage`, or starts from scratch if there is none.
+    pub fn load(storage: &(impl Storage + ?Sized)) -> Self {
+        let profiles: Profiles =
+            storage::get_value(storage, profiles::PROFILES_KEY).unwrap_or_default();
+        let mut ms: Self = storage::get_value(storage, &profiles.key()).unwrap_or_default();
+        ms.profiles = profiles;
+        ms.offer_resume();
+        ms
+    }
+
+    /// Reads a stored state strictly, unlike loading it, which replaces a game that can't be
+    /// restored and anything else that fails with defaults.
+    fn check(text: &str) -> Result<(), ron::error::SpannedError> {
+        #[derive(Deserialize)]
+        struct Stored {
+            #[serde(rename = "game")]
+            _game: Game,
+        }
+        ron::from_str::<Self>(text)?;
+        ron::from_str::<Stored>(text)?;
+        Ok(())
+    }
+
+    pub fn save(&self, storage: &mut (impl Storage + ?Sized)) {
+        storage::set_value(storage, profiles::PROFILES_KEY, &self.profiles);
+        storage::set_value(storage, &self.profiles.key(), self);
+    }
+
+    /// Persists the game state to `storage` after every move, instead of the storage of eframe.
+    /// It's flushed every few seconds and should flush pending changes when it's dropped.
+    pub fn set_storage(&mut self, storage: Option<Box<dyn Storage>>) {
+        self.storage = storage;
+    }
+
+    /// Notifies the player of events, instead of vibrating and playing sounds.
+    pub fn set_feedback(&mut self, feedback: Option<Box<dyn Feedback>>) {
+        self.feedback = feedback;
+    }
+
+    fn feedback(&mut self, event: Event) {
+        match &mut self.feedback {
+            Some(feedback) => feedback.event(event),
+            None => {
+                if self.settings.vibration {
+                    Vibration.event(event);
+                }
+                #[cfg(all(feature = "audio", not(target_arch = "wasm32")))]
+                if !self.settings.muted {
+                    let sounds = self.sounds.get_or_insert_with(audio::Sounds::new);
+                    if let Some(sounds) = sounds {
+                        sounds.event(event);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Persists the game state after a move, so it isn't lost if the app is killed.
+    fn persist(&mut self, frame: &mut eframe::Frame) {
+        if let Some(mut storage) = self.storage.take() {
+            self.save(&mut *storage);
+            self.storage = Some(storage);
+        } else if let Some(storage) = frame.storage_mut() {
+            self.save(storage);
+        }
+        self.saved_at = Some(Instant::now());
+    }
+
+    /// Writes the persisted state out, the storage of eframe only does so every 30 seconds and
+    /// on exit.
+    fn flush(&mut self, frame: &mut eframe::Frame) {
+        self.flushed_at = Some(Instant::now());
+        match &mut self.storage {
+            Some(storage) => storage.flush(),
+            None => {
+                if let Some(storage) = frame.storage_mut() {
+                    eframe::Storage::flush(storage);
+                }
+            }
+        }
+    }
+
+    /// Asks whether a running game that was restored should be continued.
+    fn offer_resume(&mut self) {
+        if let PlayState::Playing(_) = self.game.play_state {
+            self.prompt = Some(Prompt::Resume(self.game.play_duration()));
+        }
+    }
+
+    /// Saves the state of the active profile, including a running game, and continues with the
+    /// state of `profile`. What the embedding app has set is kept.
+    fn switch_profile(&mut self, frame: &mut eframe::Frame, profile: Option<String>) {
+        self.persist(frame);
+        let mut profiles = std::mem::take(&mut self.profiles);
+        profiles.set_current(profile);
+        self.reload(frame, profiles);
+    }
+
+    /// Continues with the stored state of the active profile in `profiles`.
+    fn reload(&mut self, frame: &mut eframe::Frame, profiles: Profiles) {
+        let mut next: Self = self.read(frame, &profiles.key()).unwrap_or_default();
+        next.theme = self.theme.take();
+        next.translations = self.translations.take();
+        next.storage = self.storage.take();
+        next.profiles = profiles;
+        next.offer_resume();
+        *self = next;
+        self.persist(frame);
+    }
+
+    fn read<T: serde::de::DeserializeOwned>(&self, frame: &eframe::Frame, key: &str) -> Option<T> {
+        match &self.storage {
+            Some(storage) => storage::get_value(&**storage, key),
+            None => frame.storage().and_then(|s| storage::get_value(s, key)),
+        }
+    }
+
+    /// Saves the stored state of all profiles to a single file.
+    fn export_bundle(&mut self, frame: &mut eframe::Frame) {
+        self.persist(frame);
+        let bundle = match &self.storage {
+            Some(storage) => Bundle::collect(&**storage, &self.profiles),
+            None => match frame.storage() {
+                Some(storage) => Bundle::collect(storage, &self.profiles),
+                None => return,
+            },
+        };
+        let secs = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        let name = format!("minesweeper-{secs}-bundle.ron");
+        self.save_files("text/plain", &[(name, bundle.to_ron().as_bytes())]);
+    }
+
+    /// Saves the files where the player chooses and tells them where they went.
+    fn save_files(&mut self, mime: &str, files: &[(String, &[u8])]) {
+        let tr = self.translations();
+        let mut lines = Vec::new();
+        for (name, data) in files {
+            match export::save_file(data, name, mime) {
+                Ok(Some(path)) => {
+                    log::info!("saved {path}");
+                    lines.push(fill(&tr.saved_file, path));
+                }
+                Ok(None) => (),
+                Err(e) => {
+                    log::warn!("failed to save {name}: {e}");
+                    lines.push(fill(&tr.save_failed, e));
+                }
+            }
+        }
+        // nothing is shown if every dialog was cancelled
+        let message = lines.join("\n");
+        if !message.is_empty() {
+            self.save_message = Some((message, Instant::now()));
+        }
+    }
+
+    /// Replaces the stored state with the one of the bundle, and continues with it.
+    fn import_bundle(&mut self, frame: &mut eframe::Frame, bundle: Bundle) {
+        match &mut self.storage {
+            Some(storage) => bundle.restore(&mut **storage),
+            None => match frame.storage_mut() {
+                Some(storage) => bundle.restore(storage),
+                None => return,
+            },
         }
+        let profiles = self.read(frame, profiles::PROFILES_KEY).unwrap_or_default();
+        self.reload(frame, profiles);
+    }
+
+    /// Overrides the built-in palettes, `None` restores the palette chosen in the settings.
+    pub fn set_theme(&mut self, theme: Option<Theme>) {
+        self.theme = theme;
+    }
+
+    /// Overrides the built-in translations, `None` restores the language chosen in the settings.
+    pub fn set_translations(&mut self, translations: Option<Translations>) {
+        self.translations = translations.map(Arc::new);
     }
 
     fn new_game(&mut self) {
+        let playing = matches!(self.game.play_state, PlayState::Playing(_));
+        if playing && self.game.hot_seat.is_none() && !self.coop {
+            self.finish_game(Outcome::Abandoned);
+        }
+        self.coop = false;
+
         self.game = match self.difficulty {
             Difficulty::Easy => Game::easy(self.unambigous),
             Difficulty::Medium => Game::medium(self.unambigous),
             Difficulty::Hard => Game::hard(self.unambigous),
         };
+        self.verdict = None;
+        self.lost_at = None;
+        self.won_at = None;
+        self.solver_hint = None;
+        self.summary_hidden = false;
+        self.view = View::default();
+        self.generation = None;
+        self.playback = None;
+        self.previous_seed_best = None;
+        self.ghost = None;
+
+        if self.hot_seat {
+            self.game.hot_seat = Some(HotSeat::new(self.game.fields.len()));
+        }
+
+        if self.settings.auto_open {
+            (self.cursor_x, self.cursor_y) = self.game.auto_open();
+        }
+    }
+
+    /// Starts a game on the board of a record, and plays its recorded steps if `watch` is set.
+    fn play_record(
+        &mut self,
+        difficulty: Difficulty,
+        unambigous: bool,
+        record: &Record,
+        watch: bool,
+    ) {
+        let Some(board) = &record.board else {
+            return;
+        };
+        if self.play_board(difficulty, unambigous, board) && watch {
+            self.playback = Some(Playback::new(record.steps.clone()));
+        }
+    }
+
+    /// Starts a game on a board that was shared or played before, returns false if the board
+    /// can't be generated.
+    fn play_board(&mut self, difficulty: Difficulty, unambigous: bool, board: &BoardSeed) -> bool {
+        if !board.is_valid() {
+            return false;
+        }
+        self.new_game();
+        self.game = Game::from_seed(difficulty, unambigous, board);
+        true
+    }
+
+    /// Starts a multiplayer race with the opening revealed, so every player starts the same.
+    #[cfg(feature = "multiplayer")]
+    fn start_race(&mut self, board: &BoardSeed) {
+        let Some(difficulty) = Difficulty::from_size(board.width, board.height) else {
+            return;
+        };
+        if !self.play_board(difficulty, false, board) {
+            return;
+        }
+        self.coop = self.multiplayer.is_coop();
+        if let Some((x, y)) = board.opening {
+            self.game.click(x, y);
+        }
+        // every player reveals the opening itself
+        self.multiplayer.skip_moves(self.game.steps.len());
+    }
+
+    /// Sends the move to the host instead of making it, if playing in co-op as a client.
+    #[cfg(feature = "multiplayer")]
+    fn propose(&mut self, x: i16, y: i16, kind: StepKind) -> bool {
+        if !self.coop || !self.game.is_in_bounds(x, y) {
+            return false;
+        }
+        let time = self.game.play_duration().as_millis() as u32;
+        self.multiplayer.propose(Step { x, y, kind, time })
+    }
+
+    /// Replays the finished board from the start.
+    fn retry(&mut self, frame: &mut eframe::Frame) {
+        self.game.retry();
+        self.previous_seed_best = None;
+        self.ghost = None;
+        self.verdict = None;
+        self.lost_at = None;
+        self.won_at = None;
+        self.solver_hint = None;
+        self.summary_hidden = false;
+
+        self.persist(frame);
+    }
+
+    /// The translations set by the embedding app, or the language chosen in the settings.
+    fn translations(&mut self) -> Arc<Translations> {
+        if let Some(translations) = &self.translations {
+            return Arc::clone(translations);
+        }
+        let language = self.settings.language;
+        match &self.builtin_translations {
+            Some((l, translations)) if *l == language => Arc::clone(translations),
+            _ => {
+                let translations = Arc::new(Translations::builtin(language));
+                self.builtin_translations = Some((language, Arc::clone(&translations)));
+                translations
+            }
+        }
+    }
+
+    /// The theme set by the embedding app, or the one chosen in the settings.
+    fn resolved_theme(&self, visuals: &Visuals) -> Theme {
+        match &self.theme {
+            Some(theme) => theme.clone(),
+            None => builtin_theme(&self.settings, visuals),
+        }
+    }
+
+    fn save_image(&mut self, visuals: &Visuals) {
+        let image = snapshot::render(&self.game, &self.resolved_theme(visuals), 32);
+        let secs = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        let png = snapshot::encode_png(&image);
+        self.save_files(
+            "image/png",
+            &[(format!("minesweeper-{secs}.png"), png.as_slice())],
+        );
+    }
+
+    fn copy_image(&mut self, visuals: &Visuals) {
+        let image = snapshot::render(&self.game, &self.resolved_theme(visuals), 32);
+        let tr = self.translations();
+        let message = match self.clipboard.copy_image(&image) {
+            Ok(()) => tr.copied_board_image.to_string(),
+            Err(e) => {
+                log::warn!("failed to copy the board image: {e}");
+                fill(&tr.copy_failed, e)
+            }
+        };
+        self.save_message = Some((message, Instant::now()));
+    }
+
+    fn save_replay(&mut self, visuals: &Visuals) {
+        let apng = replay::export_apng(&self.game, &self.resolved_theme(visuals));
+        let secs = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        let name = format!("minesweeper-replay-{secs}.png");
+        self.save_files("image/png", &[(name, apng.as_slice())]);
+    }
+
+    fn export_stats(&mut self, format: export::Format) {
+        let secs = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        let mime = match format {
+            export::Format::Csv => "text/csv",
+            export::Format::Json => "application/json",
+        };
+        let files = export::export(format, &self.stats, &self.history);
+        let files = files
+            .iter()
+            .map(|(suffix, text)| (format!("minesweeper-{secs}-{suffix}"), text.as_bytes()))
+            .collect::<Vec<_>>();
+        self.save_files(mime, &files);
+    }
+
+    /// Merges the bests of other clients into the records, returns the number of new records.
+    fn import_records(&mut self, text: &str) -> usize {
+        let mut count = 0;
+        for (difficulty, record) in import::parse(text) {
+            let known = self
+                .highscores
+                .records(difficulty, false)
+                .iter()
+                .any(|r| r.time == record.time && r.date == record.date);
+            if !known {
+                self.highscores.insert(difficulty, false, record);
+                count += 1;
+            }
+        }
+        count
+    }
+
+    fn toggle_fullscreen(&mut self, ctx: &egui::Context) {
+        self.fullscreen = !self.fullscreen;
+        set_fullscreen(ctx, self.fullscreen);
+    }
+
+    /// Start a new game, or ask for confirmation first if a game is in progress.
+    fn restart(&mut self) {
+        let in_progress = matches!(self.game.play_state, PlayState::Playing(_));
+        if in_progress && self.settings.confirm_restart {
+            self.prompt = Some(Prompt::Restart);
+        } else {
+            self.new_game();
+        }
+    }
+
+    /// Starts a game of the difficulty unless one is running, a running game is only abandoned
+    /// after asking. After a finished game the difficulty is used for the next one.
+    fn select_difficulty(&mut self, difficulty: Difficulty) {
+        match self.game.play_state {
+            PlayState::Init => {
+                self.difficulty = difficulty;
+                self.new_game();
+            }
+            PlayState::Playing(_) => {
+                if difficulty != self.difficulty {
+                    self.change_difficulty(difficulty);
+                }
+            }
+            PlayState::Won(_) | PlayState::Lost(_) => self.difficulty = difficulty,
+        }
+    }
+
+    /// Switch to a new difficulty mid-game, which abandons the current game.
+    fn change_difficulty(&mut self, difficulty: Difficulty) {
+        if self.settings.confirm_restart {
+            self.prompt = Some(Prompt::ChangeDifficulty(difficulty));
+        } else {
+            self.difficulty = difficulty;
+            self.new_game();
+        }
     }
 
     fn cursor_x_neg(&mut self) {
         self.cursor_visible = true;
         self.cursor_x -= 1;
         if self.cursor_x < 0 {
-            self.cursor_x = self.game.width - 1;
+            self.cursor_x = if self.settings.wrap_cursor {
+                self.game.width - 1
+            } else {
+                0
+            };
         }
     }
 
@@ -73,7 +851,11 @@ impl Minesweeper {
         self.cursor_visible = true;
         self.cursor_x += 1;
         if self.cursor_x >= self.game.width {
-            self.cursor_x = 0
+            self.cursor_x = if self.settings.wrap_cursor {
+                0
+            } else {
+                self.game.width - 1
+            };
         }
     }
 
@@ -81,7 +863,11 @@ impl Minesweeper {
         self.cursor_visible = true;
         self.cursor_y -= 1;
         if self.cursor_y < 0 {
-            self.cursor_y = self.game.height - 1;
+            self.cursor_y = if self.settings.wrap_cursor {
+                self.game.height - 1
+            } else {
+                0
+            };
         }
     }
 
@@ -89,7 +875,11 @@ impl Minesweeper {
         self.cursor_visible = true;
         self.cursor_y += 1;
         if self.cursor_y >= self.game.height {
-            self.cursor_y = 0
+            self.cursor_y = if self.settings.wrap_cursor {
+                0
+            } else {
+                self.game.height - 1
+            };
         }
     }
 
@@ -125,136 +915,732 @@ impl Minesweeper {
         }
     }
 
-    fn click(&mut self, frame: &mut eframe::Frame, x: i16, y: i16) {
-        if let Some(duration) = self.game.click(x, y) {
-            let scores = &mut self.highscores
-                [self.game.difficulty as usize + (3 * self.game.unambigous as usize)];
-            let idx = scores.iter().position(|d| duration < *d);
-            match idx {
-                Some(i) => scores.insert(i, duration),
-                None => scores.push(duration),
-            }
-        }
-
-        if let Some(storage) = frame.storage_mut() {
-            eframe::set_value(storage, eframe::APP_KEY, self);
+    fn cursor_left_edge(&mut self, flipped: bool) {
+        self.cursor_visible = true;
+        if flipped {
+            self.cursor_y = self.game.height - 1;
+        } else {
+            self.cursor_x = 0;
         }
     }
 
-    fn hint(&mut self, frame: &mut eframe::Frame, x: i16, y: i16) {
-        let PlayState::Playing(_) = self.game.play_state else { return };
-
-        self.game.hint_(x, y);
-        if let Some(storage) = frame.storage_mut() {
-            eframe::set_value(storage, eframe::APP_KEY, self);
+    fn cursor_right_edge(&mut self, flipped: bool) {
+        self.cursor_visible = true;
+        if flipped {
+            self.cursor_y = 0;
+        } else {
+            self.cursor_x = self.game.width - 1;
         }
     }
-}
-
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
-enum Difficulty {
-    Easy = 0,
-    Medium = 1,
-    Hard = 2,
-}
 
-impl Display for Difficulty {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Difficulty::Easy => write!(f, "Easy"),
-            Difficulty::Medium => write!(f, "Medium"),
-            Difficulty::Hard => write!(f, "Hard"),
+    fn cursor_top_edge(&mut self, flipped: bool) {
+        self.cursor_visible = true;
+        if flipped {
+            self.cursor_x = 0;
+        } else {
+            self.cursor_y = 0;
         }
     }
-}
-
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
-struct Game {
-    difficulty: Difficulty,
-    unambigous: bool,
-    num_mines: u16,
-    play_state: PlayState,
-    width: i16,
-    height: i16,
-    fields: Vec<Field>,
-}
-
-impl Game {
-    fn easy(unambigous: bool) -> Self {
-        Self::new(20, 14, 0.12..0.13, Difficulty::Easy, unambigous)
-    }
 
-    fn medium(unambigous: bool) -> Self {
-        Self::new(30, 18, 0.16..0.17, Difficulty::Medium, unambigous)
+    fn cursor_bottom_edge(&mut self, flipped: bool) {
+        self.cursor_visible = true;
+        if flipped {
+            self.cursor_x = self.game.width - 1;
+        } else {
+            self.cursor_y = self.game.height - 1;
+        }
     }
 
-    fn hard(unambigous: bool) -> Self {
-        Self::new(40, 24, 0.21..0.22, Difficulty::Hard, unambigous)
+    fn cursor_center(&mut self) {
+        self.cursor_visible = true;
+        self.cursor_x = self.game.width / 2;
+        self.cursor_y = self.game.height / 2;
     }
 
-    fn new(
-        width: i16,
-        height: i16,
-        probability_range: std::ops::Range<f64>,
-        difficulty: Difficulty,
-        unambigous: bool,
-    ) -> Self {
-        let len = (width * height) as usize;
+    fn click(&mut self, frame: &mut eframe::Frame, x: i16, y: i16) {
+        if self.generation.is_some() || self.playback.is_some() {
+            return;
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if self.game.unambigous && !self.game.generated && self.game.is_in_bounds(x, y) {
+            self.generate(x, y);
+            return;
+        }
 
-        let min = (probability_range.start * len as f64) as u16;
-        let max = (probability_range.end * len as f64) as u16;
-        let num_mines = rand::thread_rng().gen_range(min..max);
+        let before = match self.game.play_state {
+            PlayState::Playing(_) => Some(self.game.fields.clone()),
+            _ => None,
+        };
+        #[cfg(feature = "online")]
+        let starting = self.game.play_state == PlayState::Init;
 
-        Self {
-            difficulty,
-            unambigous,
-            num_mines,
-            play_state: PlayState::Init,
-            width,
-            height,
-            fields: vec![Field::free(0); len],
+        let chord =
+            self.game.is_in_bounds(x, y) && self.game[(x, y)].visibility() == Visibility::Show;
+        #[cfg(feature = "multiplayer")]
+        {
+            let kind = match chord {
+                true => StepKind::Chord,
+                false => StepKind::Reveal,
+            };
+            if self.propose(x, y, kind) {
+                return;
+            }
         }
-    }
+        let num_shown = self.game.num_shown();
+        let result = self.game.click(x, y);
 
-    fn clear_board(&mut self) {
-        for f in self.fields.iter_mut() {
-            f.state = FieldState::Free(0);
+        let moved = self.game.num_shown() != num_shown;
+        #[cfg(feature = "online")]
+        if starting && self.game.play_state != PlayState::Init {
+            self.start_daily();
         }
-    }
-
-    /// Returns the duration if the game was won.
-    fn click(&mut self, x: i16, y: i16) -> Option<Duration> {
-        if !self.is_in_bounds(x, y) {
-            return None;
+        if self.game.is_in_bounds(x, y) {
+            let clicks = &mut self.game.clicks;
+            if chord {
+                clicks.chords += 1;
+            } else {
+                clicks.left += 1;
+            }
+            if !moved && !matches!(self.game.play_state, PlayState::Lost(_)) {
+                clicks.wasted += 1;
+            }
+        }
+        if let (Some(hot_seat), PlayState::Playing(_)) =
+            (&mut self.game.hot_seat, self.game.play_state)
+        {
+            if moved {
+                hot_seat.next_turn();
+            }
         }
 
-        let first = self.play_state == PlayState::Init;
-        if first {
-            self.gen_board();
-
-            let mut field = &self[(x, y)];
-            loop {
-                if field.state == FieldState::Free(0) {
-                    if !self.unambigous || self.is_unambigous(x, y) {
-                        break;
-                    }
+        // games that were played together aren't personal results
+        let personal = self.game.hot_seat.is_none() && !self.coop;
+        if personal {
+            match (result, self.game.play_state) {
+                (Some(duration), _) => {
+                    let metrics = self.game.metrics(duration);
+                    self.finish_game(Outcome::Won(metrics));
                 }
-
-                self.clear_board();
-                self.gen_board();
-                field = &self[(x, y)];
+                (None, PlayState::Lost(_)) if before.is_some() => {
+                    self.finish_game(Outcome::Lost);
+                }
+                _ => (),
             }
+        }
 
-            self.play_state = PlayState::Playing(SystemTime::now());
+        let fair = !self.game.assisted && personal;
+        if let (Some(duration), true) = (result, fair) {
+            let (difficulty, unambigous) = (self.game.difficulty, self.game.unambigous);
+            let date = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH);
+            let record = Record {
+                time: duration,
+                date: date.ok().map(|d| d.as_secs()),
+                board: self.game.board_seed(),
+                steps: self.game.steps.clone(),
+                bbbv: self.game.bbbv(),
+                clicks: self.game.clicks.total(),
+                wasted: self.game.clicks.wasted,
+                solver_hints: self.game.solver_hints_used,
+            };
+            if let Some(board) = record.board {
+                self.previous_seed_best = self.seed_bests.insert(&board, record.clone());
+                #[cfg(feature = "online")]
+                self.submit_daily(&board, &record);
+            }
+            if !self.game.known_board {
+                self.highscores.insert(difficulty, unambigous, record);
+            }
         }
 
-        let field = &mut self[(x, y)];
-        if field.visibility == Visibility::Hint {
-            return None;
+        if let (PlayState::Lost(_), Some(fields)) = (self.game.play_state, before) {
+            let mut board = self.game.clone();
+            board.fields = fields;
+            self.verdict = Some(board.analyze());
+            self.lost_at = Some(Instant::now());
+            self.feedback(Event::Explosion);
         }
-        match field.state {
+        match self.game.play_state {
+            PlayState::Won(_) => {
+                self.won_at = Some(Instant::now());
+                self.feedback(Event::Win);
+            }
+            PlayState::Lost(_) => (),
+            _ if chord && moved => self.feedback(Event::Chord),
+            _ if self.game.num_shown() >= num_shown + CASCADE_FIELDS => {
+                self.feedback(Event::Cascade);
+            }
+            _ if moved => self.feedback(Event::Reveal),
+            _ => (),
+        }
+
+        self.persist(frame);
+    }
+
+    /// Remembers that the daily challenge was attempted, when a game on its board starts. Retries
+    /// and later games on the board don't count, even after a loss.
+    #[cfg(feature = "online")]
+    fn start_daily(&mut self) {
+        let day = today().filter(|&d| self.game.board_seed() == Some(BoardSeed::daily(d)));
+        self.first_daily = day.is_some() && day != self.daily_started && !self.game.known_board;
+        if day.is_some() {
+            self.daily_started = day;
+        }
+    }
+
+    /// Submits the win of the first attempt at the daily challenge, if the player opted in.
+    #[cfg(feature = "online")]
+    fn submit_daily(&self, board: &BoardSeed, record: &Record) {
+        let online = &self.settings.online;
+        let Some(day) = today() else { return };
+        if !online.enabled || *board != BoardSeed::daily(day) || !self.first_daily {
+            return;
+        }
+        let submission = online::Submission {
+            name: online.name.clone(),
+            board: board.id(),
+            time_ms: record.time.as_millis() as u64,
+            replay_hash: online::replay_hash(&record.steps),
+        };
+        online::submit(online, day, &submission);
+    }
+
+    /// Counts the outcome of the game in the statistics, and logs the game in the history.
+    fn finish_game(&mut self, outcome: Outcome) {
+        self.stats.record(self.game.difficulty, outcome);
+
+        let (result, metrics) = match outcome {
+            Outcome::Won(metrics) => (GameResult::Won, metrics),
+            Outcome::Lost => (
+                GameResult::Lost,
+                self.game.metrics(self.game.play_duration()),
+            ),
+            Outcome::Abandoned => (
+                GameResult::Abandoned,
+                self.game.metrics(self.game.play_duration()),
+            ),
+        };
+        let date = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH);
+        let entry = Entry {
+            date: date.ok().map(|d| d.as_secs()),
+            difficulty: self.game.difficulty,
+            unambigous: self.game.unambigous,
+            seed: self.game.seed.filter(|_| self.game.generated),
+            result,
+            metrics,
+        };
+        self.history.push(entry);
+        self.session.push(entry);
+    }
+
+    /// Generates the board on another thread, the click is made once it is done.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn generate(&mut self, x: i16, y: i16) {
+        let (sender, receiver) = mpsc::channel();
+        let checked = Arc::new(AtomicU32::new(0));
+        let cancel = Arc::new(AtomicBool::new(false));
+        let mut game = self.game.clone();
+        let (counter, cancelled) = (checked.clone(), cancel.clone());
+        std::thread::spawn(move || {
+            if game.gen_opening_counted(x, y, &counter, &cancelled) {
+                // the game might have been abandoned in the meantime
+                _ = sender.send(game);
+            }
+        });
+        self.generation = Some(Generation {
+            receiver,
+            checked,
+            cancel,
+            click: (x, y),
+        });
+    }
+
+    fn hint(&mut self, frame: &mut eframe::Frame, x: i16, y: i16) {
+        if self.playback.is_some() {
+            return;
+        }
+        match self.game.play_state {
+            PlayState::Playing(_) => (),
+            PlayState::Init if self.game.generated => {
+                self.game.play_state = PlayState::Playing(SystemTime::now());
+            }
+            _ => return,
+        }
+        #[cfg(feature = "multiplayer")]
+        if self.propose(x, y, StepKind::Hint) {
+            return;
+        }
+
+        if self.game.is_in_bounds(x, y) {
+            self.game.clicks.right += 1;
+            if self.game[(x, y)].visibility() == Visibility::Show {
+                self.game.clicks.wasted += 1;
+            }
+        }
+        self.game.hint_(x, y);
+        self.persist(frame);
+    }
+
+    /// Reveals the neighbors of a shown field if enough hints surround it, without ever revealing
+    /// a hidden field itself.
+    fn chord(&mut self, frame: &mut eframe::Frame, x: i16, y: i16) {
+        if !self.game.is_in_bounds(x, y) || self.game[(x, y)].visibility() != Visibility::Show {
+            return;
+        }
+        self.click(frame, x, y);
+    }
+
+    /// Toggles a hint placed by the user, which is denied if more hints than mines would be
+    /// placed and hints are capped.
+    fn user_hint(&mut self, frame: &mut eframe::Frame, x: i16, y: i16) {
+        let placing =
+            self.game.is_in_bounds(x, y) && self.game[(x, y)].visibility() == Visibility::Hide;
+        if self.settings.cap_hints && placing && self.game.mines_remaining() <= 0 {
+            self.counter_flash = true;
+            return;
+        }
+
+        let hints = self.game.mines_remaining();
+        self.hint(frame, x, y);
+        if self.game.mines_remaining() != hints {
+            self.feedback(Event::Flag);
+        }
+    }
+
+    fn solver_hint(&mut self) {
+        if self.remaining_solver_hints() == Some(0) {
+            return;
+        }
+
+        let penalty = Duration::from_secs(self.settings.solver_hint_penalty as u64);
+        if let Some(pos) = self.game.solver_hint(penalty) {
+            self.solver_hint = Some(pos);
+        }
+    }
+
+    /// How long the timer has been paused, because no input arrived within the idle timeout.
+    fn idle_duration(&self) -> Duration {
+        let (Some(last_input), Some(timeout)) = (self.last_input, self.settings.idle_timeout)
+        else {
+            return Duration::ZERO;
+        };
+        last_input
+            .elapsed()
+            .saturating_sub(Duration::from_secs(timeout as u64))
+    }
+
+    fn remaining_solver_hints(&self) -> Option<u32> {
+        let limit = self.settings.solver_hint_limit?;
+        Some(limit.saturating_sub(self.game.solver_hints_used))
+    }
+
+    fn auto_move(&mut self, frame: &mut eframe::Frame, mv: ai::Move) {
+        self.game.assisted = true;
+        match mv.action {
+            ai::Action::Reveal => self.click(frame, mv.x, mv.y),
+            ai::Action::Hint => self.hint(frame, mv.x, mv.y),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Prompt {
+    Restart,
+    /// A dropped bundle, which is kept until it's confirmed.
+    RestoreBundle,
+    ChangeDifficulty(Difficulty),
+    /// A running game was restored, the timer stays stopped at the duration until it's resumed.
+    Resume(Duration),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+enum Difficulty {
+    Easy = 0,
+    Medium = 1,
+    Hard = 2,
+}
+
+impl Difficulty {
+    /// The difficulty of boards with this size.
+    fn from_size(width: i16, height: i16) -> Option<Self> {
+        match (width, height) {
+            (20, 14) => Some(Difficulty::Easy),
+            (30, 18) => Some(Difficulty::Medium),
+            (40, 24) => Some(Difficulty::Hard),
+            _ => None,
+        }
+    }
+
+    /// The share of fields that are mines.
+    fn mine_density(self) -> std::ops::Range<f64> {
+        match self {
+            Difficulty::Easy => 0.12..0.13,
+            Difficulty::Medium => 0.16..0.17,
+            Difficulty::Hard => 0.21..0.22,
+        }
+    }
+}
+
+impl Display for Difficulty {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Difficulty::Easy => write!(f, "Easy"),
+            Difficulty::Medium => write!(f, "Medium"),
+            Difficulty::Hard => write!(f, "Hard"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "GameSerde")]
+struct Game {
+    difficulty: Difficulty,
+    unambigous: bool,
+    num_mines: u16,
+    play_state: PlayState,
+    width: i16,
+    height: i16,
+    fields: Board,
+    #[serde(default)]
+    loss: Option<Loss>,
+    /// Whether the solver made moves in this game.
+    #[serde(default)]
+    assisted: bool,
+    /// Whether the mines were known before the game, because the board was played before. Only
+    /// the best time on the board is kept, not a record.
+    #[serde(default)]
+    known_board: bool,
+    #[serde(default)]
+    solver_hints_used: u32,
+    /// Whether mines have been placed, which might happen before the game is started.
+    #[serde(default)]
+    generated: bool,
+    #[serde(default)]
+    hot_seat: Option<HotSeat>,
+    #[serde(default)]
+    clicks: Clicks,
+    #[serde(default)]
+    steps: Vec<Step>,
+    /// Seed of the mine placement, which is chosen when the mines are placed.
+    #[serde(default)]
+    seed: Option<u64>,
+    /// The field the first click was on, which is kept free of mines.
+    #[serde(default)]
+    opening: Option<(i16, i16)>,
+    /// Kept up to date when fields are hinted or shown, `None` until counted after a restore or
+    /// mines were placed.
+    #[serde(skip)]
+    counts: Option<Counts>,
+}
+
+/// Boards used to be stored as a single list, which is chunked when the game is restored.
+#[derive(Deserialize)]
+#[serde(rename = "Game")]
+struct GameSerde {
+    difficulty: Difficulty,
+    unambigous: bool,
+    num_mines: u16,
+    play_state: PlayState,
+    width: i16,
+    height: i16,
+    fields: Vec<Field>,
+    #[serde(default)]
+    loss: Option<Loss>,
+    #[serde(default)]
+    assisted: bool,
+    #[serde(default)]
+    known_board: bool,
+    #[serde(default)]
+    solver_hints_used: u32,
+    #[serde(default)]
+    generated: bool,
+    #[serde(default)]
+    hot_seat: Option<HotSeat>,
+    #[serde(default = "default_clicks")]
+    clicks: ClicksSerde,
+    #[serde(default)]
+    steps: Vec<Step>,
+    #[serde(default)]
+    seed: Option<u64>,
+    #[serde(default)]
+    opening: Option<(i16, i16)>,
+}
+
+fn default_clicks() -> ClicksSerde {
+    ClicksSerde::Clicks(Clicks::default())
+}
+
+impl TryFrom<GameSerde> for Game {
+    type Error = &'static str;
+
+    fn try_from(g: GameSerde) -> Result<Self, Self::Error> {
+        let fields = Board::from_rows(g.width, g.height, &g.fields)
+            .ok_or("the fields don't match the size of the board")?;
+        Ok(Self {
+            difficulty: g.difficulty,
+            unambigous: g.unambigous,
+            num_mines: g.num_mines,
+            play_state: g.play_state,
+            width: g.width,
+            height: g.height,
+            fields,
+            loss: g.loss,
+            assisted: g.assisted,
+            known_board: g.known_board,
+            solver_hints_used: g.solver_hints_used,
+            generated: g.generated,
+            hot_seat: g.hot_seat,
+            clicks: g.clicks.into(),
+            steps: g.steps,
+            seed: g.seed,
+            opening: g.opening,
+            counts: None,
+        })
+    }
+}
+
+/// A game that can't be restored is replaced by a new one, instead of losing the stats and
+/// records stored with it.
+fn game_or_new<'de, D>(deserializer: D) -> Result<Game, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let game = <GameSerde as serde::Deserialize>::deserialize(deserializer)?;
+    Ok(Game::try_from(game).unwrap_or_else(|e| {
+        log::warn!("failed to restore the game: {e}");
+        Game::easy(false)
+    }))
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct Counts {
+    hints: u16,
+    shown: u32,
+    /// Free fields that still have to be shown to win.
+    hidden_free: u32,
+}
+
+impl Game {
+    fn easy(unambigous: bool) -> Self {
+        let difficulty = Difficulty::Easy;
+        Self::new(20, 14, difficulty.mine_density(), difficulty, unambigous)
+    }
+
+    fn medium(unambigous: bool) -> Self {
+        let difficulty = Difficulty::Medium;
+        Self::new(30, 18, difficulty.mine_density(), difficulty, unambigous)
+    }
+
+    fn hard(unambigous: bool) -> Self {
+        let difficulty = Difficulty::Hard;
+        Self::new(40, 24, difficulty.mine_density(), difficulty, unambigous)
+    }
+
+    fn new(
+        width: i16,
+        height: i16,
+        probability_range: std::ops::Range<f64>,
+        difficulty: Difficulty,
+        unambigous: bool,
+    ) -> Self {
+        let len = (width * height) as usize;
+
+        let min = (probability_range.start * len as f64) as u16;
+        let max = (probability_range.end * len as f64) as u16;
+        let num_mines = rand::thread_rng().gen_range(min..max);
+
+        Self {
+            difficulty,
+            unambigous,
+            num_mines,
+            play_state: PlayState::Init,
+            width,
+            height,
+            fields: Board::new(width, height, Field::free(0)),
+            loss: None,
+            assisted: false,
+            known_board: false,
+            solver_hints_used: 0,
+            generated: false,
+            hot_seat: None,
+            clicks: Clicks::default(),
+            steps: Vec::new(),
+            seed: None,
+            opening: None,
+            counts: Some(Counts {
+                hidden_free: len as u32,
+                ..Counts::default()
+            }),
+        }
+    }
+
+    /// Generates the board of a record or a shared board ID again.
+    fn from_seed(difficulty: Difficulty, unambigous: bool, board: &BoardSeed) -> Self {
+        let mut game = Self::new(board.width, board.height, 0.0..1.0, difficulty, unambigous);
+        game.num_mines = board.num_mines;
+        game.seed = Some(board.seed);
+        game.gen_board();
+        if let Some((x, y)) = board.opening {
+            game.clear_opening(x, y);
+        }
+        game.opening = board.opening;
+        game.generated = true;
+        game.known_board = true;
+        game.recount();
+        game
+    }
+
+    fn clear_board(&mut self) {
+        for f in self.fields.iter_mut() {
+            f.set_state(FieldState::Free(0));
+        }
+        self.counts = None;
+    }
+
+    /// Generates a board on which the field at (x, y) has no neighboring mines. Only boards that
+    /// turn out to be ambigous are generated again.
+    fn gen_opening(&mut self, x: i16, y: i16) {
+        self.gen_opening_counted(x, y, &AtomicU32::new(0), &AtomicBool::new(false));
+    }
+
+    /// Like [`Self::gen_opening`], but counts the boards that were checked in `checked`. Stops
+    /// once `cancel` is set and returns false.
+    fn gen_opening_counted(
+        &mut self,
+        x: i16,
+        y: i16,
+        checked: &AtomicU32,
+        cancel: &AtomicBool,
+    ) -> bool {
+        #[cfg(feature = "rayon")]
+        if self.unambigous {
+            if !self.gen_unambigous(x, y, checked, cancel) {
+                return false;
+            }
+            self.opening = Some((x, y));
+            self.generated = true;
+            self.recount();
+            return true;
+        }
+
+        loop {
+            if cancel.load(Ordering::Relaxed) {
+                return false;
+            }
+            self.gen_board();
+            self.clear_opening(x, y);
+            checked.fetch_add(1, Ordering::Relaxed);
+            if !self.unambigous || self.is_unambigous(x, y) {
+                break;
+            }
+
+            self.clear_board();
+            self.seed = None;
+        }
+
+        self.opening = Some((x, y));
+        self.generated = true;
+        self.recount();
+        true
+    }
+
+    /// Generates the board and reveals a random opening, without starting the timer.
+    fn auto_open(&mut self) -> (i16, i16) {
+        let mut rng = rand::thread_rng();
+        let x = rng.gen_range(0..self.width);
+        let y = rng.gen_range(0..self.height);
+        self.gen_opening(x, y);
+        self.show_neighbors(x, y);
+        self.record(x, y, StepKind::Reveal);
+        (x, y)
+    }
+
+    fn record(&mut self, x: i16, y: i16, kind: StepKind) {
+        let time = self.play_duration().as_millis() as u32;
+        self.steps.push(Step { x, y, kind, time });
+    }
+
+    /// Identifies the mine placement, which is generated again from the size, mine count, seed
+    /// and the field the mines were moved away from.
+    fn board_id(&self) -> Option<String> {
+        self.board_seed().map(|b| b.id())
+    }
+
+    fn metrics(&self, time: Duration) -> Metrics {
+        Metrics {
+            time,
+            bbbv: self.bbbv(),
+            clicks: self.clicks.total(),
+            wasted: self.clicks.wasted,
+            solver_hints: self.solver_hints_used,
+        }
+    }
+
+    fn board_seed(&self) -> Option<BoardSeed> {
+        Some(BoardSeed {
+            width: self.width,
+            height: self.height,
+            num_mines: self.num_mines,
+            seed: self.seed.filter(|_| self.generated)?,
+            opening: self.opening,
+        })
+    }
+
+    /// A new board of the difficulty to race on, which is only generated by the players.
+    #[cfg(feature = "multiplayer")]
+    fn race_board(difficulty: Difficulty) -> BoardSeed {
+        let game = match difficulty {
+            Difficulty::Easy => Game::easy(false),
+            Difficulty::Medium => Game::medium(false),
+            Difficulty::Hard => Game::hard(false),
+        };
+        BoardSeed {
+            width: game.width,
+            height: game.height,
+            num_mines: game.num_mines,
+            seed: rand::random(),
+            opening: Some((game.width / 2, game.height / 2)),
+        }
+    }
+
+    #[cfg(feature = "multiplayer")]
+    fn race_progress(&self) -> multiplayer::Progress {
+        let total = (self.fields.len() - self.num_mines as usize) as u32;
+        let revealed = self.num_shown() as u32;
+        match self.play_state {
+            PlayState::Won(duration) => multiplayer::Progress::Won {
+                time_ms: duration.as_millis() as u64,
+            },
+            PlayState::Lost(_) => multiplayer::Progress::Lost { revealed, total },
+            _ => multiplayer::Progress::Playing { revealed, total },
+        }
+    }
+
+    /// Returns the duration if the game was won.
+    fn click(&mut self, x: i16, y: i16) -> Option<Duration> {
+        if !self.is_in_bounds(x, y) {
+            return None;
+        }
+        let kind = match self[(x, y)].visibility() {
+            Visibility::Show => StepKind::Chord,
+            _ => StepKind::Reveal,
+        };
+        self.record(x, y, kind);
+
+        let first = self.play_state == PlayState::Init;
+        if first {
+            if !self.generated {
+                self.gen_opening(x, y);
+            }
+            self.play_state = PlayState::Playing(SystemTime::now());
+        }
+
+        let field = &mut self[(x, y)];
+        if field.visibility() == Visibility::Hint {
+            return None;
+        }
+        match field.state() {
             FieldState::Free(neighbors) => {
-                if let Visibility::Show = field.visibility {
+                if let Visibility::Show = field.visibility() {
                     let hinted_adjacents = self.hinted_adjacents(x, y);
                     if hinted_adjacents.num() == neighbors {
                         self.show_if_not_hinted(x - 1, y - 1);
@@ -265,6 +1651,10 @@ impl Game {
                         self.show_if_not_hinted(x + 1, y - 1);
                         self.show_if_not_hinted(x + 1, y + 0);
                         self.show_if_not_hinted(x + 1, y + 1);
+
+                        if let Some(loss) = &mut self.loss {
+                            loss.chord = Some((x, y));
+                        }
                     }
                 }
 
@@ -282,12 +1672,44 @@ impl Game {
         if !self.is_in_bounds(x, y) {
             return;
         }
+        self.record(x, y, StepKind::Hint);
 
-        let field = &mut self[(x, y)];
-        if field.visibility == Visibility::Hint {
-            field.visibility = Visibility::Hide;
-        } else if field.visibility == Visibility::Hide {
-            field.visibility = Visibility::Hint;
+        let field = self[(x, y)];
+        if field.visibility() == Visibility::Hint {
+            self.set_visibility(x, y, Visibility::Hide);
+        } else if field.visibility() == Visibility::Hide {
+            self.set_visibility(x, y, Visibility::Hint);
+            if let Some(hot_seat) = &mut self.hot_seat {
+                hot_seat.set_hint_owner(self.width as usize * y as usize + x as usize);
+            }
+        }
+    }
+
+    /// Returns a field that is known to be safe, and adds the `penalty` to the play duration.
+    fn solver_hint(&mut self, penalty: Duration) -> Option<(i16, i16)> {
+        let PlayState::Playing(start) = self.play_state else { return None };
+        let &pos = self.analyze().safe.first()?;
+        self.solver_hints_used += 1;
+        self.play_state = PlayState::Playing(start - penalty);
+        Some(pos)
+    }
+
+    /// Hides all fields again, to replay the same board.
+    fn retry(&mut self) {
+        for f in self.fields.iter_mut() {
+            f.set_visibility_unchecked(Visibility::Hide);
+        }
+        self.counts = None;
+        self.recount();
+        self.play_state = PlayState::Init;
+        self.loss = None;
+        self.solver_hints_used = 0;
+        self.clicks = Clicks::default();
+        self.steps.clear();
+        // knowing where the mines are doesn't make for a fair time
+        self.known_board = true;
+        if let Some(hot_seat) = &mut self.hot_seat {
+            *hot_seat = HotSeat::new(self.fields.len());
         }
     }
 
@@ -296,8 +1718,9 @@ impl Game {
             return;
         };
         let duration = SystemTime::now().duration_since(start).unwrap();
-        self[(x, y)].visibility = Visibility::Show;
+        self.set_visibility(x, y, Visibility::Show);
         self.play_state = PlayState::Lost(duration);
+        self.loss = Some(Loss { x, y, chord: None });
     }
 
     fn check_if_won(&mut self) -> Option<Duration> {
@@ -311,8 +1734,14 @@ impl Game {
         let duration = SystemTime::now().duration_since(start).unwrap();
         self.play_state = PlayState::Won(duration);
         for f in self.fields.iter_mut() {
-            f.visibility = Visibility::Show;
+            f.set_visibility_unchecked(Visibility::Show);
         }
+        let shown = self.fields.len() as u32;
+        self.counts = Some(Counts {
+            hints: 0,
+            shown,
+            hidden_free: 0,
+        });
         Some(duration)
     }
 
@@ -322,11 +1751,11 @@ impl Game {
         }
 
         let field = &mut self[(x, y)];
-        if field.visibility == Visibility::Show || field.visibility == Visibility::Hint {
+        if field.visibility() == Visibility::Show || field.visibility() == Visibility::Hint {
             return;
         }
 
-        if let FieldState::Mine = field.state {
+        if let FieldState::Mine = field.state() {
             self.lose(x, y);
             return;
         }
@@ -339,14 +1768,14 @@ impl Game {
             return;
         }
 
-        let field = &mut self[(x, y)];
-        if field.visibility == Visibility::Show {
+        let field = self[(x, y)];
+        if field.visibility() == Visibility::Show {
             return;
         }
 
-        field.visibility = Visibility::Show;
+        self.set_visibility(x, y, Visibility::Show);
 
-        if field.state != FieldState::Free(0) {
+        if field.state() != FieldState::Free(0) {
             return;
         }
 
@@ -360,14 +1789,59 @@ impl Game {
         self.show_neighbors(x + 1, y + 1);
     }
 
-    fn open_mine_count(&self) -> i16 {
-        let mut hints = 0;
-        for f in self.fields.iter() {
-            if let Visibility::Hint = f.visibility {
-                hints += 1;
+    /// The number of mines minus the number of hints, which can be negative.
+    fn mines_remaining(&self) -> i16 {
+        self.num_mines as i16 - self.counts().hints as i16
+    }
+
+    fn num_shown(&self) -> usize {
+        self.counts().shown as usize
+    }
+
+    fn counts(&self) -> Counts {
+        self.counts.unwrap_or_else(|| {
+            let mut counts = Counts::default();
+            for f in self.fields.iter() {
+                match f.visibility() {
+                    Visibility::Hide => (),
+                    Visibility::Hint => counts.hints += 1,
+                    Visibility::Show => counts.shown += 1,
+                }
+                if let (FieldState::Free(_), false) =
+                    (f.state(), f.visibility() == Visibility::Show)
+                {
+                    counts.hidden_free += 1;
+                }
             }
+            counts
+        })
+    }
+
+    /// Stores the counts, so they don't have to be counted again.
+    fn recount(&mut self) {
+        self.counts = Some(self.counts());
+    }
+
+    /// Changes the visibility of a field and updates the counts.
+    fn set_visibility(&mut self, x: i16, y: i16, visibility: Visibility) {
+        let mut counts = self.counts();
+        let field = &mut self[(x, y)];
+        match field.visibility() {
+            Visibility::Hide => (),
+            Visibility::Hint => counts.hints -= 1,
+            Visibility::Show => counts.shown -= 1,
+        }
+        match visibility {
+            Visibility::Hide => (),
+            Visibility::Hint => counts.hints += 1,
+            Visibility::Show => counts.shown += 1,
         }
-        self.num_mines as i16 - hints
+        if let FieldState::Free(_) = field.state() {
+            let shown = |v| (v == Visibility::Show) as u32;
+            counts.hidden_free = counts.hidden_free + shown(field.visibility()) - shown(visibility);
+        }
+        field.set_visibility_unchecked(visibility);
+        self.counts = Some(counts);
     }
 
     fn play_duration(&self) -> Duration {
@@ -388,13 +1862,31 @@ impl std::ops::Index<(i16, i16)> for Game {
     type Output = Field;
 
     fn index(&self, (x, y): (i16, i16)) -> &Self::Output {
-        &self.fields[self.width as usize * y as usize + x as usize]
+        &self.fields[(x, y)]
     }
 }
 
 impl std::ops::IndexMut<(i16, i16)> for Game {
     fn index_mut(&mut self, (x, y): (i16, i16)) -> &mut Self::Output {
-        &mut self.fields[self.width as usize * y as usize + x as usize]
+        &mut self.fields[(x, y)]
+    }
+}
+
+/// The move that lost a game.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct Loss {
+    x: i16,
+    y: i16,
+    /// The number that was chorded, if the mine was revealed by a chord.
+    chord: Option<(i16, i16)>,
+}
+
+impl Loss {
+    fn summary<'a>(&self, tr: &'a Translations) -> &'a str {
+        match self.chord {
+            Some(_) => &tr.lost_to_chord,
+            None => &tr.lost_to_click,
+        }
     }
 }
 
@@ -453,26 +1945,101 @@ impl<'de> serde::Deserialize<'de> for PlayState {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
-struct Field {
-    visibility: Visibility,
-    state: FieldState,
-}
+/// A field packed into a single byte: the number of neighboring mines in the lowest 4 bits, then
+/// the mine bit and 2 bits of visibility.
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct Field(u8);
+
+const FIELD_NEIGHBORS: u8 = 0b1111;
+const FIELD_MINE: u8 = 1 << 4;
+const FIELD_VISIBILITY_SHIFT: u8 = 5;
 
 impl Field {
     fn free(neighbors: u8) -> Self {
-        Self {
-            visibility: Visibility::Hide,
-            state: FieldState::Free(neighbors),
+        let mut field = Self(0);
+        field.set_state(FieldState::Free(neighbors));
+        field
+    }
+
+    fn state(self) -> FieldState {
+        match self.0 & FIELD_MINE {
+            0 => FieldState::Free(self.0 & FIELD_NEIGHBORS),
+            _ => FieldState::Mine,
         }
     }
-}
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
-enum Visibility {
-    Hide,
-    Hint,
-    Show,
+    fn set_state(&mut self, state: FieldState) {
+        let bits = match state {
+            FieldState::Free(neighbors) => neighbors & FIELD_NEIGHBORS,
+            FieldState::Mine => FIELD_MINE,
+        };
+        self.0 = (self.0 & !(FIELD_MINE | FIELD_NEIGHBORS)) | bits;
+    }
+
+    fn visibility(self) -> Visibility {
+        match self.0 >> FIELD_VISIBILITY_SHIFT {
+            0 => Visibility::Hide,
+            1 => Visibility::Hint,
+            _ => Visibility::Show,
+        }
+    }
+
+    /// Only changes the field, the counts of the game have to be updated through
+    /// [`Game::set_visibility`] or recounted.
+    fn set_visibility_unchecked(&mut self, visibility: Visibility) {
+        let mask = (1 << FIELD_VISIBILITY_SHIFT) - 1;
+        self.0 = (self.0 & mask) | (visibility as u8) << FIELD_VISIBILITY_SHIFT;
+    }
+}
+
+impl std::fmt::Debug for Field {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Field")
+            .field("visibility", &self.visibility())
+            .field("state", &self.state())
+            .finish()
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Field")]
+struct FieldSerde {
+    visibility: Visibility,
+    state: FieldState,
+}
+
+impl serde::Serialize for Field {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let f = FieldSerde {
+            visibility: self.visibility(),
+            state: self.state(),
+        };
+
+        f.serialize(serializer)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Field {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let f = FieldSerde::deserialize(deserializer)?;
+        let mut field = Field(0);
+        field.set_state(f.state);
+        field.set_visibility_unchecked(f.visibility);
+        Ok(field)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+enum Visibility {
+    Hide,
+    Hint,
+    Show,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -485,8 +2052,481 @@ fn format_duration(duration: Duration) -> String {
     let total_secs = duration.as_secs();
     let secs = total_secs % 60;
     let mins = total_secs / 60;
+    format!("{mins:2}:{secs:02}")
+}
+
+/// Formats a short duration in seconds with centiseconds.
+fn format_seconds(duration: Duration) -> String {
+    format!("{:.2} s", duration.as_secs_f32())
+}
+
+/// Formats the duration with centiseconds, which are what sets competitive times apart.
+fn format_duration_precise(duration: Duration) -> String {
     let sub_secs = duration.subsec_millis() / 10;
-    format!("{mins:2}:{secs:02}.{sub_secs:02}")
+    format!("{}.{sub_secs:02}", format_duration(duration))
+}
+
+/// Outlines the widget while it has keyboard focus, frameless buttons don't show it otherwise.
+/// A frameless button with an icon, which is laid out once and kept in the cache instead of every
+/// frame like the text of a [`Button`].
+fn icon_button(
+    ui: &mut Ui,
+    galleys: &mut GalleyCache,
+    icon: &'static str,
+    font_id: FontId,
+    selected: bool,
+) -> Response {
+    let galley = galleys.get(ui.painter(), icon, font_id);
+    let size = galley
+        .size()
+        .max(Vec2::new(0.0, ui.spacing().interact_size.y));
+    let (rect, resp) = ui.allocate_at_least(size, Sense::click());
+    resp.widget_info(|| WidgetInfo::labeled(WidgetType::Button, icon));
+
+    if ui.is_rect_visible(rect) {
+        let visuals = ui.style().interact(&resp);
+        if selected {
+            let selection = ui.visuals().selection;
+            let painter = ui.painter();
+            painter.rect(rect, Rounding::ZERO, selection.bg_fill, selection.stroke);
+        }
+        let pos = ui.layout().align_size_within_rect(galley.size(), rect).min;
+        ui.painter()
+            .galley_with_color(pos, galley, visuals.text_color());
+    }
+    if let (Some(cursor), true) = (ui.visuals().interact_cursor, resp.hovered()) {
+        ui.ctx().set_cursor_icon(cursor);
+    }
+    resp
+}
+
+fn focus_outline(resp: Response) -> Response {
+    if resp.has_focus() {
+        let stroke = resp.ctx.style().visuals.selection.stroke;
+        let painter = resp.ctx.layer_painter(resp.layer_id);
+        painter.rect_stroke(resp.rect.expand(2.0), 4.0, stroke);
+    }
+    resp
+}
+
+fn cell_rect(
+    height: i16,
+    board_offset: Pos2,
+    cell_size: Vec2,
+    x: i16,
+    y: i16,
+    flipped: bool,
+) -> Rect {
+    let cell_idx = if flipped {
+        Vec2::new((height - y - 1) as f32, x as f32)
+    } else {
+        Vec2::new(x as f32, y as f32)
+    };
+    Rect::from_min_size(board_offset + cell_idx * cell_size, cell_size)
+}
+
+fn paint_number(
+    painter: &Painter,
+    theme: &Theme,
+    galleys: &mut GalleyCache,
+    cell_rect: Rect,
+    n: u8,
+    font_id: FontId,
+    shape: bool,
+) {
+    const DIGITS: [&str; 8] = ["1", "2", "3", "4", "5", "6", "7", "8"];
+    const ROMAN: [&str; 8] = ["I", "II", "III", "IV", "V", "VI", "VII", "VIII"];
+
+    let color = theme.numbers[n as usize - 1];
+    if theme.emoji {
+        let keycap = format!("{n}\u{fe0f}\u{20e3}");
+        if paint_emoji(painter, cell_rect, &keycap, color) {
+            return;
+        }
+    }
+    match theme.glyphs {
+        Glyphs::Digits => {
+            let galley = galleys.get(painter, DIGITS[n as usize - 1], font_id);
+            paint_text(painter, theme, cell_rect, galley, color);
+        }
+        Glyphs::Roman => {
+            let text = ROMAN[n as usize - 1];
+            // keep the long numerals inside the cell
+            let font_id = FontId {
+                size: font_id.size * (2.0 / text.len() as f32).min(1.0),
+                ..font_id
+            };
+            let galley = galleys.get(painter, text, font_id);
+            paint_text(painter, theme, cell_rect, galley, color);
+        }
+        Glyphs::Dice => {
+            let rect = Rect::from_center_size(cell_rect.center(), 0.7 * cell_rect.size());
+            paint_pips(painter, rect, n, color);
+        }
+        Glyphs::Dominoes => {
+            let rect =
+                Rect::from_center_size(cell_rect.center(), Vec2::new(0.9, 0.5) * cell_rect.size());
+            let stroke = Stroke::new((0.04 * cell_rect.height()).max(1.0), color);
+            painter.line_segment([rect.center_top(), rect.center_bottom()], stroke);
+            let left = Rect::from_min_max(rect.min, rect.center_bottom());
+            let right = Rect::from_min_max(rect.center_top(), rect.max);
+            paint_pips(painter, left.shrink(0.1 * left.width()), n - n / 2, color);
+            paint_pips(painter, right.shrink(0.1 * right.width()), n / 2, color);
+        }
+    }
+    if shape {
+        paint_number_shape(painter, cell_rect, n, color);
+    }
+}
+
+/// Number texts laid out once per font, instead of for every field in every frame.
+#[derive(Default)]
+struct GalleyCache {
+    pixels_per_point: f32,
+    galleys: HashMap<(&'static str, u32, FontFamily), Arc<Galley>>,
+}
+
+impl GalleyCache {
+    fn get(&mut self, painter: &Painter, text: &'static str, font_id: FontId) -> Arc<Galley> {
+        // growing numbers are laid out in many sizes, which aren't worth keeping around
+        let pixels_per_point = painter.ctx().pixels_per_point();
+        if self.pixels_per_point != pixels_per_point || self.galleys.len() > MAX_CACHED_GALLEYS {
+            self.pixels_per_point = pixels_per_point;
+            self.galleys.clear();
+        }
+
+        let key = (text, font_id.size.to_bits(), font_id.family.clone());
+        let galley = self.galleys.entry(key).or_insert_with(|| {
+            painter.layout_no_wrap(text.to_string(), font_id, Color32::TEMPORARY_COLOR)
+        });
+        Arc::clone(galley)
+    }
+}
+
+/// Labels of the top bar laid out once, and again only when the value they show changes.
+#[derive(Default)]
+struct LabelCache {
+    labels: HashMap<&'static str, (u64, Arc<Galley>)>,
+}
+
+impl LabelCache {
+    /// The label in `slot`, which calls `text` only if `key` changed since it was last laid out.
+    /// The text needs an explicit color, since it is part of the layout.
+    fn get(
+        &mut self,
+        ui: &Ui,
+        slot: &'static str,
+        key: impl Hash,
+        text: impl FnOnce() -> RichText,
+    ) -> WidgetText {
+        let mut hasher = DefaultHasher::new();
+        (key, ui.ctx().pixels_per_point().to_bits()).hash(&mut hasher);
+        let hash = hasher.finish();
+        if let Some((h, galley)) = self.labels.get(slot) {
+            if *h == hash {
+                return WidgetText::Galley(Arc::clone(galley));
+            }
+        }
+
+        let text = WidgetText::from(text());
+        let galley = text
+            .into_galley(ui, Some(false), f32::INFINITY, TextStyle::Body)
+            .galley;
+        self.labels.insert(slot, (hash, Arc::clone(&galley)));
+        WidgetText::Galley(galley)
+    }
+}
+
+fn paint_text(
+    painter: &Painter,
+    theme: &Theme,
+    cell_rect: Rect,
+    galley: Arc<Galley>,
+    color: Color32,
+) {
+    let rect = Rect::from_center_size(cell_rect.center(), galley.size());
+    if theme.bold_numbers {
+        // there is no bold font, so thicken the glyphs by painting them slightly offset
+        let offset = (galley.size().y * 0.04).max(1.0);
+        for d in [Vec2::new(-offset, 0.0), Vec2::new(offset, 0.0)] {
+            painter.galley_with_color(rect.min + d, Arc::clone(&galley), color);
+        }
+    }
+    painter.galley_with_color(rect.min, galley, color);
+}
+
+/// Paints `emoji` centered in the cell, returns false without painting anything if the fonts lack
+/// any of its glyphs.
+fn paint_emoji(painter: &Painter, cell_rect: Rect, emoji: &str, color: Color32) -> bool {
+    let font_id = FontId::proportional(cell_rect.height() * 0.7);
+    if !painter.fonts(|f| f.has_glyphs(&font_id, emoji)) {
+        return false;
+    }
+    painter.text(
+        cell_rect.center(),
+        Align2::CENTER_CENTER,
+        emoji,
+        font_id,
+        color,
+    );
+    true
+}
+
+/// Paints `n` pips on a 3 by 3 grid inside `rect`, like on a die. Numbers above 6 fill the
+/// remaining grid positions.
+fn paint_pips(painter: &Painter, rect: Rect, n: u8, color: Color32) {
+    const PIPS: [&[(u8, u8)]; 9] = [
+        &[],
+        &[(1, 1)],
+        &[(0, 0), (2, 2)],
+        &[(0, 0), (1, 1), (2, 2)],
+        &[(0, 0), (2, 0), (0, 2), (2, 2)],
+        &[(0, 0), (2, 0), (1, 1), (0, 2), (2, 2)],
+        &[(0, 0), (2, 0), (0, 1), (2, 1), (0, 2), (2, 2)],
+        &[(0, 0), (2, 0), (0, 1), (1, 1), (2, 1), (0, 2), (2, 2)],
+        &[
+            (0, 0),
+            (1, 0),
+            (2, 0),
+            (0, 1),
+            (2, 1),
+            (0, 2),
+            (1, 2),
+            (2, 2),
+        ],
+    ];
+    let size = rect.width().min(rect.height());
+    let square = Rect::from_center_size(rect.center(), Vec2::splat(size));
+    let r = size * 0.12;
+    for &(x, y) in PIPS[n as usize] {
+        let fraction = Vec2::new(x as f32, y as f32) / 2.0;
+        let pos = square.shrink(r).lerp_inside(fraction);
+        painter.circle_filled(pos, r, color);
+    }
+}
+
+/// The built-in palette with the theme options chosen in the settings.
+fn builtin_theme(settings: &Settings, visuals: &Visuals) -> Theme {
+    Theme {
+        glyphs: settings.glyphs,
+        emoji: settings.emoji,
+        ..Theme::builtin(settings.palette, visuals)
+    }
+}
+
+/// What a field shows, mirroring the built-in drawing of the board.
+fn cell_state(play_state: PlayState, field: Field, mine_hidden: bool) -> CellState {
+    use FieldState::{Free, Mine};
+    use Visibility::{Hide, Hint, Show};
+    match (play_state, field.state(), field.visibility()) {
+        (PlayState::Init | PlayState::Playing(_), _, Hide) => CellState::Hidden,
+        (PlayState::Init | PlayState::Playing(_), _, Hint) => CellState::Flagged,
+        (PlayState::Init | PlayState::Playing(_), Free(n), Show) => CellState::Revealed(n),
+        (PlayState::Init | PlayState::Playing(_), Mine, Show) => CellState::Mine,
+        (PlayState::Won(_), Free(n), _) => CellState::Revealed(n),
+        (PlayState::Won(_), Mine, Hint) => CellState::Flagged,
+        (PlayState::Won(_), Mine, _) => CellState::Mine,
+        (PlayState::Lost(_), Mine, Hide) if mine_hidden => CellState::Hidden,
+        (PlayState::Lost(_), Free(_), Hide) => CellState::Hidden,
+        (PlayState::Lost(_), Free(_), Hint) => CellState::WrongFlag,
+        (PlayState::Lost(_), Free(n), Show) => CellState::Revealed(n),
+        (PlayState::Lost(_), Mine, Hide) => CellState::Mine,
+        (PlayState::Lost(_), Mine, Hint) => CellState::Flagged,
+        (PlayState::Lost(_), Mine, Show) => CellState::Exploded,
+    }
+}
+
+fn paint_backdrop(painter: &Painter, theme: &Theme, rect: Rect) {
+    match theme.backdrop {
+        Backdrop::Panel => (),
+        Backdrop::Solid(color) => {
+            painter.rect_filled(rect, 0.0, color);
+        }
+        Backdrop::Gradient { top, bottom } => {
+            let mut mesh = Mesh::default();
+            mesh.colored_vertex(rect.left_top(), top);
+            mesh.colored_vertex(rect.right_top(), top);
+            mesh.colored_vertex(rect.left_bottom(), bottom);
+            mesh.colored_vertex(rect.right_bottom(), bottom);
+            mesh.add_triangle(0, 1, 2);
+            mesh.add_triangle(2, 1, 3);
+            painter.add(mesh);
+        }
+    }
+    if let Some(texture) = theme.backdrop_texture {
+        let uv = Rect::from_min_max(Pos2::ZERO, Pos2::new(1.0, 1.0));
+        painter.image(texture, rect, uv, Color32::WHITE);
+    }
+}
+
+/// Paints a cell with a flat color, or a sprite if the theme has any.
+/// Sprites are tinted with `color` where it differs from the regular color of the cell, so hints,
+/// safe cells and the exploded mine still stand out.
+/// With a `mesh` flat cells are added to it, instead of being painted one by one.
+fn paint_cell(
+    painter: &Painter,
+    mesh: &mut Option<Mesh>,
+    theme: &Theme,
+    cell_rect: Rect,
+    sprite: Sprite,
+    color: Color32,
+    stroke: Stroke,
+) {
+    match &theme.sprites {
+        Some(sprites) => {
+            let rect = cell_rect.shrink(0.5 * theme.gap * cell_rect.height());
+            let regular = match sprite {
+                Sprite::Hidden => theme.hidden,
+                Sprite::Shown => theme.shown,
+                Sprite::Flag | Sprite::Mine => color,
+            };
+            let tint = if color == regular {
+                Color32::WHITE
+            } else {
+                color
+            };
+            painter.image(sprites.texture, rect, sprites.uv(sprite), tint);
+        }
+        None => {
+            let size = cell_rect.height();
+            let rect = cell_rect.shrink(0.5 * theme.gap * size);
+            match (theme.cell_style, sprite) {
+                (CellStyle::Beveled, Sprite::Hidden) => {
+                    painter.rect_filled(rect, 0.0, color);
+                    paint_bevel(painter, rect, (0.12 * size).max(1.0));
+                }
+                (CellStyle::Beveled, _) => {
+                    painter.rect(rect, 0.0, color, stroke);
+                }
+                (CellStyle::Flat, _) => match mesh {
+                    // the cells are too small for rounding
+                    Some(mesh) => add_stroked_rect(mesh, rect, color, stroke),
+                    None => painter.rect(rect, theme.rounding * size, color, stroke),
+                },
+            }
+        }
+    }
+}
+
+/// Adds the rect with the stroke centered on its edges, like [`Painter::rect`] paints it.
+fn add_stroked_rect(mesh: &mut Mesh, rect: Rect, color: Color32, stroke: Stroke) {
+    mesh.add_colored_rect(rect, color);
+    if stroke.is_empty() {
+        return;
+    }
+    let outer = rect.expand(0.5 * stroke.width);
+    let inner = rect.shrink(0.5 * stroke.width);
+    let edges = [
+        Rect::from_min_max(outer.min, Pos2::new(outer.max.x, inner.min.y)),
+        Rect::from_min_max(Pos2::new(outer.min.x, inner.max.y), outer.max),
+        Rect::from_x_y_ranges(outer.min.x..=inner.min.x, inner.min.y..=inner.max.y),
+        Rect::from_x_y_ranges(inner.max.x..=outer.max.x, inner.min.y..=inner.max.y),
+    ];
+    for edge in edges {
+        mesh.add_colored_rect(edge, stroke.color);
+    }
+}
+
+/// Light top and left edges and dark bottom and right edges, which make the cell look raised.
+fn paint_bevel(painter: &Painter, rect: Rect, width: f32) {
+    let light = Color32::from_white_alpha(0xc0);
+    let dark = Color32::from_black_alpha(0x80);
+    let inset = |pos: Pos2, x: f32, y: f32| pos + width * Vec2::new(x, y);
+    let (lt, rt, lb, rb) = (
+        rect.left_top(),
+        rect.right_top(),
+        rect.left_bottom(),
+        rect.right_bottom(),
+    );
+
+    let edges = [
+        ([lt, rt, inset(rt, -1.0, 1.0), inset(lt, 1.0, 1.0)], light),
+        ([lt, inset(lt, 1.0, 1.0), inset(lb, 1.0, -1.0), lb], light),
+        ([lb, inset(lb, 1.0, -1.0), inset(rb, -1.0, -1.0), rb], dark),
+        ([rt, rb, inset(rb, -1.0, -1.0), inset(rt, -1.0, 1.0)], dark),
+    ];
+    for (points, color) in edges {
+        painter.add(Shape::convex_polygon(points.to_vec(), color, Stroke::NONE));
+    }
+}
+
+fn paint_mine(painter: &Painter, theme: &Theme, cell_rect: Rect) {
+    if let Some(sprites) = &theme.sprites {
+        let uv = sprites.uv(Sprite::Mine);
+        painter.image(sprites.texture, cell_rect, uv, Color32::WHITE);
+        return;
+    }
+    if theme.emoji && paint_emoji(painter, cell_rect, "💣", theme.mine) {
+        return;
+    }
+
+    let c = cell_rect.center();
+    let r = cell_rect.height() * 0.26;
+    let stroke = Stroke::new((cell_rect.height() * 0.07).max(1.0), theme.mine);
+    for i in 0..4 {
+        let d = 1.5 * r * Vec2::angled(i as f32 * std::f32::consts::FRAC_PI_4);
+        painter.line_segment([c - d, c + d], stroke);
+    }
+    painter.circle_filled(c, r, theme.mine);
+    let highlight = Color32::from_white_alpha(0xa0);
+    painter.circle_filled(c - Vec2::splat(r * 0.35), r * 0.25, highlight);
+}
+
+fn paint_cross(painter: &Painter, cell_rect: Rect, color: Color32) {
+    let rect = cell_rect.shrink(cell_rect.height() * 0.2);
+    let stroke = Stroke::new((cell_rect.height() * 0.08).max(1.0), color);
+    painter.line_segment([rect.left_top(), rect.right_bottom()], stroke);
+    painter.line_segment([rect.right_top(), rect.left_bottom()], stroke);
+}
+
+fn paint_flag(painter: &Painter, theme: &Theme, cell_rect: Rect) {
+    if let Some(sprites) = &theme.sprites {
+        let uv = sprites.uv(Sprite::Flag);
+        painter.image(sprites.texture, cell_rect, uv, Color32::WHITE);
+        return;
+    }
+    if theme.emoji && paint_emoji(painter, cell_rect, "🚩", theme.flag) {
+        return;
+    }
+
+    let color = theme.flag;
+    let pos = |x: f32, y: f32| cell_rect.left_top() + cell_rect.size() * Vec2::new(x, y);
+    let stroke = Stroke::new((cell_rect.height() * 0.06).max(1.0), color);
+    painter.line_segment([pos(0.38, 0.18), pos(0.38, 0.8)], stroke);
+    painter.line_segment([pos(0.24, 0.8), pos(0.62, 0.8)], stroke);
+    let cloth = vec![pos(0.38, 0.18), pos(0.76, 0.34), pos(0.38, 0.5)];
+    painter.add(Shape::convex_polygon(cloth, color, Stroke::NONE));
+}
+
+/// Paints a small shape in the corner of a cell that is distinct for every number, so numbers
+/// don't have to be told apart by color alone.
+fn paint_number_shape(painter: &Painter, cell_rect: Rect, n: u8, color: Color32) {
+    let r = cell_rect.height() * 0.09;
+    let c = cell_rect.left_top() + Vec2::splat(r * 1.8);
+    let polygon = |corners: usize, rotation: f32| {
+        (0..corners)
+            .map(|i| {
+                let angle = rotation + i as f32 * std::f32::consts::TAU / corners as f32;
+                c + r * Vec2::angled(angle)
+            })
+            .collect::<Vec<_>>()
+    };
+    let stroke = Stroke::new(r * 0.5, color);
+    let quarter = std::f32::consts::FRAC_PI_2;
+    let shape = match n {
+        1 => Shape::circle_filled(c, r, color),
+        2 => Shape::convex_polygon(polygon(3, -quarter), color, Stroke::NONE),
+        3 => Shape::rect_filled(Rect::from_center_size(c, Vec2::splat(r * 1.6)), 0.0, color),
+        4 => Shape::convex_polygon(polygon(4, 0.0), color, Stroke::NONE),
+        5 => Shape::convex_polygon(polygon(3, quarter), color, Stroke::NONE),
+        6 => Shape::convex_polygon(polygon(6, 0.0), color, Stroke::NONE),
+        7 => Shape::Vec(vec![
+            Shape::line_segment([c - Vec2::X * r, c + Vec2::X * r], stroke),
+            Shape::line_segment([c - Vec2::Y * r, c + Vec2::Y * r], stroke),
+        ]),
+        _ => Shape::circle_stroke(c, r * 0.8, stroke),
+    };
+    painter.add(shape);
 }
 
 fn board_idx_from_screen_pos(
@@ -506,26 +2546,179 @@ fn board_idx_from_screen_pos(
 }
 
 #[cfg(not(target_arch = "wasm32"))]
-fn vibrate(_ms: u32) {}
+fn set_fullscreen(ctx: &egui::Context, fullscreen: bool) {
+    ctx.send_viewport_cmd(egui::ViewportCommand::Fullscreen(fullscreen));
+}
 
 #[cfg(target_arch = "wasm32")]
-fn vibrate(ms: u32) {
-    let Some(window) = web_sys::window() else { return };
-    let navigator = window.navigator();
-    let Ok(user_agent) = navigator.user_agent() else { return };
-    let parser = woothee::parser::Parser::new();
-    let Some(res) = parser.parse(&user_agent) else { return };
-    if res.vendor != "Apple" {
-        navigator.vibrate_with_duration(ms);
-        log::info!("{res:?}");
+fn set_fullscreen(_ctx: &egui::Context, fullscreen: bool) {
+    let Some(document) = web_sys::window().and_then(|w| w.document()) else { return };
+    if fullscreen {
+        if let Some(element) = document.document_element() {
+            if let Err(e) = element.request_fullscreen() {
+                log::warn!("fullscreen request failed: {e:?}");
+            }
+        }
+    } else {
+        document.exit_fullscreen();
+    }
+}
+
+fn prompt_window(frame: &mut eframe::Frame, ui: &mut Ui, ms: &mut Minesweeper) {
+    let Some(prompt) = ms.prompt else { return };
+
+    let tr = ms.translations();
+    let text = match prompt {
+        Prompt::Restart => tr.abandon_game.to_string(),
+        Prompt::RestoreBundle => tr.restore_bundle.to_string(),
+        Prompt::ChangeDifficulty(d) => fill(&tr.abandon_for_difficulty, tr.difficulty(d)),
+        Prompt::Resume(d) => fill(&tr.resume_game, format_duration(d).trim()),
+    };
+    let (yes, no) = match prompt {
+        Prompt::Restart | Prompt::ChangeDifficulty(_) => (&tr.abandon, &tr.cancel),
+        Prompt::RestoreBundle => (&tr.replace, &tr.cancel),
+        Prompt::Resume(_) => (&tr.resume, &tr.new_game),
+    };
+
+    let mut answer = ui.input(|i| {
+        if i.key_pressed(Key::Escape) {
+            // dismissing the question whether to resume continues the game
+            Some(matches!(prompt, Prompt::Resume(_)))
+        } else if i.key_pressed(Key::Enter) {
+            Some(true)
+        } else {
+            None
+        }
+    });
+    egui::Window::new("prompt")
+        .title_bar(false)
+        .collapsible(false)
+        .resizable(false)
+        .anchor(Align2::CENTER_CENTER, Vec2::ZERO)
+        .show(ui.ctx(), |ui| {
+            ui.label(RichText::new(text).font(FontId::proportional(20.0)));
+            ui.horizontal(|ui| {
+                let text = RichText::new(&**yes).font(FontId::proportional(20.0));
+                if ui.button(text).clicked() {
+                    answer = Some(true);
+                }
+                let text = RichText::new(&**no).font(FontId::proportional(20.0));
+                if ui.button(text).clicked() {
+                    answer = Some(false);
+                }
+            });
+        });
+
+    match answer {
+        Some(true) => {
+            ms.prompt = None;
+            match prompt {
+                Prompt::Restart => ms.new_game(),
+                Prompt::RestoreBundle => {
+                    if let Some(bundle) = ms.pending_bundle.take() {
+                        ms.import_bundle(frame, bundle);
+                        ms.bundle_message = Some(tr.bundle_imported.to_string());
+                    }
+                }
+                Prompt::ChangeDifficulty(d) => {
+                    ms.difficulty = d;
+                    ms.new_game();
+                }
+                Prompt::Resume(d) => {
+                    ms.game.play_state = PlayState::Playing(SystemTime::now() - d);
+                    ms.last_input = Some(Instant::now());
+                }
+            }
+        }
+        Some(false) => {
+            ms.prompt = None;
+            ms.pending_bundle = None;
+            if let Prompt::Resume(_) = prompt {
+                ms.new_game();
+            }
+        }
+        None => (),
     }
 }
 
 pub fn update(frame: &mut eframe::Frame, ui: &mut Ui, ms: &mut Minesweeper) {
-    ui.ctx().request_repaint();
+    // the timer only changes once a second, anything else requests a repaint when it changes
+    if let PlayState::Playing(_) = ms.game.play_state {
+        let elapsed = ms.game.play_duration();
+        let next_second = Duration::from_secs(elapsed.as_secs() + 1) - elapsed;
+        ui.ctx().request_repaint_after(next_second);
+    }
+    let tr = ms.translations();
+
+    let active = ms.auto_play.is_some() || ui.input(|i| !i.events.is_empty());
+    if active {
+        if let PlayState::Playing(start) = ms.game.play_state {
+            // resume the timer without counting the time it was paused
+            ms.game.play_state = PlayState::Playing(start + ms.idle_duration());
+        }
+    }
+    if active || ms.last_input.is_none() {
+        ms.last_input = Some(Instant::now());
+    }
+
+    if let Some(generation) = &ms.generation {
+        match generation.receiver.try_recv() {
+            Ok(game) => {
+                let (x, y) = generation.click;
+                ms.generation = None;
+                ms.game = game;
+                ms.click(frame, x, y);
+            }
+            Err(mpsc::TryRecvError::Empty) => {
+                ui.ctx().request_repaint_after(Duration::from_millis(100));
+            }
+            Err(mpsc::TryRecvError::Disconnected) => ms.generation = None,
+        }
+    }
+
+    let scale = ms.settings.ui_scale;
+    let hover_pos = ui.input(|i| i.pointer.hover_pos());
+
+    egui::SidePanel::right("session")
+        .resizable(false)
+        .show_animated_inside(ui, ms.session_open, |ui| {
+            ms.session.ui(ui, &tr, ms.difficulty);
+        });
+
+    // the top bar can collapse to a thin strip, which expands while hovered
+    let in_game = matches!(ms.game.play_state, PlayState::Playing(_));
+    let collapsible = ms.settings.collapse_top_bar || (ms.settings.auto_hide_top_bar && in_game);
+    let input_free = ms.prompt.is_none() && !ms.settings.input_map.is_recording();
+    if collapsible && input_free && ui.input(|i| i.key_pressed(Key::Escape)) {
+        ms.top_bar_peek = !ms.top_bar_peek;
+    }
+    let hud_height = if ms.settings.bbbv_hud {
+        20.0 * scale
+    } else {
+        0.0
+    };
+    let full_height = 40.0 * scale + hud_height;
+    let collapsed_height = COLLAPSED_TOP_BAR_HEIGHT * scale;
+    let hover_height = if ms.top_bar_hovered {
+        full_height
+    } else {
+        collapsed_height
+    };
+    ms.top_bar_hovered = hover_pos.is_some_and(|p| p.y < hover_height);
+    let collapsed = collapsible && !ms.top_bar_hovered && !ms.top_bar_peek;
 
-    let menu_bar_height = 40.0;
-    let available_size = ui.available_size() - Vec2::new(0.0, menu_bar_height);
+    let menu_bar_height = if collapsed {
+        collapsed_height
+    } else {
+        full_height
+    };
+    let progress_bar_height = if ms.settings.progress_bar {
+        8.0 * scale
+    } else {
+        0.0
+    };
+    let available_size =
+        ui.available_size() - Vec2::new(0.0, menu_bar_height + progress_bar_height);
     let flipped = available_size.x < available_size.y;
     let cells;
     if flipped {
@@ -534,134 +2727,842 @@ pub fn update(frame: &mut eframe::Frame, ui: &mut Ui, ms: &mut Minesweeper) {
         cells = Vec2::new(ms.game.width as f32, ms.game.height as f32);
     }
     let ratio = available_size / cells;
-    let cell_size = Vec2::splat(ratio.min_elem());
+    let viewport = Rect::from_min_size(
+        Pos2::new(0.0, menu_bar_height + progress_bar_height),
+        available_size,
+    );
+
+    // zoom around the pointer with ctrl+scroll or pinch
+    // boards that don't fit with the minimum cell size are scrolled
+    let min_cell_size = if ms.settings.large_mode {
+        LARGE_CELL_SIZE
+    } else {
+        MIN_CELL_SIZE * scale
+    };
+    let base_cell_size = ratio.min_elem().max(min_cell_size);
+    let fit_size = cells * base_cell_size;
+    if let Some(pos) = hover_pos.filter(|p| viewport.contains(*p)) {
+        let zoom_delta = ui.input(|i| i.zoom_delta());
+        if zoom_delta != 1.0 {
+            ms.view.zoom_around(zoom_delta, pos, viewport, fit_size);
+        }
+
+        // pan by scrolling, dragging with the middle mouse button or with space held
+        let (scroll_delta, space_drag, drag) = ui.input(|i| {
+            let space_drag = i.key_down(Key::Space) && i.pointer.primary_down();
+            let drag = (i.pointer.middle_down() || space_drag).then(|| i.pointer.delta());
+            (i.scroll_delta, space_drag, drag)
+        });
+        if zoom_delta == 1.0 {
+            ms.view.pan_by(scroll_delta, viewport, fit_size);
+        }
+        if let Some(delta) = drag {
+            ms.view.pan_by(delta, viewport, fit_size);
+        }
+        if ui.input(|i| i.pointer.any_pressed()) {
+            ms.space_drag = false;
+        }
+        ms.space_drag |= space_drag;
+    }
+    ms.view.clamp(viewport, fit_size);
+
+    let cell_size = Vec2::splat(base_cell_size * ms.view.zoom);
     let board_size = cells * cell_size;
-    let board_offset = Pos2::new(0.0, menu_bar_height) + (available_size - board_size) * 0.5;
+    let mut board_offset = ms.view.board_offset(viewport, board_size);
+    if let (Some(lost_at), false) = (ms.lost_at, ms.settings.reduced_motion) {
+        let elapsed = lost_at.elapsed().as_secs_f32();
+        if elapsed < SHAKE_DURATION {
+            let amplitude = SHAKE_AMPLITUDE * scale * (1.0 - elapsed / SHAKE_DURATION);
+            let mut rng = rand::thread_rng();
+            let offset = Vec2::new(rng.gen_range(-1.0..=1.0), rng.gen_range(-1.0..=1.0));
+            board_offset += amplitude * offset;
+            ui.ctx().request_repaint();
+        }
+    }
 
     let board_rect = Rect::from_min_size(board_offset, board_size);
-    ui.allocate_ui(Vec2::new(ui.available_width(), menu_bar_height), |ui| {
-        ui.horizontal(|ui| {
-            ui.add_space(board_offset.x);
-            let open_mine_count = ms.game.open_mine_count().to_string();
-            let text = RichText::new(open_mine_count).font(FontId::monospace(30.0));
-            ui.label(text);
-
-            ui.add_space(20.0);
-            let visuals = ui.style().visuals.clone();
-            let new_visuals = if visuals.dark_mode {
-                let text = RichText::new("☀").font(FontId::proportional(20.0));
-                ui.add(Button::new(text).frame(false))
-                    .on_hover_text("Switch to light mode")
-                    .clicked()
-                    .then_some(Visuals::light())
-            } else {
-                let text = RichText::new("🌙").font(FontId::proportional(20.0));
-                ui.add(Button::new(text).frame(false))
-                    .on_hover_text("Switch to dark mode")
-                    .clicked()
-                    .then_some(Visuals::dark())
-            };
-            if let Some(visuals) = new_visuals {
-                ui.ctx().set_visuals(visuals);
-            }
-
-            ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
-                ui.add_space(board_offset.x);
-                let play_duration = format_duration(ms.game.play_duration());
-                let text = RichText::new(play_duration).font(FontId::monospace(30.0));
+    // the part of the board that is inside the viewport
+    let visible_rect = board_rect.intersect(viewport);
+    let minimap_rect = (!viewport.contains_rect(board_rect)).then(|| {
+        let width = MINIMAP_WIDTH.min(viewport.width() / 3.0);
+        let size = Vec2::new(width, width * cells.y / cells.x);
+        let size = size * (viewport.height() / 3.0 / size.y).min(1.0);
+        Rect::from_min_size(viewport.max - size - Vec2::splat(8.0), size)
+    });
+    let over_minimap = match (minimap_rect, hover_pos) {
+        (Some(rect), Some(pos)) => rect.contains(pos),
+        _ => false,
+    };
+    if collapsed {
+        let (rect, _) = ui.allocate_exact_size(
+            Vec2::new(ui.available_width(), menu_bar_height),
+            Sense::hover(),
+        );
+        let handle = Rect::from_center_size(rect.center(), Vec2::new(40.0, 2.0) * scale);
+        ui.painter()
+            .rect_filled(handle, 1.0, ui.visuals().weak_text_color());
+    } else {
+        ui.allocate_ui(Vec2::new(ui.available_width(), menu_bar_height), |ui| {
+            ui.horizontal(|ui| {
+                ui.add_space(visible_rect.min.x);
+                let flash_id = Id::new("counter_flash");
+                // a blink of a single frame would be worse than none with reduced motion
+                let flash = if ms.settings.reduced_motion {
+                    ms.counter_flash = false;
+                    0.0
+                } else {
+                    let ctx = ui.ctx();
+                    ctx.animate_bool_with_time(flash_id, ms.counter_flash, 0.15)
+                };
+                if flash >= 1.0 {
+                    ms.counter_flash = false;
+                }
+                let text_color = ui.visuals().text_color();
+                let color =
+                    Rgba::from(text_color) * (1.0 - flash) + Rgba::from(Color32::RED) * flash;
+                let mines_remaining = ms.game.mines_remaining();
+                let color = Color32::from(color);
+                let key = (mines_remaining, color, scale.to_bits());
+                let text = ms.labels.get(ui, "mines_remaining", key, || {
+                    RichText::new(mines_remaining.to_string())
+                        .font(FontId::monospace(30.0 * scale))
+                        .color(color)
+                });
                 ui.label(text);
 
-                ui.add_space(20.0);
-                let text = RichText::new("\u{21bb}").font(FontId::monospace(30.0));
+                ui.add_space(20.0 * scale);
+                let remaining = match ms.remaining_solver_hints() {
+                    Some(n) => n.to_string(),
+                    None => "∞".to_string(),
+                };
+                let text = RichText::new(format!("💡{remaining}"))
+                    .font(FontId::proportional(20.0 * scale));
                 let button = Button::new(text).frame(false);
-                if ui.add(button).clicked() {
-                    ms.new_game();
+                let enabled = matches!(ms.game.play_state, PlayState::Playing(_));
+                if focus_outline(ui.add_enabled(enabled, button))
+                    .on_hover_text(&*tr.solver_hint)
+                    .clicked()
+                {
+                    ms.solver_hint();
                 }
 
-                ui.add_space(20.0);
-                let text =
-                    RichText::new(ms.difficulty.to_string()).font(FontId::proportional(20.0));
-                let prev_difficulty = ms.difficulty;
-                ComboBox::new("difficulty", "")
-                    .selected_text(text)
-                    .show_ui(ui, |ui| {
-                        let text = RichText::new(Difficulty::Easy.to_string())
-                            .font(FontId::proportional(20.0));
-                        ui.selectable_value(&mut ms.difficulty, Difficulty::Easy, text);
-
-                        let text = RichText::new(Difficulty::Medium.to_string())
-                            .font(FontId::proportional(20.0));
-                        ui.selectable_value(&mut ms.difficulty, Difficulty::Medium, text);
-
-                        let text = RichText::new(Difficulty::Hard.to_string())
-                            .font(FontId::proportional(20.0));
-                        ui.selectable_value(&mut ms.difficulty, Difficulty::Hard, text);
+                if let Some(hot_seat) = &ms.game.hot_seat {
+                    ui.add_space(20.0 * scale);
+                    let player = hot_seat.turn + 1;
+                    let finished = match ms.game.play_state {
+                        PlayState::Init | PlayState::Playing(_) => None,
+                        PlayState::Won(_) => Some(true),
+                        PlayState::Lost(_) => Some(false),
+                    };
+                    let color = hot_seat.turn_color();
+                    let key = (
+                        player,
+                        finished,
+                        color,
+                        ms.settings.language,
+                        scale.to_bits(),
+                    );
+                    let text = ms.labels.get(ui, "hot_seat", key, || {
+                        let text = match finished {
+                            None => fill(&tr.player_turn, player),
+                            Some(true) => tr.both_survived.to_string(),
+                            Some(false) => fill(&tr.player_lost, player),
+                        };
+                        RichText::new(text)
+                            .font(FontId::proportional(20.0 * scale))
+                            .color(color)
                     });
-                if ms.difficulty != prev_difficulty && ms.game.play_state == PlayState::Init {
-                    ms.new_game();
+                    ui.label(text);
                 }
 
-                ui.add_space(20.0);
-                let text = RichText::new("unambigous").font(FontId::proportional(20.0));
-                ui.checkbox(&mut ms.unambigous, text);
-            });
-        });
-    });
+                ui.add_space(20.0 * scale);
+                let visuals = ui.style().visuals.clone();
+                let new_visuals = if visuals.dark_mode {
+                    let font_id = FontId::proportional(20.0 * scale);
+                    focus_outline(icon_button(ui, &mut ms.galleys, "☀", font_id, false))
+                        .on_hover_text(&*tr.light_mode)
+                        .clicked()
+                        .then_some(Visuals::light())
+                } else {
+                    let font_id = FontId::proportional(20.0 * scale);
+                    focus_outline(icon_button(ui, &mut ms.galleys, "🌙", font_id, false))
+                        .on_hover_text(&*tr.dark_mode)
+                        .clicked()
+                        .then_some(Visuals::dark())
+                };
+                if let Some(visuals) = new_visuals {
+                    ui.ctx().set_visuals(visuals);
+                }
+
+                ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                    ui.add_space(visible_rect.min.x);
+                    let idle_duration = ms.idle_duration();
+                    // running games only show whole seconds
+                    let (finished, play_duration) = match ms.game.play_state {
+                        PlayState::Init | PlayState::Playing(_) => {
+                            let d = ms.game.play_duration().saturating_sub(idle_duration);
+                            (false, Duration::from_secs(d.as_secs()))
+                        }
+                        PlayState::Won(d) | PlayState::Lost(d) => (true, d),
+                    };
+                    let color = ui.visuals().text_color();
+                    let key = (finished, play_duration, color, scale.to_bits());
+                    let text = ms.labels.get(ui, "play_duration", key, || {
+                        let text = match finished {
+                            true => format_duration_precise(play_duration),
+                            false => format_duration(play_duration),
+                        };
+                        RichText::new(text)
+                            .font(FontId::monospace(30.0 * scale))
+                            .color(color)
+                    });
+                    ui.label(text);
+                    if in_game && idle_duration > Duration::ZERO {
+                        let color = ui.visuals().weak_text_color();
+                        let key = (color, scale.to_bits());
+                        let text = ms.labels.get(ui, "paused", key, || {
+                            RichText::new("⏸")
+                                .font(FontId::proportional(20.0 * scale))
+                                .color(color)
+                        });
+                        ui.label(text).on_hover_text(&*tr.paused_while_idle);
+                    }
+
+                    let (difficulty, unambigous) = (ms.game.difficulty, ms.game.unambigous);
+                    if let Some(best) = ms.highscores.best(difficulty, unambigous) {
+                        ui.add_space(10.0 * scale);
+                        let color = ui.visuals().weak_text_color();
+                        let key = (best, color, scale.to_bits());
+                        let text = ms.labels.get(ui, "best_time", key, || {
+                            RichText::new(format!("🏆{}", format_duration_precise(best)))
+                                .font(FontId::monospace(16.0 * scale))
+                                .color(color)
+                        });
+                        ui.label(text).on_hover_text(&*tr.best_time);
+                    }
+
+                    ui.add_space(20.0 * scale);
+                    let font_id = FontId::monospace(30.0 * scale);
+                    let button = icon_button(ui, &mut ms.galleys, "\u{21bb}", font_id, false);
+                    if focus_outline(button).on_hover_text(&*tr.new_game).clicked() {
+                        ms.restart();
+                    }
+
+                    ui.add_space(20.0 * scale);
+                    let (icon, hover_text) = if ms.settings.collapse_top_bar {
+                        ("▾", &tr.keep_top_bar_open)
+                    } else {
+                        ("▴", &tr.collapse_top_bar)
+                    };
+                    let font_id = FontId::proportional(20.0 * scale);
+                    let button = icon_button(ui, &mut ms.galleys, icon, font_id, false);
+                    if focus_outline(button).on_hover_text(&**hover_text).clicked() {
+                        ms.settings.collapse_top_bar = !ms.settings.collapse_top_bar;
+                        ms.top_bar_peek = false;
+                    }
+
+                    ui.add_space(20.0 * scale);
+                    let font_id = FontId::proportional(20.0 * scale);
+                    let button = icon_button(ui, &mut ms.galleys, "⛶", font_id, false);
+                    if focus_outline(button)
+                        .on_hover_text(&*tr.toggle_fullscreen)
+                        .clicked()
+                    {
+                        ms.toggle_fullscreen(ui.ctx());
+                    }
+
+                    #[cfg(feature = "multiplayer")]
+                    {
+                        ui.add_space(20.0 * scale);
+                        let font_id = FontId::proportional(20.0 * scale);
+                        let button =
+                            icon_button(ui, &mut ms.galleys, "🖧", font_id, ms.multiplayer_open);
+                        if focus_outline(button)
+                            .on_hover_text(&*tr.multiplayer)
+                            .clicked()
+                        {
+                            ms.multiplayer_open = !ms.multiplayer_open;
+                        }
+                    }
+
+                    #[cfg(all(feature = "audio", not(target_arch = "wasm32")))]
+                    {
+                        ui.add_space(20.0 * scale);
+                        let (icon, hover_text) = if ms.settings.muted {
+                            ("🔇", &tr.unmute)
+                        } else {
+                            ("🔊", &tr.mute)
+                        };
+                        let font_id = FontId::proportional(20.0 * scale);
+                        let button = icon_button(ui, &mut ms.galleys, icon, font_id, false);
+                        if focus_outline(button).on_hover_text(&**hover_text).clicked() {
+                            ms.settings.muted = !ms.settings.muted;
+                        }
+                    }
+
+                    ui.add_space(20.0 * scale);
+                    let font_id = FontId::proportional(20.0 * scale);
+                    let button = icon_button(ui, &mut ms.galleys, "📷", font_id, false);
+                    if focus_outline(button)
+                        .on_hover_text(&*tr.save_board_image)
+                        .clicked()
+                    {
+                        ms.save_image(ui.visuals());
+                    }
+
+                    ui.add_space(20.0 * scale);
+                    let font_id = FontId::proportional(20.0 * scale);
+                    let button = icon_button(ui, &mut ms.galleys, "📋", font_id, false);
+                    if focus_outline(button)
+                        .on_hover_text(&*tr.copy_board_image)
+                        .clicked()
+                    {
+                        ms.copy_image(ui.visuals());
+                    }
+
+                    ui.add_space(20.0 * scale);
+                    let font_id = FontId::proportional(20.0 * scale);
+                    let button = icon_button(ui, &mut ms.galleys, "📊", font_id, ms.stats_open);
+                    if focus_outline(button)
+                        .on_hover_text(&*tr.statistics)
+                        .clicked()
+                    {
+                        ms.stats_open = !ms.stats_open;
+                    }
+
+                    ui.add_space(20.0 * scale);
+                    let font_id = FontId::proportional(20.0 * scale);
+                    let button = icon_button(ui, &mut ms.galleys, "⏱", font_id, ms.session_open);
+                    if focus_outline(button).on_hover_text(&*tr.session).clicked() {
+                        ms.session_open = !ms.session_open;
+                    }
+
+                    ui.add_space(20.0 * scale);
+                    let font_id = FontId::proportional(20.0 * scale);
+                    let button = icon_button(ui, &mut ms.galleys, "📈", font_id, ms.progress_open);
+                    if focus_outline(button).on_hover_text(&*tr.progress).clicked() {
+                        ms.progress_open = !ms.progress_open;
+                    }
+
+                    ui.add_space(20.0 * scale);
+                    let font_id = FontId::proportional(20.0 * scale);
+                    let button = icon_button(ui, &mut ms.galleys, "📜", font_id, ms.history_open);
+                    if focus_outline(button).on_hover_text(&*tr.history).clicked() {
+                        ms.history_open = !ms.history_open;
+                    }
+
+                    ui.add_space(20.0 * scale);
+                    let font_id = FontId::proportional(20.0 * scale);
+                    let button =
+                        icon_button(ui, &mut ms.galleys, "🏅", font_id, ms.leaderboard_open);
+                    if focus_outline(button)
+                        .on_hover_text(&*tr.leaderboard)
+                        .clicked()
+                    {
+                        ms.leaderboard_open = !ms.leaderboard_open;
+                    }
+
+                    ui.add_space(20.0 * scale);
+                    let font_id = FontId::proportional(20.0 * scale);
+                    let button = icon_button(ui, &mut ms.galleys, "⚙", font_id, false);
+                    if focus_outline(button).on_hover_text(&*tr.settings).clicked() {
+                        ms.settings_open = !ms.settings_open;
+                    }
+
+                    ui.add_space(20.0 * scale);
+                    let font_id = FontId::proportional(20.0 * scale);
+                    let watching = ms.auto_play.is_some();
+                    let button = icon_button(ui, &mut ms.galleys, "🤖", font_id, watching);
+                    if focus_outline(button).on_hover_text(&*tr.watch_ai).clicked() {
+                        ms.auto_play = match ms.auto_play {
+                            Some(_) => None,
+                            None => Some(AutoPlay::default()),
+                        };
+                    }
+
+                    ui.add_space(20.0 * scale);
+                    let profile = ms.profiles.ui(ui, &tr, scale, &mut ms.profile_name_input);
+                    if let Some(profile) = profile {
+                        ms.switch_profile(frame, profile);
+                    }
+
+                    ui.add_space(20.0 * scale);
+                    let text = RichText::new(tr.difficulty(ms.difficulty))
+                        .font(FontId::proportional(20.0 * scale));
+                    let prev_difficulty = ms.difficulty;
+                    ComboBox::new("difficulty", "")
+                        .selected_text(text)
+                        .show_ui(ui, |ui| {
+                            let text =
+                                RichText::new(&*tr.easy).font(FontId::proportional(20.0 * scale));
+                            ui.selectable_value(&mut ms.difficulty, Difficulty::Easy, text);
+
+                            let text =
+                                RichText::new(&*tr.medium).font(FontId::proportional(20.0 * scale));
+                            ui.selectable_value(&mut ms.difficulty, Difficulty::Medium, text);
+
+                            let text =
+                                RichText::new(&*tr.hard).font(FontId::proportional(20.0 * scale));
+                            ui.selectable_value(&mut ms.difficulty, Difficulty::Hard, text);
+                        });
+                    if ms.difficulty != prev_difficulty {
+                        let difficulty = ms.difficulty;
+                        ms.difficulty = prev_difficulty;
+                        ms.select_difficulty(difficulty);
+                    }
+
+                    ui.add_space(20.0 * scale);
+                    let text =
+                        RichText::new(&*tr.unambigous).font(FontId::proportional(20.0 * scale));
+                    ui.checkbox(&mut ms.unambigous, text);
+
+                    ui.add_space(20.0 * scale);
+                    let text =
+                        RichText::new(&*tr.two_players).font(FontId::proportional(20.0 * scale));
+                    let resp = ui.checkbox(&mut ms.hot_seat, text);
+                    if resp.changed() && ms.game.play_state == PlayState::Init {
+                        ms.new_game();
+                    }
+                });
+            });
+
+            if hud_height > 0.0 {
+                ui.horizontal(|ui| {
+                    ui.add_space(visible_rect.min.x);
+                    let clicks = ms.game.clicks.total();
+                    let color = ui.visuals().text_color();
+                    let key = (
+                        ms.game.generated,
+                        clicks,
+                        ms.game.num_shown(),
+                        ms.settings.language,
+                        color,
+                        scale.to_bits(),
+                    );
+                    let game = &ms.game;
+                    let text = ms.labels.get(ui, "bbbv_hud", key, || {
+                        let text = if game.generated {
+                            let (solved, total) = game.bbbv_progress();
+                            let efficiency = match clicks {
+                                0 => "-".to_string(),
+                                _ => format!("{:.0}%", 100.0 * solved as f32 / clicks as f32),
+                            };
+                            format!(
+                                "3BV {solved}/{total}   {} {clicks}   {} {efficiency}",
+                                tr.clicks, tr.efficiency
+                            )
+                        } else {
+                            format!("3BV -   {} {clicks}   {} -", tr.clicks, tr.efficiency)
+                        };
+                        RichText::new(text)
+                            .font(FontId::monospace(14.0 * scale))
+                            .color(color)
+                    });
+                    ui.label(text);
+                });
+            }
+        });
+    }
+
+    let mut export_bundle = false;
+    egui::Window::new(&*tr.settings)
+        .id(Id::new("settings"))
+        .open(&mut ms.settings_open)
+        .collapsible(false)
+        .resizable(false)
+        .show(ui.ctx(), |ui| {
+            ms.settings.ui(ui, &tr);
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui.button(&*tr.export_bundle).clicked() {
+                    export_bundle = true;
+                }
+                ui.label(&*tr.import_bundle_hint);
+            });
+            if let Some(message) = &ms.bundle_message {
+                ui.label(message);
+            }
+        });
+    if export_bundle {
+        ms.export_bundle(frame);
+    }
+
+    let mut export = None;
+    egui::Window::new(&*tr.statistics)
+        .id(Id::new("stats"))
+        .open(&mut ms.stats_open)
+        .collapsible(false)
+        .resizable(false)
+        .show(ui.ctx(), |ui| {
+            ms.stats.ui(ui, &tr);
+            ui.add_space(8.0);
+            ui.horizontal(|ui| {
+                if ui.button(&*tr.export_csv).clicked() {
+                    export = Some(export::Format::Csv);
+                }
+                if ui.button(&*tr.export_json).clicked() {
+                    export = Some(export::Format::Json);
+                }
+            });
+        });
+    if let Some(format) = export {
+        ms.export_stats(format);
+    }
+
+    egui::Window::new(&*tr.history)
+        .open(&mut ms.history_open)
+        .collapsible(false)
+        .resizable(false)
+        .show(ui.ctx(), |ui| {
+            ms.history.ui(ui, &tr, &mut ms.history_view);
+        });
+
+    egui::Window::new(&*tr.progress)
+        .open(&mut ms.progress_open)
+        .collapsible(false)
+        .resizable(false)
+        .show(ui.ctx(), |ui| {
+            progress::ui(ui, &tr, &ms.history, &mut ms.progress_difficulty);
+        });
+
+    let mut selected_record = None;
+    let mut selected_board = None;
+    egui::Window::new(&*tr.leaderboard)
+        .open(&mut ms.leaderboard_open)
+        .collapsible(false)
+        .resizable(false)
+        .show(ui.ctx(), |ui| {
+            ui.horizontal(|ui| {
+                ui.label(RichText::new(&*tr.board_id).font(FontId::proportional(16.0)));
+                ui.text_edit_singleline(&mut ms.board_id_input);
+                let board = BoardSeed::parse(&ms.board_id_input)
+                    .and_then(|b| Some((Difficulty::from_size(b.width, b.height)?, b)));
+                let button = Button::new(&*tr.play_board);
+                if ui.add_enabled(board.is_some(), button).clicked() {
+                    selected_board = board;
+                }
+            });
+            if let Some(day) = today() {
+                ui.horizontal(|ui| {
+                    let text = RichText::new(&*tr.daily_challenge).font(FontId::proportional(16.0));
+                    ui.label(text);
+                    if ui.button(&*tr.play_board).clicked() {
+                        selected_board = Some((Difficulty::Medium, BoardSeed::daily(day)));
+                    }
+                });
+                #[cfg(feature = "online")]
+                if ms.settings.online.enabled {
+                    ms.online.ui(ui, &tr, &ms.settings.online, day);
+                }
+            }
+            ui.label(&*tr.import_hint);
+            if let Some(message) = &ms.import_message {
+                ui.label(message);
+            }
+            ui.separator();
+            selected_record = ms.highscores.ui(ui, &tr, &mut ms.leaderboard_mode);
+        });
+    if let Some((difficulty, board)) = selected_board {
+        ms.play_board(difficulty, ms.unambigous, &board);
+        ms.leaderboard_open = false;
+    }
+    if let Some((i, watch)) = selected_record {
+        let (difficulty, unambigous) = ms.leaderboard_mode;
+        let record = ms.highscores.records(difficulty, unambigous)[i].clone();
+        ms.play_record(difficulty, unambigous, &record, watch);
+        ms.leaderboard_open = false;
+    }
+
+    #[cfg(feature = "multiplayer")]
+    {
+        let mut start = false;
+        egui::Window::new(&*tr.multiplayer)
+            .open(&mut ms.multiplayer_open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ui.ctx(), |ui| {
+                start = ms.multiplayer.ui(ui, &tr);
+            });
+        if start {
+            let board = Game::race_board(ms.difficulty);
+            ms.multiplayer.start(board);
+            ms.start_race(&board);
+        }
+
+        // own moves are sent before the moves of teammates are played, so the host sends them in
+        // the order they were made on its board
+        if ms.multiplayer.board().is_some() && ms.multiplayer.board() == ms.game.board_seed() {
+            ms.multiplayer.report(ms.game.race_progress());
+            let cursor = ms.hovered.unwrap_or((ms.cursor_x, ms.cursor_y));
+            ms.multiplayer
+                .report_moves(ui.ctx(), &ms.game.steps, cursor);
+        }
+
+        // spectators follow the moves of the watched player, and co-op the moves of everyone
+        for event in ms.multiplayer.poll(ui.ctx(), &tr) {
+            match event {
+                multiplayer::Event::Start(board) => ms.start_race(&board),
+                multiplayer::Event::Step(step) => {
+                    if replay::apply(&mut ms.game, step).is_some() {
+                        ms.won_at = Some(Instant::now());
+                    }
+                    if let PlayState::Lost(_) = ms.game.play_state {
+                        ms.lost_at.get_or_insert_with(Instant::now);
+                    }
+                    ms.multiplayer.skip_moves(ms.game.steps.len());
+                }
+                multiplayer::Event::Cursor(x, y) => {
+                    (ms.cursor_x, ms.cursor_y) = (x, y);
+                    ms.cursor_visible = true;
+                }
+            }
+        }
+    }
+
+    // stats and replays of other clients dropped on the window
+    let dropped = ui.input(|i| i.raw.dropped_files.clone());
+    for file in dropped {
+        let data = match (file.bytes, file.path) {
+            (Some(bytes), _) => Some(bytes.to_vec()),
+            (None, Some(path)) => std::fs::read(path).ok(),
+            (None, None) => None,
+        };
+        let Some(data) = data else { continue };
+        let text = String::from_utf8_lossy(&data);
+        if let Some(bundle) = Bundle::parse(&text) {
+            // nothing is written before the whole bundle was read and the player confirmed it
+            if !bundle.is_supported() {
+                ms.bundle_message = Some(tr.bundle_too_new.to_string());
+            } else if let Err(e) = bundle.check() {
+                log::warn!("failed to check the bundle: {e}");
+                ms.bundle_message = Some(fill(&tr.bundle_invalid, e));
+            } else if ms.prompt.is_none() {
+                ms.pending_bundle = Some(bundle);
+                ms.prompt = Some(Prompt::RestoreBundle);
+                ms.bundle_message = None;
+            }
+            ms.settings_open = true;
+            continue;
+        }
+        let count = ms.import_records(&text);
+        ms.import_message = Some(fill(&tr.imported_records, count));
+        ms.leaderboard_open = true;
+        ms.persist(frame);
+    }
+
+    // save the running game every few seconds, so a crash only loses the last moments
+    let resuming = matches!(ms.prompt, Some(Prompt::Resume(_)));
+    let interval = ms.settings.autosave_interval;
+    if let (PlayState::Playing(_), false, Some(interval)) = (ms.game.play_state, resuming, interval)
+    {
+        let interval = Duration::from_secs(interval as u64);
+        let since_saved = ms.saved_at.map_or(interval, |t| t.elapsed());
+        if since_saved >= interval {
+            ms.persist(frame);
+            ms.flush(frame);
+            ui.ctx().request_repaint_after(interval);
+        } else {
+            ui.ctx().request_repaint_after(interval - since_saved);
+        }
+    }
+
+    // moves are written out a little later, so they don't wait for the disk
+    let flushed_at = ms.flushed_at;
+    let unflushed = ms
+        .saved_at
+        .is_some_and(|saved| flushed_at.is_none_or(|flushed| saved > flushed));
+    if unflushed {
+        let since_flushed = flushed_at.map_or(FLUSH_INTERVAL, |t| t.elapsed());
+        match FLUSH_INTERVAL.checked_sub(since_flushed) {
+            Some(remaining) if !remaining.is_zero() => ui.ctx().request_repaint_after(remaining),
+            _ => ms.flush(frame),
+        }
+    }
+
+    // keys go to the focused widget, like a button of the top bar reached with tab, unless it's
+    // the field under the cursor
+    let cursor_id = a11y::cell_id(ms.cursor_x, ms.cursor_y);
+    let widget_focused = ui.memory(|m| m.focus()).is_some_and(|id| id != cursor_id);
+    let input_enabled =
+        ms.prompt.is_none() && !ms.settings.input_map.is_recording() && !widget_focused;
+    #[cfg(feature = "multiplayer")]
+    let input_enabled = input_enabled && !ms.multiplayer.is_spectating();
+    prompt_window(frame, ui, ms);
+
+    // auto play
+    if let Some(mut auto_play) = ms.auto_play {
+        let time = ui.input(|i| i.time);
+        let interval = ms.settings.auto_play_interval as f64 / 1000.0;
+        if input_enabled && time - auto_play.last_move_time >= interval {
+            if let Some(mv) = ai::next_move(&ms.game) {
+                ms.auto_move(frame, mv);
+                auto_play.last_move = Some(mv);
+                auto_play.last_move_time = time;
+            }
+        }
+        if let PlayState::Init | PlayState::Playing(_) = ms.game.play_state {
+            let next_move = auto_play.last_move_time + interval - time;
+            ui.ctx()
+                .request_repaint_after(Duration::from_secs_f64(next_move.max(0.0)));
+        }
+        ms.auto_play = Some(auto_play);
+    }
+
+    // playback of a recorded game
+    if let Some(playback) = &mut ms.playback {
+        while let Some(step) = playback.next_step() {
+            if replay::apply(&mut ms.game, step).is_some() {
+                ms.won_at = Some(Instant::now());
+            }
+        }
+        match playback.next_delay() {
+            Some(delay) => ui.ctx().request_repaint_after(delay),
+            None => ms.playback = None,
+        }
+    }
+
+    // the best run on the same board, racing the player
+    if let (true, PlayState::Playing(_)) = (ms.settings.ghost, ms.game.play_state) {
+        if ms.ghost.is_none() {
+            let best = ms.game.board_seed().and_then(|b| ms.seed_bests.get(&b));
+            if let Some(best) = best.filter(|b| !b.steps.is_empty()) {
+                ms.ghost = Some(Ghost::new(&ms.game, best.steps.clone()));
+            }
+        }
+        if let Some(ghost) = &mut ms.ghost {
+            let elapsed = ms.game.play_duration();
+            ghost.advance(elapsed);
+            if let Some(delay) = ghost.next_delay(elapsed) {
+                ui.ctx().request_repaint_after(delay);
+            }
+        }
+    }
 
     // input
-    ui.input(|i| {
-        // arrow keys
-        if i.key_pressed(Key::ArrowUp) {
+    let repeat_delay = Duration::from_millis(ms.settings.key_repeat_delay as u64);
+    let repeat_interval = Duration::from_millis(ms.settings.key_repeat_interval as u64);
+    let actions = ui.input(|i| {
+        ms.settings
+            .input_map
+            .actions(i, repeat_delay, repeat_interval)
+    });
+    // held movement keys are repeated without waiting for events
+    if let Some(delay) = ms.settings.input_map.repeat_delay(ui.input(|i| i.time)) {
+        ui.ctx().request_repaint_after(delay);
+    }
+    if input_enabled {
+        if actions.contains(Action::MoveUp) {
             ms.cursor_up(flipped);
-        } else if i.key_pressed(Key::ArrowRight) {
+        }
+        if actions.contains(Action::MoveRight) {
             ms.cursor_right(flipped);
-        } else if i.key_pressed(Key::ArrowDown) {
+        }
+        if actions.contains(Action::MoveDown) {
             ms.cursor_down(flipped);
-        } else if i.key_pressed(Key::ArrowLeft) {
+        }
+        if actions.contains(Action::MoveLeft) {
             ms.cursor_left(flipped);
         }
-
-        // wasd keys
-        if i.key_pressed(Key::W) {
+        if actions.contains(Action::MoveUpLeft) {
             ms.cursor_up(flipped);
-        } else if i.key_pressed(Key::D) {
-            ms.cursor_right(flipped);
-        } else if i.key_pressed(Key::S) {
-            ms.cursor_down(flipped);
-        } else if i.key_pressed(Key::A) {
             ms.cursor_left(flipped);
         }
-
-        // vim keys
-        if i.key_pressed(Key::K) {
+        if actions.contains(Action::MoveUpRight) {
             ms.cursor_up(flipped);
-        } else if i.key_pressed(Key::L) {
             ms.cursor_right(flipped);
-        } else if i.key_pressed(Key::J) {
+        }
+        if actions.contains(Action::MoveDownLeft) {
             ms.cursor_down(flipped);
-        } else if i.key_pressed(Key::H) {
             ms.cursor_left(flipped);
         }
+        if actions.contains(Action::MoveDownRight) {
+            ms.cursor_down(flipped);
+            ms.cursor_right(flipped);
+        }
+
+        if actions.contains(Action::JumpLeft) {
+            ms.cursor_left_edge(flipped);
+        }
+        if actions.contains(Action::JumpRight) {
+            ms.cursor_right_edge(flipped);
+        }
+        if actions.contains(Action::JumpTop) {
+            ms.cursor_top_edge(flipped);
+        }
+        if actions.contains(Action::JumpBottom) {
+            ms.cursor_bottom_edge(flipped);
+        }
+        if actions.contains(Action::JumpCenter) {
+            ms.cursor_center();
+        }
 
-        if i.key_pressed(Key::R) {
-            ms.new_game();
+        let moves = [
+            Action::MoveUp,
+            Action::MoveRight,
+            Action::MoveDown,
+            Action::MoveLeft,
+            Action::MoveUpLeft,
+            Action::MoveUpRight,
+            Action::MoveDownLeft,
+            Action::MoveDownRight,
+            Action::JumpLeft,
+            Action::JumpRight,
+            Action::JumpTop,
+            Action::JumpBottom,
+            Action::JumpCenter,
+        ];
+        if moves.iter().any(|&a| actions.contains(a)) {
+            // keep the cursor in view when zoomed in
+            let rect = cell_rect(
+                ms.game.height,
+                board_offset,
+                cell_size,
+                ms.cursor_x,
+                ms.cursor_y,
+                flipped,
+            );
+            ms.view.pan_to(rect, viewport, fit_size);
+            // screen readers read the field the cursor moved to
+            let id = a11y::cell_id(ms.cursor_x, ms.cursor_y);
+            ui.memory_mut(|m| m.request_focus(id));
+        }
+
+        if actions.contains(Action::Restart) {
+            ms.restart();
+        }
+        if actions.contains(Action::Fullscreen) {
+            ms.toggle_fullscreen(ui.ctx());
+        }
+        let difficulties = [
+            (Action::Easy, Difficulty::Easy),
+            (Action::Medium, Difficulty::Medium),
+            (Action::Hard, Difficulty::Hard),
+        ];
+        for (action, difficulty) in difficulties {
+            if actions.contains(action) {
+                ms.select_difficulty(difficulty);
+            }
         }
 
         if let PlayState::Init | PlayState::Playing(_) = ms.game.play_state {
-            if i.key_pressed(Key::Enter) || i.key_pressed(Key::Space) {
-                if i.modifiers.ctrl {
-                    ms.hint(frame, ms.cursor_x, ms.cursor_y);
-                } else {
-                    ms.click(frame, ms.cursor_x, ms.cursor_y);
-                }
+            if actions.contains(Action::Reveal) {
+                ms.click(frame, ms.cursor_x, ms.cursor_y);
+            }
+            if actions.contains(Action::Hint) {
+                ms.user_hint(frame, ms.cursor_x, ms.cursor_y);
+            }
+            if actions.contains(Action::Chord) {
+                ms.chord(frame, ms.cursor_x, ms.cursor_y);
             }
         }
-    });
+    }
 
-    let resp = ui.allocate_rect(board_rect, Sense::click_and_drag());
-    if let PlayState::Init | PlayState::Playing(_) = ms.game.play_state {
+    let resp = ui.allocate_rect(visible_rect, Sense::click_and_drag());
+    let playing = matches!(ms.game.play_state, PlayState::Init | PlayState::Playing(_));
+    if input_enabled && playing && !over_minimap {
         ui.input_mut(|i| {
-            if i.pointer.velocity() != Vec2::ZERO {
+            let mouse_moves_cursor = ms.settings.mouse_moves_cursor;
+            if mouse_moves_cursor && i.pointer.velocity() != Vec2::ZERO {
                 ms.cursor_visible = false;
             }
 
@@ -673,7 +3574,7 @@ pub fn update(frame: &mut eframe::Frame, ui: &mut Ui, ms: &mut Minesweeper) {
                 if let Some(pos) = i.pointer.press_origin() {
                     if let Some(start_time) = i.pointer.press_start_time() {
                         let duration = i.time - start_time;
-                        if !ms.long_press && duration > 0.4 {
+                        if !ms.long_press && ms.two_finger_tap.is_none() && duration > 0.4 {
                             let (x, y) = board_idx_from_screen_pos(
                                 ms.game.height,
                                 board_offset,
@@ -681,96 +3582,251 @@ pub fn update(frame: &mut eframe::Frame, ui: &mut Ui, ms: &mut Minesweeper) {
                                 pos,
                                 flipped,
                             );
-                            vibrate(100);
-                            ms.hint(frame, x, y);
+                            ms.user_hint(frame, x, y);
                             ms.long_press = true;
                         }
                     }
                 }
             }
 
+            let chord = ms.settings.chord;
+            if chord.both_buttons && i.pointer.primary_down() && i.pointer.secondary_down() {
+                ms.both_buttons.get_or_insert(false);
+            }
+
+            // the touches of the single fingers of a two finger tap are ignored
+            if let Some(touch) = i.multi_touch().filter(|t| t.num_touches == 2) {
+                let tap = ms.two_finger_tap.get_or_insert(TwoFingerTap {
+                    start_time: touch.start_time,
+                    pos: touch.start_pos,
+                    moved: 0.0,
+                    zoom: 1.0,
+                    ended: false,
+                });
+                tap.moved += touch.translation_delta.length();
+                tap.zoom *= touch.zoom_delta;
+            }
+
             if let Some(pos) = resp.interact_pointer_pos() {
-                let mut clicked = false;
-                let mut hint = false;
-                if i.pointer.primary_released() {
-                    clicked = true;
-                } else if i.pointer.secondary_released() {
-                    clicked = true;
-                    hint = true;
+                let (x, y) = board_idx_from_screen_pos(
+                    ms.game.height,
+                    board_offset,
+                    cell_size,
+                    pos,
+                    flipped,
+                );
+                let shown =
+                    ms.game.is_in_bounds(x, y) && ms.game[(x, y)].visibility() == Visibility::Show;
+                let primary = i.pointer.primary_released();
+                let secondary = i.pointer.secondary_released();
+
+                // with both buttons held the first release chords and the other one is ignored
+                let action = match ms.both_buttons {
+                    Some(false) if primary || secondary => {
+                        ms.both_buttons = Some(true);
+                        Some(Action::Chord)
+                    }
+                    Some(_) => None,
+                    None if primary && shown => chord.left_click.then_some(Action::Chord),
+                    None if primary => Some(Action::Reveal),
+                    None if secondary => Some(Action::Hint),
+                    None if resp.middle_clicked() => chord.middle_click.then_some(Action::Chord),
+                    None => None,
+                };
+                let double_click = chord.double_click && shown && resp.double_clicked();
+                let action = action.or(double_click.then_some(Action::Chord));
+
+                let dragged = ms.long_press || ms.space_drag;
+                if let (Some(action), false, None) = (action, dragged, ms.two_finger_tap) {
+                    match action {
+                        Action::Hint => ms.user_hint(frame, x, y),
+                        Action::Chord => ms.chord(frame, x, y),
+                        _ => ms.click(frame, x, y),
+                    }
+
+                    if mouse_moves_cursor && ms.game.is_in_bounds(x, y) {
+                        ms.cursor_x = x;
+                        ms.cursor_y = y;
+                    }
                 }
+            }
+
+            if !i.pointer.any_down() {
+                ms.both_buttons = None;
+            }
 
-                if clicked && !ms.long_press {
+            // the tap ends when the first finger is lifted
+            let tap = ms.two_finger_tap.as_mut().filter(|t| !t.ended);
+            if let (Some(tap), None) = (tap, i.multi_touch()) {
+                tap.ended = true;
+                if tap.is_tap(i.time) {
+                    let tap = *tap;
                     let (x, y) = board_idx_from_screen_pos(
                         ms.game.height,
                         board_offset,
                         cell_size,
-                        pos,
+                        tap.pos,
                         flipped,
                     );
-
-                    if hint {
-                        ms.hint(frame, x, y);
-                    } else {
-                        ms.click(frame, x, y);
-                    }
-
-                    if ms.game.is_in_bounds(x, y) {
+                    ms.chord(frame, x, y);
+                    if mouse_moves_cursor && ms.game.is_in_bounds(x, y) {
                         ms.cursor_x = x;
                         ms.cursor_y = y;
                     }
                 }
             }
+            if !i.pointer.any_down() {
+                ms.two_finger_tap = None;
+            }
         });
     }
 
-    // draw
-    let painter = ui.painter();
-    let dark_mode = ui.visuals().dark_mode;
-    let bg_color = ui.style().visuals.window_fill;
-    let cell_stroke = Stroke::new(1.0, bg_color);
-    painter.rect(board_rect, 0.0, bg_color, Stroke::NONE);
-
-    let color_cursor = if dark_mode {
-        Color32::from_rgb(0xd0, 0xe0, 0xff)
-    } else {
-        Color32::from_rgb(0x20, 0x40, 0x70)
-    };
+    // the field under the held primary button is drawn pressed, sliding off the board before
+    // releasing cancels the click
+    let primary_down = ui.input(|i| i.pointer.primary_down());
+    let pressing = input_enabled
+        && playing
+        && !over_minimap
+        && !ms.long_press
+        && !ms.space_drag
+        && primary_down;
+    let pressed = resp
+        .interact_pointer_pos()
+        .filter(|_| pressing && resp.is_pointer_button_down_on())
+        .map(|pos| {
+            board_idx_from_screen_pos(ms.game.height, board_offset, cell_size, pos, flipped)
+        });
 
-    let color_hide = if dark_mode {
-        Color32::from_gray(0x40)
-    } else {
-        Color32::from_gray(0xa0)
+    // with press and cancel, the hidden neighbors of a held chord are drawn pressed as well, like
+    // in some classic clients
+    let (middle_down, any_down) = ui.input(|i| {
+        let middle = i.pointer.button_down(PointerButton::Middle);
+        (middle, i.pointer.any_down())
+    });
+    let chord = ms.settings.chord;
+    let chording = ms.both_buttons.is_some()
+        || (chord.middle_click && middle_down)
+        || (chord.left_click && primary_down);
+    let chord_pressed = resp
+        .interact_pointer_pos()
+        .filter(|_| chord.unsatisfied == UnsatisfiedChord::PressAndCancel)
+        .filter(|_| input_enabled && playing && !over_minimap && !ms.long_press)
+        .filter(|_| chording && any_down && resp.is_pointer_button_down_on())
+        .map(|pos| board_idx_from_screen_pos(ms.game.height, board_offset, cell_size, pos, flipped))
+        .filter(|&(x, y)| {
+            ms.game.is_in_bounds(x, y) && ms.game[(x, y)].visibility() == Visibility::Show
+        });
+    let chord_pressed = |(x, y): (i16, i16)| {
+        chord_pressed.is_some_and(|(cx, cy)| (x - cx).abs() <= 1 && (y - cy).abs() <= 1)
     };
-    let color_hint = if dark_mode {
-        Color32::from_rgb(0xf0, 0xc0, 0x30)
-    } else {
-        Color32::from_rgb(0xf0, 0xc0, 0x30)
+
+    // draw
+    let clip_rect = Rect::from_min_max(Pos2::new(0.0, menu_bar_height), viewport.max);
+    let painter = &ui.painter_at(clip_rect);
+    let builtin_theme;
+    let theme = match &ms.theme {
+        Some(theme) => theme,
+        None => {
+            builtin_theme = self::builtin_theme(&ms.settings, ui.visuals());
+            &builtin_theme
+        }
     };
-    let color_show = if dark_mode {
-        Color32::from_gray(0x80)
+    // the large mode thickens the outlines and the grid of any theme and makes numbers bold
+    let large_theme;
+    let theme = if ms.settings.large_mode {
+        large_theme = Theme {
+            grid: Stroke::new(theme.grid.width * 2.0, theme.grid.color),
+            stroke_width: theme.stroke_width * 2.5,
+            bold_numbers: true,
+            ..theme.clone()
+        };
+        &large_theme
     } else {
-        Color32::from_gray(0xc0)
+        theme
     };
-    let color_lose = if dark_mode {
-        Color32::from_rgb(0xd0, 0x60, 0x30)
-    } else {
-        Color32::from_rgb(0xd0, 0x60, 0x30)
+    let number_size = if ms.settings.large_mode { 0.95 } else { 0.8 };
+    let cell_stroke = theme.grid;
+    paint_backdrop(painter, theme, clip_rect);
+    painter.rect(board_rect, 0.0, theme.background, Stroke::NONE);
+
+    // progress
+    if ms.settings.progress_bar {
+        let num_hints = ms.game.num_mines as i16 - ms.game.mines_remaining();
+        let num_free = ms.game.fields.len() - ms.game.num_mines as usize;
+        let hint_progress = num_hints as f32 / ms.game.num_mines.max(1) as f32;
+        let shown_progress = ms.game.num_shown() as f32 / num_free.max(1) as f32;
+
+        let bar_size = Vec2::new(visible_rect.width(), 0.5 * progress_bar_height - 2.0);
+        let mut bar_pos = visible_rect.min - Vec2::new(0.0, progress_bar_height);
+        for (progress, color) in [(hint_progress, theme.hint), (shown_progress, theme.shown)] {
+            let bar = Rect::from_min_size(bar_pos, bar_size);
+            let mut filled = bar;
+            filled.set_width(bar.width() * progress.clamp(0.0, 1.0));
+            painter.rect_filled(bar, 0.0, theme.hidden);
+            painter.rect_filled(filled, 0.0, color);
+            bar_pos.y += 0.5 * progress_bar_height;
+        }
+    }
+
+    // with reduced motion all mines are revealed at once and there is no blast
+    let explosion = match (ms.lost_at, ms.game.loss, ms.settings.reduced_motion) {
+        (Some(lost_at), Some(loss), false) => Some((lost_at.elapsed().as_secs_f32(), loss)),
+        _ => None,
     };
-    let colors_nums: [Color32; 8] = [
-        Color32::BLUE,
-        Color32::GREEN,
-        Color32::RED,
-        Color32::DARK_BLUE,
-        Color32::DARK_RED,
-        Color32::LIGHT_BLUE,
-        Color32::BLACK,
-        Color32::GRAY,
-    ];
 
-    for y in 0..ms.game.height {
-        for x in 0..ms.game.width {
+    let time = ui.input(|i| i.time);
+    if ms.revealed_at.len() != ms.game.fields.len() {
+        // fields that were already revealed, e.g. in a restored game, don't grow
+        let revealed =
+            |f: &Field| (f.visibility() == Visibility::Show).then_some(f64::NEG_INFINITY);
+        ms.revealed_at = ms.game.fields.iter().map(revealed).collect();
+        ms.game.recount();
+    }
+
+    // large boards are painted as one mesh, which is placed below everything drawn on the cells
+    let mut mesh = (ms.game.fields.len() >= MESH_MIN_FIELDS).then(Mesh::default);
+    let ghost = ms.ghost.as_ref().filter(|_| ms.settings.ghost);
+    let mesh_idx = painter.add(Shape::Noop);
+    // only the chunks that can be seen are painted
+    let [min, max] = [visible_rect.min, visible_rect.max].map(|pos| {
+        board_idx_from_screen_pos(ms.game.height, board_offset, cell_size, pos, flipped)
+    });
+    let visible_min = (min.0.min(max.0), min.1.min(max.1));
+    let visible_max = (min.0.max(max.0), min.1.max(max.1));
+    for chunk in ms.game.fields.chunks_in(visible_min, visible_max) {
+        for (x, y) in ms.game.fields.chunk_fields(chunk) {
             let field = ms.game[(x, y)];
+            let revealed_at = &mut ms.revealed_at[ms.game.width as usize * y as usize + x as usize];
+            let pop = match field.visibility() {
+                Visibility::Show => {
+                    let elapsed = (time - *revealed_at.get_or_insert(time)) as f32;
+                    (elapsed / NUMBER_POP_DURATION).min(1.0)
+                }
+                _ => {
+                    *revealed_at = None;
+                    1.0
+                }
+            };
+            let pop = if ms.settings.reduced_motion { 1.0 } else { pop };
+            if pop < 1.0 {
+                ui.ctx().request_repaint();
+            }
+            let color_hint = match &ms.game.hot_seat {
+                Some(hot_seat) => {
+                    hot_seat.hint_color(ms.game.width as usize * y as usize + x as usize)
+                }
+                None => theme.hint,
+            };
+            // mines are revealed outward from the one that was hit
+            let mine_hidden = match explosion {
+                Some((elapsed, loss)) => {
+                    let distance = ((x - loss.x) as f32).hypot((y - loss.y) as f32);
+                    elapsed < distance * MINE_REVEAL_DELAY
+                }
+                None => false,
+            };
 
+            let board_pos = (x, y);
             let (x, y) = if flipped {
                 (ms.game.height - y - 1, x)
             } else {
@@ -778,160 +3834,666 @@ pub fn update(frame: &mut eframe::Frame, ui: &mut Ui, ms: &mut Minesweeper) {
             };
             let cell_pos = board_offset + Vec2::new(x as f32, y as f32) * cell_size;
             let cell_rect = Rect::from_min_size(cell_pos, cell_size);
-            let cell_center_pos = cell_pos + cell_size / 2.0;
-            let mut text_style = TextStyle::Monospace.resolve(ui.style().as_ref());
-            text_style.size = cell_size.y * 0.8;
+            // only paint what can be seen of large or zoomed boards
+            if !cell_rect.intersects(visible_rect) {
+                continue;
+            }
+            if let Some(cell_painter) = &theme.cell_painter {
+                let state = cell_state(ms.game.play_state, field, mine_hidden);
+                let (x, y) = board_pos;
+                let hint_color = color_hint;
+                (cell_painter.0)(
+                    painter,
+                    cell_rect,
+                    CellView {
+                        x,
+                        y,
+                        state,
+                        hint_color,
+                    },
+                );
+                continue;
+            }
+            let number_rect = Rect::from_center_size(cell_rect.center(), pop * cell_size);
+            let font_id = FontId::new(pop * cell_size.y * number_size, theme.number_font.clone());
 
             match ms.game.play_state {
-                PlayState::Init | PlayState::Playing(_) => match (field.state, field.visibility) {
-                    (_, Visibility::Hide) => {
-                        painter.rect(cell_rect, 0.0, color_hide, cell_stroke);
-                    }
-                    (_, Visibility::Hint) => {
-                        painter.rect(cell_rect, 0.0, color_hint, cell_stroke);
-                    }
-                    (FieldState::Free(n), Visibility::Show) => {
-                        painter.rect(cell_rect, 0.0, color_show, cell_stroke);
-                        if n != 0 {
-                            let num_color = colors_nums[n as usize - 1];
-                            painter.text(
-                                cell_center_pos,
-                                Align2::CENTER_CENTER,
-                                n,
-                                text_style,
-                                num_color,
+                PlayState::Init | PlayState::Playing(_) => {
+                    match (field.state(), field.visibility()) {
+                        (_, Visibility::Hide)
+                            if pressed == Some(board_pos) || chord_pressed(board_pos) =>
+                        {
+                            paint_cell(
+                                painter,
+                                &mut mesh,
+                                theme,
+                                cell_rect,
+                                Sprite::Shown,
+                                theme.shown,
+                                cell_stroke,
+                            );
+                        }
+                        (_, Visibility::Hide) => {
+                            paint_cell(
+                                painter,
+                                &mut mesh,
+                                theme,
+                                cell_rect,
+                                Sprite::Hidden,
+                                theme.hidden,
+                                cell_stroke,
+                            );
+                            if ghost.is_some_and(|g| g.is_shown(board_pos.0, board_pos.1)) {
+                                painter.rect_filled(
+                                    cell_rect.shrink(0.15 * cell_size.x),
+                                    theme.rounding,
+                                    theme.shown.gamma_multiply(0.4),
+                                );
+                            }
+                        }
+                        (_, Visibility::Hint) => {
+                            paint_cell(
+                                painter,
+                                &mut mesh,
+                                theme,
+                                cell_rect,
+                                Sprite::Hidden,
+                                color_hint,
+                                cell_stroke,
+                            );
+                            paint_flag(painter, theme, cell_rect);
+                        }
+                        (FieldState::Free(n), Visibility::Show) => {
+                            paint_cell(
+                                painter,
+                                &mut mesh,
+                                theme,
+                                cell_rect,
+                                Sprite::Shown,
+                                theme.shown,
+                                cell_stroke,
+                            );
+                            if n != 0 && pop > 0.0 {
+                                let shape = ms.settings.number_shapes;
+                                paint_number(
+                                    painter,
+                                    theme,
+                                    &mut ms.galleys,
+                                    number_rect,
+                                    n,
+                                    font_id,
+                                    shape,
+                                );
+                            }
+                        }
+                        (FieldState::Mine, Visibility::Show) => {
+                            // Just for debugging
+                            paint_cell(
+                                painter,
+                                &mut mesh,
+                                theme,
+                                cell_rect,
+                                Sprite::Shown,
+                                theme.safe,
+                                cell_stroke,
                             );
                         }
                     }
-                    (FieldState::Mine, Visibility::Show) => {
-                        // Just for debugging
-                        painter.rect(cell_rect, 0.0, Color32::GREEN, cell_stroke);
-                    }
-                },
-                PlayState::Won(_) => match (field.state, field.visibility) {
+                }
+                PlayState::Won(_) => match (field.state(), field.visibility()) {
                     (FieldState::Free(n), _) => {
-                        painter.rect(cell_rect, 0.0, color_show, cell_stroke);
-                        if n != 0 {
-                            let num_color = colors_nums[n as usize - 1];
-                            painter.text(
-                                cell_center_pos,
-                                Align2::CENTER_CENTER,
+                        paint_cell(
+                            painter,
+                            &mut mesh,
+                            theme,
+                            cell_rect,
+                            Sprite::Shown,
+                            theme.shown,
+                            cell_stroke,
+                        );
+                        if n != 0 && pop > 0.0 {
+                            let shape = ms.settings.number_shapes;
+                            paint_number(
+                                painter,
+                                theme,
+                                &mut ms.galleys,
+                                number_rect,
                                 n,
-                                text_style,
-                                num_color,
+                                font_id,
+                                shape,
                             );
                         }
                     }
                     (FieldState::Mine, Visibility::Hint) => {
-                        painter.rect(cell_rect, 0.0, color_hint, cell_stroke);
-                        painter.text(
-                            cell_center_pos,
-                            Align2::CENTER_CENTER,
-                            "*",
-                            text_style,
-                            Color32::BLACK,
+                        paint_cell(
+                            painter,
+                            &mut mesh,
+                            theme,
+                            cell_rect,
+                            Sprite::Hidden,
+                            color_hint,
+                            cell_stroke,
                         );
+                        paint_flag(painter, theme, cell_rect);
                     }
                     (FieldState::Mine, _) => {
-                        painter.rect(cell_rect, 0.0, color_show, cell_stroke);
-                        painter.text(
-                            cell_center_pos,
-                            Align2::CENTER_CENTER,
-                            "*",
-                            text_style,
-                            Color32::BLACK,
+                        paint_cell(
+                            painter,
+                            &mut mesh,
+                            theme,
+                            cell_rect,
+                            Sprite::Shown,
+                            theme.shown,
+                            cell_stroke,
                         );
+                        paint_mine(painter, theme, cell_rect);
                     }
                 },
-                PlayState::Lost(_) => match (field.state, field.visibility) {
+                PlayState::Lost(_) => match (field.state(), field.visibility()) {
+                    (FieldState::Mine, Visibility::Hide) if mine_hidden => {
+                        paint_cell(
+                            painter,
+                            &mut mesh,
+                            theme,
+                            cell_rect,
+                            Sprite::Hidden,
+                            theme.hidden,
+                            cell_stroke,
+                        );
+                    }
                     (FieldState::Free(_), Visibility::Hide) => {
-                        painter.rect(cell_rect, 0.0, color_hide, cell_stroke);
+                        paint_cell(
+                            painter,
+                            &mut mesh,
+                            theme,
+                            cell_rect,
+                            Sprite::Hidden,
+                            theme.hidden,
+                            cell_stroke,
+                        );
                     }
                     (FieldState::Free(_), Visibility::Hint) => {
-                        painter.rect(cell_rect, 0.0, color_hint, cell_stroke);
-                        painter.text(
-                            cell_center_pos,
-                            Align2::CENTER_CENTER,
-                            "x",
-                            text_style,
-                            Color32::RED,
+                        paint_cell(
+                            painter,
+                            &mut mesh,
+                            theme,
+                            cell_rect,
+                            Sprite::Hidden,
+                            color_hint,
+                            cell_stroke,
                         );
+                        paint_flag(painter, theme, cell_rect);
+                        paint_cross(painter, cell_rect, theme.wrong_hint);
                     }
                     (FieldState::Free(n), Visibility::Show) => {
-                        painter.rect(cell_rect, 0.0, color_show, cell_stroke);
-                        if n != 0 {
-                            let num_color = colors_nums[n as usize - 1];
-                            painter.text(
-                                cell_center_pos,
-                                Align2::CENTER_CENTER,
+                        paint_cell(
+                            painter,
+                            &mut mesh,
+                            theme,
+                            cell_rect,
+                            Sprite::Shown,
+                            theme.shown,
+                            cell_stroke,
+                        );
+                        if n != 0 && pop > 0.0 {
+                            let shape = ms.settings.number_shapes;
+                            paint_number(
+                                painter,
+                                theme,
+                                &mut ms.galleys,
+                                number_rect,
                                 n,
-                                text_style,
-                                num_color,
+                                font_id,
+                                shape,
                             );
                         }
                     }
                     (FieldState::Mine, Visibility::Hide) => {
-                        painter.rect(cell_rect, 0.0, color_show, cell_stroke);
-                        painter.text(
-                            cell_center_pos,
-                            Align2::CENTER_CENTER,
-                            "*",
-                            text_style,
-                            Color32::BLACK,
+                        paint_cell(
+                            painter,
+                            &mut mesh,
+                            theme,
+                            cell_rect,
+                            Sprite::Shown,
+                            theme.shown,
+                            cell_stroke,
                         );
+                        paint_mine(painter, theme, cell_rect);
                     }
                     (FieldState::Mine, Visibility::Hint) => {
-                        painter.rect(cell_rect, 0.0, color_hint, cell_stroke);
-                        painter.text(
-                            cell_center_pos,
-                            Align2::CENTER_CENTER,
-                            "*",
-                            text_style,
-                            Color32::BLACK,
+                        paint_cell(
+                            painter,
+                            &mut mesh,
+                            theme,
+                            cell_rect,
+                            Sprite::Hidden,
+                            color_hint,
+                            cell_stroke,
                         );
+                        paint_flag(painter, theme, cell_rect);
                     }
                     (FieldState::Mine, Visibility::Show) => {
-                        painter.rect(cell_rect, 0.0, color_lose, cell_stroke);
-                        painter.text(
-                            cell_center_pos,
-                            Align2::CENTER_CENTER,
-                            "*",
-                            text_style,
-                            Color32::BLACK,
+                        paint_cell(
+                            painter,
+                            &mut mesh,
+                            theme,
+                            cell_rect,
+                            Sprite::Shown,
+                            theme.lose,
+                            cell_stroke,
                         );
+                        paint_mine(painter, theme, cell_rect);
                     }
                 },
             }
         }
     }
+    if let Some(mesh) = mesh {
+        painter.set(mesh_idx, Shape::mesh(mesh));
+    }
+
+    // guide lines
+    if ms.settings.guide_lines {
+        let stroke = Stroke::new(2.0 * theme.stroke_width, theme.cursor.gamma_multiply(0.4));
+        for x in (GUIDE_SPACING..ms.game.width).step_by(GUIDE_SPACING as usize) {
+            let rect = cell_rect(ms.game.height, board_offset, cell_size, x, 0, flipped);
+            let line = if flipped {
+                [board_rect.left(), board_rect.right()].map(|x| Pos2::new(x, rect.top()))
+            } else {
+                [board_rect.top(), board_rect.bottom()].map(|y| Pos2::new(rect.left(), y))
+            };
+            painter.line_segment(line, stroke);
+        }
+        for y in (GUIDE_SPACING..ms.game.height).step_by(GUIDE_SPACING as usize) {
+            let rect = cell_rect(ms.game.height, board_offset, cell_size, 0, y, flipped);
+            let line = if flipped {
+                [board_rect.top(), board_rect.bottom()].map(|y| Pos2::new(rect.right(), y))
+            } else {
+                [board_rect.left(), board_rect.right()].map(|x| Pos2::new(x, rect.top()))
+            };
+            painter.line_segment(line, stroke);
+        }
+    }
+
+    // solver hint
+    if let Some((x, y)) = ms.solver_hint {
+        if ms.game[(x, y)].visibility() == Visibility::Show {
+            ms.solver_hint = None;
+        } else {
+            let rect = cell_rect(ms.game.height, board_offset, cell_size, x, y, flipped);
+            painter.rect(
+                rect.shrink(2.0),
+                4.0,
+                Color32::TRANSPARENT,
+                Stroke::new(3.0 * theme.stroke_width, theme.safe),
+            );
+        }
+    }
+
+    // loss analysis
+    if let (PlayState::Lost(_), Some(loss)) = (ms.game.play_state, ms.game.loss) {
+        let rect = cell_rect(
+            ms.game.height,
+            board_offset,
+            cell_size,
+            loss.x,
+            loss.y,
+            flipped,
+        );
+        painter.rect(
+            rect,
+            0.0,
+            Color32::TRANSPARENT,
+            Stroke::new(3.0 * theme.stroke_width, Color32::RED),
+        );
+
+        if let Some((elapsed, _)) = explosion {
+            let t = elapsed / EXPLOSION_DURATION;
+            if t < 1.0 {
+                let radius = cell_size.y * (0.5 + 2.5 * t);
+                let color = theme.lose.gamma_multiply(1.0 - t);
+                painter.circle_filled(rect.center(), radius, color);
+            }
+
+            let max_dx = loss.x.max(ms.game.width - loss.x - 1) as f32;
+            let max_dy = loss.y.max(ms.game.height - loss.y - 1) as f32;
+            let duration = EXPLOSION_DURATION.max(max_dx.hypot(max_dy) * MINE_REVEAL_DELAY);
+            if elapsed < duration {
+                ui.ctx().request_repaint();
+            }
+        }
+
+        if let Some((x, y)) = loss.chord {
+            let rect = cell_rect(ms.game.height, board_offset, cell_size, x, y, flipped);
+            painter.rect(
+                rect,
+                0.0,
+                Color32::TRANSPARENT,
+                Stroke::new(2.0 * theme.stroke_width, theme.lose),
+            );
+        }
+
+        if let Some(verdict) = &ms.verdict {
+            for &(x, y) in verdict.safe.iter() {
+                let rect = cell_rect(ms.game.height, board_offset, cell_size, x, y, flipped);
+                painter.rect(
+                    rect.shrink(2.0),
+                    0.0,
+                    Color32::TRANSPARENT,
+                    Stroke::new(2.0 * theme.stroke_width, theme.safe),
+                );
+            }
+        }
+    }
+
+    let cell_rect_at = |x, y| cell_rect(ms.game.height, board_offset, cell_size, x, y, flipped);
+    a11y::board(
+        ui.ctx(),
+        &ms.game,
+        &tr,
+        (visible_min, visible_max),
+        cell_rect_at,
+    );
+    ms.announcer.update(&ms.game, &tr);
+    ms.announcer.ui(ui.ctx());
+
+    // the field is read again when it changes, like when it was flagged
+    let cursor_in_bounds = ms.game.is_in_bounds(ms.cursor_x, ms.cursor_y);
+    if ms.settings.speak_cursor && ms.cursor_visible && cursor_in_bounds {
+        let (x, y) = (ms.cursor_x, ms.cursor_y);
+        let spoken = Some(((x, y), ms.game[(x, y)], ms.settings.language));
+        if spoken != ms.spoken {
+            let label = a11y::cell_label(&ms.game, &tr, x, y);
+            match &mut ms.feedback {
+                Some(feedback) => feedback.speak(&label),
+                None => feedback::speak(&label),
+            }
+            ms.spoken = spoken;
+        }
+    }
+
+    // hover
+    ms.hovered = None;
+    if let (Some(pos), true, false) = (resp.hover_pos(), playing, over_minimap) {
+        let (x, y) =
+            board_idx_from_screen_pos(ms.game.height, board_offset, cell_size, pos, flipped);
+        if ms.game.is_in_bounds(x, y) {
+            let rect = cell_rect(ms.game.height, board_offset, cell_size, x, y, flipped);
+            painter.rect_filled(rect, 0.0, theme.cursor.gamma_multiply(0.2));
+            ms.hovered = Some((x, y));
+        }
+    }
+
+    // the cursors of teammates in co-op, with their names
+    #[cfg(feature = "multiplayer")]
+    for (name, (x, y), color) in ms.multiplayer.cursors() {
+        if !ms.game.is_in_bounds(x, y) {
+            continue;
+        }
+        let rect = cell_rect(ms.game.height, board_offset, cell_size, x, y, flipped);
+        let stroke = Stroke::new(2.0 * scale * theme.stroke_width, color);
+        painter.rect(rect, 4.0, Color32::TRANSPARENT, stroke);
+        let galley = painter.layout_no_wrap(name.to_string(), FontId::proportional(14.0), color);
+        let tag = Rect::from_min_size(
+            rect.left_top() - Vec2::new(0.0, galley.size().y + 4.0),
+            galley.size() + Vec2::new(8.0, 4.0),
+        );
+        painter.rect_filled(tag, 3.0, theme.background.gamma_multiply(0.8));
+        painter.galley(tag.min + Vec2::new(4.0, 2.0), galley);
+    }
 
     // cursor
     if ms.cursor_visible {
-        let cursor_idx = if flipped {
-            Vec2::new(
-                (ms.game.height - ms.cursor_y - 1) as f32,
-                ms.cursor_x as f32,
-            )
+        let cursor_rect = cell_rect(
+            ms.game.height,
+            board_offset,
+            cell_size,
+            ms.cursor_x,
+            ms.cursor_y,
+            flipped,
+        );
+        // the field under the cursor can be focused, so screen readers follow it
+        let id = a11y::cell_id(ms.cursor_x, ms.cursor_y);
+        ui.interact(cursor_rect, id, Sense::focusable_noninteractive());
+        // arrow keys move the cursor instead of the focus, tab still leaves the board
+        let filter = EventFilter {
+            arrows: true,
+            ..Default::default()
+        };
+        ui.memory_mut(|m| m.set_focus_lock_filter(id, filter));
+        let cursor_rect = if ms.settings.reduced_motion {
+            cursor_rect
         } else {
-            Vec2::new(ms.cursor_x as f32, ms.cursor_y as f32)
+            // slide between fields, which makes fast movement easier to follow
+            let ctx = ui.ctx();
+            let x = ctx.animate_value_with_time(
+                Id::new("cursor_x"),
+                cursor_rect.min.x,
+                CURSOR_MOVE_DURATION,
+            );
+            let y = ctx.animate_value_with_time(
+                Id::new("cursor_y"),
+                cursor_rect.min.y,
+                CURSOR_MOVE_DURATION,
+            );
+            Rect::from_min_size(Pos2::new(x, y), cursor_rect.size())
         };
-        let cursor_pos = board_offset + cursor_idx * cell_size;
-        let cursor_rect = Rect::from_min_size(cursor_pos, cell_size);
+        if !ms.settings.reduced_motion {
+            // a glow around the cursor, so it can be found at a glance on large boards
+            let time = ui.input(|i| i.time) as f32;
+            let pulse = 0.5 + 0.5 * (time * std::f32::consts::TAU / CURSOR_PULSE_PERIOD).sin();
+            let glow = Stroke::new(
+                (2.0 + 3.0 * pulse) * scale * theme.stroke_width,
+                theme.cursor.gamma_multiply(0.5 * pulse),
+            );
+            painter.rect(cursor_rect.expand(2.0), 6.0, Color32::TRANSPARENT, glow);
+            ui.ctx().request_repaint();
+        }
         painter.rect(
             cursor_rect,
             4.0,
             Color32::TRANSPARENT,
-            Stroke::new(2.0, color_cursor),
+            Stroke::new(2.0 * scale * theme.stroke_width, theme.cursor),
+        );
+    }
+
+    if let Some(AutoPlay {
+        last_move: Some(mv),
+        ..
+    }) = ms.auto_play
+    {
+        let color_auto_play = Color32::from_rgb(0xa0, 0x60, 0xf0);
+        let rect = cell_rect(ms.game.height, board_offset, cell_size, mv.x, mv.y, flipped);
+        painter.rect(
+            rect,
+            4.0,
+            Color32::TRANSPARENT,
+            Stroke::new(2.0, color_auto_play),
+        );
+
+        let galley = painter.layout_no_wrap(
+            mv.reason.to_string(),
+            FontId::proportional(20.0),
+            Color32::WHITE,
+        );
+        let text_pos = visible_rect.center_bottom() - Vec2::new(0.0, 10.0);
+        let text_rect =
+            Align2::CENTER_BOTTOM.anchor_rect(Rect::from_min_size(text_pos, galley.size()));
+        painter.rect(
+            text_rect.expand(4.0),
+            4.0,
+            Color32::from_black_alpha(0xb0),
+            Stroke::new(1.0, color_auto_play),
+        );
+        painter.galley(text_rect.min, galley);
+    }
+
+    // board id
+    if let (Some(board_id), true) = (ms.game.board_id(), ms.settings.show_board_id) {
+        let galley = painter.layout_no_wrap(
+            board_id,
+            FontId::monospace(10.0 * scale),
+            Color32::from_white_alpha(0xc0),
+        );
+        let text_pos = visible_rect.left_bottom() + Vec2::new(4.0, -4.0);
+        let text_rect =
+            Align2::LEFT_BOTTOM.anchor_rect(Rect::from_min_size(text_pos, galley.size()));
+        painter.rect_filled(text_rect.expand(2.0), 2.0, Color32::from_black_alpha(0x80));
+        painter.galley(text_rect.min, galley);
+    }
+
+    // board generation
+    if let Some(generation) = &ms.generation {
+        let checked = generation.checked.load(Ordering::Relaxed);
+        let galley = painter.layout_no_wrap(
+            fill(&tr.checking_boards, checked),
+            FontId::proportional(16.0 * scale),
+            Color32::WHITE,
+        );
+        let spinner_size = galley.size().y;
+        let size = galley.size() + Vec2::new(spinner_size + 8.0, 0.0);
+        let rect = Rect::from_center_size(visible_rect.center(), size);
+        painter.rect_filled(rect.expand(8.0), 6.0, Color32::from_black_alpha(0xc0));
+        let spinner_rect = Rect::from_min_size(rect.min, Vec2::splat(spinner_size));
+        egui::Spinner::new()
+            .size(spinner_size)
+            .color(Color32::WHITE)
+            .paint_at(ui, spinner_rect);
+        painter.galley(rect.min + Vec2::new(spinner_size + 8.0, 0.0), galley);
+    }
+
+    // where exported files were saved
+    if let Some((message, saved_at)) = &ms.save_message {
+        let remaining = Duration::from_secs(4).checked_sub(saved_at.elapsed());
+        match remaining {
+            Some(remaining) => {
+                let galley = painter.layout(
+                    message.clone(),
+                    FontId::proportional(14.0 * scale),
+                    Color32::WHITE,
+                    visible_rect.width() - 16.0,
+                );
+                let text_pos = visible_rect.center_top() + Vec2::new(0.0, 8.0);
+                let text_rect =
+                    Align2::CENTER_TOP.anchor_rect(Rect::from_min_size(text_pos, galley.size()));
+                painter.rect_filled(text_rect.expand(4.0), 4.0, Color32::from_black_alpha(0xc0));
+                painter.galley(text_rect.min, galley);
+                ui.ctx().request_repaint_after(remaining);
+            }
+            None => ms.save_message = None,
+        }
+    }
+
+    // scroll indicators
+    let indicator_color = theme.cursor.gamma_multiply(0.5);
+    if board_rect.width() > viewport.width() {
+        let fraction = viewport.width() / board_rect.width();
+        let start = (viewport.left() - board_rect.left()) / board_rect.width();
+        let thumb = Rect::from_min_size(
+            Pos2::new(
+                viewport.left() + start * viewport.width(),
+                viewport.bottom() - SCROLL_INDICATOR_WIDTH,
+            ),
+            Vec2::new(fraction * viewport.width(), SCROLL_INDICATOR_WIDTH),
+        );
+        painter.rect_filled(thumb, 2.0, indicator_color);
+    }
+    if board_rect.height() > viewport.height() {
+        let fraction = viewport.height() / board_rect.height();
+        let start = (viewport.top() - board_rect.top()) / board_rect.height();
+        let thumb = Rect::from_min_size(
+            Pos2::new(
+                viewport.right() - SCROLL_INDICATOR_WIDTH,
+                viewport.top() + start * viewport.height(),
+            ),
+            Vec2::new(SCROLL_INDICATOR_WIDTH, fraction * viewport.height()),
         );
+        painter.rect_filled(thumb, 2.0, indicator_color);
     }
 
-    if let PlayState::Won(_) | PlayState::Lost(_) = ms.game.play_state {
+    // minimap
+    if let Some(minimap_rect) = minimap_rect {
+        painter.rect(minimap_rect.expand(2.0), 2.0, theme.background, theme.grid);
+        let mini_cell_size = minimap_rect.size() / cells;
+        for y in 0..ms.game.height {
+            for x in 0..ms.game.width {
+                let color = match ms.game[(x, y)].visibility() {
+                    Visibility::Hide => theme.hidden,
+                    Visibility::Hint => theme.hint,
+                    Visibility::Show => theme.shown,
+                };
+                let rect = cell_rect(
+                    ms.game.height,
+                    minimap_rect.min,
+                    mini_cell_size,
+                    x,
+                    y,
+                    flipped,
+                );
+                painter.rect_filled(rect, 0.0, color);
+            }
+        }
+
+        let scale = minimap_rect.size() / board_rect.size();
+        let view_rect = Rect::from_min_size(
+            minimap_rect.min + (visible_rect.min - board_rect.min) * scale,
+            visible_rect.size() * scale,
+        );
+        painter.rect_stroke(view_rect, 0.0, Stroke::new(2.0, theme.cursor));
+
+        let pressed = ui.input(|i| i.pointer.primary_down());
+        if let (Some(pos), true, true) = (hover_pos, over_minimap, pressed) {
+            let fraction = (pos - minimap_rect.min) / minimap_rect.size();
+            ms.view.center_on(fraction, viewport, fit_size);
+        }
+    }
+
+    // confetti
+    let confetti = ms.won_at.map(|t| t.elapsed().as_secs_f32());
+    if let (Some(elapsed), false) = (confetti, ms.settings.reduced_motion) {
+        if elapsed < CONFETTI_DURATION {
+            // the same seed every frame, so the pieces keep their paths
+            let mut rng = StdRng::seed_from_u64(0);
+            let painter = ui.painter().with_clip_rect(visible_rect);
+            let size = Vec2::new(cell_size.x * 0.3, cell_size.y * 0.15).max(Vec2::splat(3.0));
+            let fade = (1.0 - elapsed / CONFETTI_DURATION).min(0.5) * 2.0;
+            for _ in 0..CONFETTI_COUNT {
+                let x = rng.gen_range(0.0..1.0);
+                let delay = rng.gen_range(0.0..0.5);
+                let speed = rng.gen_range(0.6..1.2);
+                let phase = rng.gen_range(0.0..std::f32::consts::TAU);
+                let color = CONFETTI_COLORS[rng.gen_range(0..CONFETTI_COLORS.len())];
+
+                let t = (elapsed - delay).max(0.0);
+                let sway = (t * 6.0 + phase).sin() * cell_size.x;
+                let pos = Pos2::new(
+                    visible_rect.left() + x * visible_rect.width() + sway,
+                    visible_rect.top() - size.y + t * speed * visible_rect.height(),
+                );
+                let rect = Rect::from_center_size(pos, size * Vec2::new(phase.cos(), 1.0).abs());
+                painter.rect_filled(rect, 0.0, color.gamma_multiply(fade));
+            }
+            ui.ctx().request_repaint();
+        }
+    }
+
+    let finished = matches!(ms.game.play_state, PlayState::Won(_) | PlayState::Lost(_));
+    let mut new_game = false;
+    let mut retry = false;
+    let mut save_replay = false;
+    if finished && ms.summary_hidden {
+        let rect = Rect::from_min_size(visible_rect.min + Vec2::splat(8.0), Vec2::new(120.0, 30.0));
+        let text = RichText::new(&*tr.show_summary).font(FontId::proportional(16.0));
+        if ui.put(rect, Button::new(text)).clicked() {
+            ms.summary_hidden = false;
+        }
+    } else if finished {
         let min_dimension = available_size.min_elem();
         let margin = Vec2::splat(min_dimension * 0.05);
-        let scoreboard_width = 400.0;
+        let scoreboard_width = 480.0;
         let scoreboard_offset =
-            board_offset + Vec2::new(0.5 * (board_size.x - scoreboard_width), margin.y);
-        let scoreboard_size = Vec2::new(scoreboard_width, board_size.y - 2.0 * margin.y);
+            visible_rect.min + Vec2::new(0.5 * (visible_rect.width() - scoreboard_width), margin.y);
+        let scoreboard_size = Vec2::new(scoreboard_width, visible_rect.height() - 2.0 * margin.y);
         let rect = Rect::from_min_size(scoreboard_offset, scoreboard_size);
         painter.rect(
             rect,
@@ -942,11 +4504,11 @@ pub fn update(frame: &mut eframe::Frame, ui: &mut Ui, ms: &mut Minesweeper) {
 
         let title_pos = scoreboard_offset + Vec2::new(0.5 * scoreboard_size.x, margin.y);
         let unambigous_text = if ms.unambigous {
-            "unambigous"
+            &tr.unambigous
         } else {
-            "ambigous"
+            &tr.ambigous
         };
-        let title = format!("{} {}", ms.difficulty, unambigous_text);
+        let title = format!("{} {}", tr.difficulty(ms.difficulty), unambigous_text);
         painter.text(
             title_pos,
             Align2::CENTER_TOP,
@@ -955,17 +4517,112 @@ pub fn update(frame: &mut eframe::Frame, ui: &mut Ui, ms: &mut Minesweeper) {
             Color32::from_white_alpha(0xb0),
         );
 
-        let scores = &ms.highscores[ms.difficulty as usize + (3 * ms.unambigous as usize)];
-        let is_same_mode = ms.difficulty == ms.game.difficulty && ms.unambigous == ms.game.unambigous;
+        let scores = ms.highscores.records(ms.difficulty, ms.unambigous);
+        let is_same_mode =
+            ms.difficulty == ms.game.difficulty && ms.unambigous == ms.game.unambigous;
+
+        let bbbv = ms.game.bbbv();
+        let mut stats = format!(
+            "{}   3BV {bbbv}",
+            format_duration_precise(ms.game.play_duration())
+        );
+        if let PlayState::Won(time) = ms.game.play_state {
+            let metrics = ms.game.metrics(time);
+            if let Some(rate) = metrics.bbbv_per_sec() {
+                stats += &format!("   3BV/s {rate:.2}");
+            }
+            if let (Some(ioe), Some(efficiency)) = (metrics.ioe(), metrics.efficiency()) {
+                stats += &format!(
+                    "   IOE {ioe:.2}   {}",
+                    fill(&tr.efficiency_percent, format!("{efficiency:.0}"))
+                );
+            }
+        }
+        let mut info_pos = title_pos + Vec2::new(0.0, 36.0);
+        painter.text(
+            info_pos,
+            Align2::CENTER_TOP,
+            stats,
+            FontId::proportional(16.0),
+            Color32::from_white_alpha(0xb0),
+        );
+        info_pos.y += 20.0;
+        let clicks = ms.game.clicks;
+        painter.text(
+            info_pos,
+            Align2::CENTER_TOP,
+            format!(
+                "{} {}   {} {}   {} {}   {} {}",
+                tr.left_clicks,
+                clicks.left,
+                tr.right_clicks,
+                clicks.right,
+                tr.chords,
+                clicks.chords,
+                tr.wasted_clicks,
+                clicks.wasted,
+            ),
+            FontId::proportional(16.0),
+            Color32::from_white_alpha(0xb0),
+        );
+        info_pos.y += 20.0;
+
+        let personal_best = match ms.game.play_state {
+            PlayState::Won(d) => {
+                let fair = !ms.game.assisted && !ms.game.known_board;
+                is_same_mode && fair && scores.first().map(|r| r.time) == Some(d)
+            }
+            _ => false,
+        };
+        if personal_best {
+            let mut text = tr.new_personal_best.to_string();
+            if let [best, previous, ..] = scores {
+                let faster = format_seconds(previous.time - best.time);
+                text = format!("{text} {}", fill(&tr.faster_than_before, faster));
+            }
+            painter.text(
+                info_pos,
+                Align2::CENTER_TOP,
+                text,
+                FontId::proportional(20.0),
+                Color32::from_rgba_unmultiplied(0xff, 0xc0, 0x30, 0xe0),
+            );
+            info_pos.y += 24.0;
+        }
+        if let (PlayState::Won(d), Some(best)) = (ms.game.play_state, ms.previous_seed_best) {
+            let text = if d < best {
+                fill(&tr.beat_best_on_board, format_seconds(best - d))
+            } else {
+                fill(&tr.best_on_board, format_duration_precise(best))
+            };
+            painter.text(
+                info_pos,
+                Align2::CENTER_TOP,
+                text,
+                FontId::proportional(16.0),
+                Color32::from_white_alpha(0xb0),
+            );
+            info_pos.y += 20.0;
+        }
+        if ms.game.solver_hints_used > 0 {
+            painter.text(
+                info_pos,
+                Align2::CENTER_TOP,
+                fill(&tr.solver_hints_used, ms.game.solver_hints_used),
+                FontId::proportional(16.0),
+                Color32::from_white_alpha(0xb0),
+            );
+            info_pos.y += 20.0;
+        }
+        let mut score_y = info_pos.y + margin.y;
 
-        let mut score_y = scoreboard_offset.y + 2.0 * margin.y + 30.0;
         let num_x = scoreboard_offset.x + margin.x;
         let duration_x = scoreboard_offset.x + scoreboard_size.x - margin.x;
         for (i, score) in scores.iter().take(10).enumerate() {
             let mut text_color = Color32::from_white_alpha(0xb0);
             if is_same_mode {
                 if let PlayState::Won(d) = ms.game.play_state {
-                    if *score == d {
+                    if score.time == d {
                         text_color = Color32::from_rgba_unmultiplied(0xff, 0xc0, 0x30, 0xb0);
                     }
                 }
@@ -980,11 +4637,105 @@ pub fn update(frame: &mut eframe::Frame, ui: &mut Ui, ms: &mut Minesweeper) {
             painter.text(
                 Pos2::new(duration_x, score_y),
                 Align2::RIGHT_TOP,
-                format_duration(*score),
+                format_duration_precise(score.time),
                 FontId::proportional(30.0),
                 text_color,
             );
             score_y += 40.0;
         }
+
+        if let (PlayState::Lost(_), Some(loss)) = (ms.game.play_state, ms.game.loss) {
+            let summary_pos = scoreboard_offset
+                + Vec2::new(0.5 * scoreboard_size.x, scoreboard_size.y - margin.y - 40.0);
+            painter.text(
+                summary_pos,
+                Align2::CENTER_BOTTOM,
+                loss.summary(&tr),
+                FontId::proportional(20.0),
+                Color32::from_rgba_unmultiplied(0xff, 0x60, 0x30, 0xb0),
+            );
+
+            if let Some(verdict) = &ms.verdict {
+                // without a safe cell, a region that wasn't searched might still have had one
+                let (text, color) = if !verdict.safe.is_empty() {
+                    (
+                        fill(&tr.had_information, verdict.safe.len()),
+                        Color32::from_rgba_unmultiplied(0x30, 0xd0, 0x60, 0xb0),
+                    )
+                } else if !verdict.unknown.is_empty() {
+                    (
+                        tr.verdict_unknown.to_string(),
+                        Color32::from_white_alpha(0xb0),
+                    )
+                } else {
+                    (tr.forced_guess.to_string(), Color32::from_white_alpha(0xb0))
+                };
+                painter.text(
+                    summary_pos - Vec2::new(0.0, 30.0),
+                    Align2::CENTER_BOTTOM,
+                    text,
+                    FontId::proportional(20.0),
+                    color,
+                );
+            }
+        }
+
+        let button_width = (scoreboard_size.x - 2.0 * margin.x - 30.0) / 4.0;
+        let button_pos =
+            scoreboard_offset + Vec2::new(margin.x, scoreboard_size.y - margin.y - 30.0);
+        let mut button_rect = Rect::from_min_size(button_pos, Vec2::new(button_width, 30.0));
+        for (text, clicked) in [
+            (&tr.new_game, &mut new_game),
+            (&tr.retry_board, &mut retry),
+            (&tr.view_analysis, &mut ms.summary_hidden),
+            (&tr.save_replay, &mut save_replay),
+        ] {
+            let text = RichText::new(&**text).font(FontId::proportional(16.0));
+            *clicked = ui.put(button_rect, Button::new(text)).clicked();
+            button_rect = button_rect.translate(Vec2::new(button_width + 10.0, 0.0));
+        }
+    }
+
+    if new_game {
+        ms.new_game();
+    }
+    if retry {
+        ms.retry(frame);
+    }
+    if save_replay {
+        ms.save_replay(ui.visuals());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn field_packing() {
+        let states = (0..=8).map(FieldState::Free).chain([FieldState::Mine]);
+        let visibilities = [Visibility::Hide, Visibility::Hint, Visibility::Show];
+        let combinations = states
+            .flat_map(|s| visibilities.map(|v| (s, v)))
+            .collect::<Vec<_>>();
+
+        // every combination is written over every other one, so no bits are left behind
+        for &(prev_state, prev_visibility) in combinations.iter() {
+            for &(state, visibility) in combinations.iter() {
+                let mut field = Field::free(0);
+                field.set_state(prev_state);
+                field.set_visibility_unchecked(prev_visibility);
+
+                field.set_state(state);
+                field.set_visibility_unchecked(visibility);
+                assert_eq!(field.state(), state);
+                assert_eq!(field.visibility(), visibility);
+
+                field.set_visibility_unchecked(prev_visibility);
+                field.set_state(prev_state);
+                assert_eq!(field.state(), prev_state);
+                assert_eq!(field.visibility(), prev_visibility);
+            }
+        }
     }
 }
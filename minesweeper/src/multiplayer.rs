@@ -0,0 +1,956 @@
+//! Races on the same board against other players on the local network.
+//!
+//! One player hosts and the others join with the address of the host. Native builds connect over
+//! TCP, the browser connects over WebSocket to a relay that forwards the messages to the host.
+//! Players that can't reach each other directly meet in a room of the relay, which is opened by
+//! the host and entered by the others with its code.
+//!
+//! The host starts races with the seed of a board, which every player generates on their own. It
+//! keeps the list of players and their progress, and sends it to everyone whenever it changes.
+//! Spectators join without playing, the host forwards them the moves and cursors of the players.
+//!
+//! In co-op everyone plays the same board together. The players send their moves to the host,
+//! which makes them in the order they arrive and sends them to everyone in that order, so every
+//! board stays the same. The cursors are forwarded to all others.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::mpsc;
+use std::time::Duration;
+
+use egui::{Button, Color32, Context, FontId, Grid, RichText, Ui};
+use instant::Instant;
+
+pub use minesweeper_protocol::{
+    BoardSeed, Message, Player, Progress, Update, DEFAULT_PORT, MAX_MESSAGE_LEN, VERSION,
+};
+
+use crate::format_duration_precise;
+use crate::replay::Step;
+use crate::translations::{fill, Translations};
+
+#[cfg(not(target_arch = "wasm32"))]
+mod tcp;
+#[cfg(target_arch = "wasm32")]
+mod ws;
+
+#[cfg(not(target_arch = "wasm32"))]
+use tcp::{Connection, Listener};
+#[cfg(target_arch = "wasm32")]
+use ws::{Connection, Listener};
+
+/// The id of the host.
+const HOST_ID: u32 = 0;
+/// The cursor is sent at most this often, so moving the mouse doesn't flood the connection.
+const CURSOR_INTERVAL: Duration = Duration::from_millis(100);
+/// The colors of the cursors of the other players in co-op, by id.
+const CURSOR_COLORS: [Color32; 6] = [
+    Color32::from_rgb(0xf0, 0x60, 0x60),
+    Color32::from_rgb(0x50, 0xb0, 0xf0),
+    Color32::from_rgb(0x60, 0xd0, 0x70),
+    Color32::from_rgb(0xf0, 0xb0, 0x40),
+    Color32::from_rgb(0xc0, 0x70, 0xf0),
+    Color32::from_rgb(0x40, 0xd0, 0xd0),
+];
+
+/// What happened in the session, which the game has to follow.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Event {
+    /// A race or co-op game was started on the board.
+    Start(BoardSeed),
+    /// A move of the watched player, or of any player in co-op in the order of the host.
+    Step(Step),
+    /// The watched player moved the cursor.
+    Cursor(i16, i16),
+}
+
+/// How the host reaches a player.
+enum Link {
+    Direct(Connection),
+    /// Through the relay, with the id the relay gave the player.
+    Relayed {
+        id: u32,
+        inbox: VecDeque<Message>,
+        closed: bool,
+    },
+}
+
+struct Client {
+    id: u32,
+    link: Link,
+    /// Set once the join message arrived.
+    joined: bool,
+    spectator: bool,
+}
+
+impl Client {
+    fn new(id: u32, link: Link) -> Self {
+        Self {
+            id,
+            link,
+            joined: false,
+            spectator: false,
+        }
+    }
+
+    fn relay_id(&self) -> Option<u32> {
+        match self.link {
+            Link::Direct(_) => None,
+            Link::Relayed { id, .. } => Some(id),
+        }
+    }
+
+    fn send(&mut self, relay: &mut Option<Connection>, message: &Message) {
+        match &mut self.link {
+            Link::Direct(connection) => connection.send(message),
+            Link::Relayed { id, .. } => {
+                if let Some(relay) = relay {
+                    relay.send(&Message::Relayed {
+                        client: *id,
+                        message: Box::new(message.clone()),
+                    });
+                }
+            }
+        }
+    }
+
+    fn try_recv(&mut self) -> Result<Message, mpsc::TryRecvError> {
+        match &mut self.link {
+            Link::Direct(connection) => connection.try_recv(),
+            Link::Relayed { inbox, closed, .. } => match inbox.pop_front() {
+                Some(message) => Ok(message),
+                None if *closed => Err(mpsc::TryRecvError::Disconnected),
+                None => Err(mpsc::TryRecvError::Empty),
+            },
+        }
+    }
+}
+
+enum Role {
+    Host {
+        listener: Option<Listener>,
+        /// The connection to the relay, if hosting in a room.
+        relay: Option<Connection>,
+        clients: Vec<Client>,
+        next_id: u32,
+    },
+    Client {
+        connection: Connection,
+        id: Option<u32>,
+        spectator: bool,
+    },
+}
+
+/// The connections to the other players and the lobby.
+#[derive(Default)]
+pub struct Multiplayer {
+    role: Option<Role>,
+    players: Vec<Player>,
+    /// The board of the race that was started last.
+    board: Option<BoardSeed>,
+    /// Whether the board is played together instead of raced on.
+    coop: bool,
+    /// Whether the host starts the next board in co-op.
+    together: bool,
+    /// The moves of every player in the race, which spectators that switch players or join late
+    /// need to catch up.
+    steps: HashMap<u32, Vec<Step>>,
+    /// The moves of everyone in co-op, in the order the host made them.
+    sequence: Vec<(u32, Step)>,
+    cursors: HashMap<u32, (i16, i16)>,
+    /// The number of own moves that were sent.
+    sent_steps: usize,
+    sent_cursor: Option<(i16, i16)>,
+    sent_cursor_at: Option<Instant>,
+    /// The player that is shown to a spectator.
+    watched: Option<u32>,
+    events: Vec<Event>,
+    name: String,
+    /// The address of the host to join, or of the relay.
+    address: String,
+    /// The code of the room on the relay, empty to connect directly.
+    room: String,
+    /// Join without playing.
+    spectate: bool,
+    error: Option<String>,
+}
+
+impl Multiplayer {
+    pub fn board(&self) -> Option<BoardSeed> {
+        self.board
+    }
+
+    pub fn is_coop(&self) -> bool {
+        self.board.is_some() && self.coop
+    }
+
+    pub fn is_spectating(&self) -> bool {
+        matches!(
+            self.role,
+            Some(Role::Client {
+                spectator: true,
+                ..
+            })
+        )
+    }
+
+    fn host(&mut self, tr: &Translations) {
+        match Listener::bind(DEFAULT_PORT) {
+            Ok(listener) => self.start_hosting(Some(listener), None),
+            Err(e) => self.error = Some(fill(&tr.connection_failed, e)),
+        }
+    }
+
+    /// Hosts in the room of the relay, which works without being reachable by the others.
+    fn host_in_room(&mut self, ctx: &Context, tr: &Translations) {
+        match Connection::connect(self.address.trim(), ctx) {
+            Ok(mut relay) => {
+                relay.send(&Message::Open {
+                    room: self.room.trim().to_string(),
+                });
+                self.start_hosting(None, Some(relay));
+            }
+            Err(e) => self.error = Some(fill(&tr.connection_failed, e)),
+        }
+    }
+
+    fn start_hosting(&mut self, listener: Option<Listener>, relay: Option<Connection>) {
+        self.role = Some(Role::Host {
+            listener,
+            relay,
+            clients: Vec::new(),
+            next_id: HOST_ID + 1,
+        });
+        self.players = vec![Player {
+            id: HOST_ID,
+            name: self.name.clone(),
+            progress: Progress::Waiting,
+        }];
+        self.error = None;
+    }
+
+    fn join(&mut self, ctx: &Context, tr: &Translations) {
+        match Connection::connect(self.address.trim(), ctx) {
+            Ok(mut connection) => {
+                let room = self.room.trim();
+                if !room.is_empty() {
+                    connection.send(&Message::Enter {
+                        room: room.to_string(),
+                    });
+                }
+                connection.send(&Message::Join {
+                    name: self.name.clone(),
+                    version: VERSION,
+                    spectator: self.spectate,
+                });
+                self.role = Some(Role::Client {
+                    connection,
+                    id: None,
+                    spectator: self.spectate,
+                });
+                self.error = None;
+            }
+            Err(e) => self.error = Some(fill(&tr.connection_failed, e)),
+        }
+    }
+
+    fn leave(&mut self) {
+        *self = Self {
+            name: std::mem::take(&mut self.name),
+            address: std::mem::take(&mut self.address),
+            room: std::mem::take(&mut self.room),
+            spectate: self.spectate,
+            together: self.together,
+            ..Self::default()
+        };
+    }
+
+    /// Clears the moves of the previous race.
+    fn reset_race(&mut self, board: BoardSeed, coop: bool) {
+        self.board = Some(board);
+        self.coop = coop;
+        self.steps.clear();
+        self.sequence.clear();
+        self.cursors.clear();
+        self.sent_steps = 0;
+        self.sent_cursor = None;
+    }
+
+    fn record(&mut self, player: u32, update: Update) {
+        match update {
+            Update::Step(step) => {
+                self.steps.entry(player).or_default().push(step);
+                self.sequence.push((player, step));
+            }
+            Update::Cursor(x, y) => {
+                self.cursors.insert(player, (x, y));
+            }
+        }
+    }
+
+    /// The messages that bring a spectator to the current state of the race.
+    fn catch_up(&self) -> Vec<Message> {
+        let Some(board) = self.board else { return Vec::new() };
+        // the moves in co-op are made on the same board, so their order matters
+        let steps: Vec<_> = match self.coop {
+            true => self.sequence.clone(),
+            false => self
+                .steps
+                .iter()
+                .flat_map(|(&player, steps)| steps.iter().map(move |&step| (player, step)))
+                .collect(),
+        };
+        let steps = steps.into_iter().map(|(player, step)| Message::Watched {
+            player,
+            update: Update::Step(step),
+        });
+        let cursors = self
+            .cursors
+            .iter()
+            .map(|(&player, &(x, y))| Message::Watched {
+                player,
+                update: Update::Cursor(x, y),
+            });
+        let start = match self.coop {
+            true => Message::Coop(board),
+            false => Message::Start(board),
+        };
+        std::iter::once(start).chain(steps).chain(cursors).collect()
+    }
+
+    /// Handles the messages that arrived, returns what the game has to follow.
+    pub fn poll(&mut self, ctx: &Context, tr: &Translations) -> Vec<Event> {
+        let mut error = None;
+        match &mut self.role {
+            None => (),
+            Some(Role::Host {
+                listener,
+                relay,
+                clients,
+                next_id,
+            }) => {
+                if let Some(listener) = listener {
+                    for connection in listener.accept(ctx) {
+                        clients.push(Client::new(*next_id, Link::Direct(connection)));
+                        *next_id += 1;
+                    }
+                }
+                if let Some(connection) = relay {
+                    loop {
+                        let (relay_id, message) = match connection.try_recv() {
+                            Ok(Message::Relayed { client, message }) => (client, *message),
+                            Ok(Message::Refused) => {
+                                error = Some(tr.room_taken.to_string());
+                                break;
+                            }
+                            Ok(_) => continue,
+                            Err(mpsc::TryRecvError::Empty) => break,
+                            Err(mpsc::TryRecvError::Disconnected) => {
+                                error = Some(tr.connection_lost.to_string());
+                                break;
+                            }
+                        };
+                        let known = clients.iter().position(|c| c.relay_id() == Some(relay_id));
+                        let index = known.unwrap_or_else(|| {
+                            let link = Link::Relayed {
+                                id: relay_id,
+                                inbox: VecDeque::new(),
+                                closed: false,
+                            };
+                            clients.push(Client::new(*next_id, link));
+                            *next_id += 1;
+                            clients.len() - 1
+                        });
+                        if let Link::Relayed { inbox, closed, .. } = &mut clients[index].link {
+                            match message {
+                                Message::Closed => *closed = true,
+                                message => inbox.push_back(message),
+                            }
+                        }
+                    }
+                }
+
+                let mut changed = false;
+                let mut updates = Vec::new();
+                let mut joined_spectators = Vec::new();
+                clients.retain_mut(|client| loop {
+                    let message = match client.try_recv() {
+                        Ok(message) => message,
+                        Err(mpsc::TryRecvError::Empty) => return true,
+                        Err(mpsc::TryRecvError::Disconnected) => {
+                            self.players.retain(|p| p.id != client.id);
+                            changed = true;
+                            return false;
+                        }
+                    };
+                    match message {
+                        // a repeated join would add the player twice
+                        Message::Join { .. } if client.joined => (),
+                        Message::Join { version, .. } if version != VERSION => return false,
+                        Message::Join {
+                            name, spectator, ..
+                        } => {
+                            client.send(relay, &Message::Welcome { id: client.id });
+                            client.joined = true;
+                            client.spectator = spectator;
+                            if spectator {
+                                joined_spectators.push(client.id);
+                            } else {
+                                self.players.push(Player {
+                                    id: client.id,
+                                    name,
+                                    progress: Progress::Waiting,
+                                });
+                            }
+                            changed = true;
+                        }
+                        Message::Progress(progress) if !client.spectator => {
+                            let player = self.players.iter_mut().find(|p| p.id == client.id);
+                            if let Some(player) = player {
+                                player.progress = progress;
+                                changed = true;
+                            }
+                        }
+                        Message::Update(update) if !client.spectator => {
+                            updates.push((client.id, update));
+                        }
+                        _ => (),
+                    }
+                });
+
+                let catch_up = self.catch_up();
+                if let Some(Role::Host { clients, relay, .. }) = &mut self.role {
+                    let late = clients
+                        .iter_mut()
+                        .filter(|c| joined_spectators.contains(&c.id));
+                    for client in late {
+                        for message in catch_up.iter() {
+                            client.send(relay, message);
+                        }
+                    }
+                }
+                // the host makes the moves of everyone in co-op, and sends them back in its order
+                for (player, update) in updates {
+                    self.record(player, update);
+                    if self.coop {
+                        if let Update::Step(step) = update {
+                            self.events.push(Event::Step(step));
+                        }
+                        self.broadcast(&Message::Watched { player, update });
+                    } else {
+                        self.send_spectators(&Message::Watched { player, update });
+                    }
+                }
+                if changed {
+                    self.broadcast(&Message::Players(self.players.clone()));
+                }
+            }
+            Some(Role::Client {
+                connection,
+                id,
+                spectator,
+            }) => {
+                let spectator = *spectator;
+                let mut messages = Vec::new();
+                let disconnected = loop {
+                    match connection.try_recv() {
+                        Ok(Message::Welcome { id: own_id }) => *id = Some(own_id),
+                        Ok(Message::Refused) => {
+                            error = Some(tr.room_not_found.to_string());
+                            break false;
+                        }
+                        Ok(message) => messages.push(message),
+                        Err(mpsc::TryRecvError::Empty) => break false,
+                        Err(mpsc::TryRecvError::Disconnected) => break true,
+                    }
+                };
+
+                for message in messages {
+                    match message {
+                        Message::Players(players) => self.players = players,
+                        Message::Start(board) | Message::Coop(board) if !board.is_valid() => {
+                            log::warn!("the host started an invalid board: {}", board.id());
+                        }
+                        Message::Start(board) => {
+                            self.reset_race(board, false);
+                            self.events.push(Event::Start(board));
+                        }
+                        Message::Coop(board) => {
+                            self.reset_race(board, true);
+                            self.events.push(Event::Start(board));
+                        }
+                        Message::Watched { player, update } if spectator || self.coop => {
+                            self.record(player, update);
+                            let watched = self.watched == Some(player);
+                            match update {
+                                // the board is shared in co-op, so every move is followed
+                                Update::Step(step) if watched || self.coop => {
+                                    self.events.push(Event::Step(step));
+                                }
+                                Update::Cursor(x, y) if watched && spectator => {
+                                    self.events.push(Event::Cursor(x, y));
+                                }
+                                _ => (),
+                            }
+                        }
+                        _ => (),
+                    }
+                }
+                if spectator && self.watched.is_none() {
+                    self.watched = self.players.first().map(|p| p.id);
+                }
+
+                if disconnected {
+                    error = Some(tr.connection_lost.to_string());
+                }
+            }
+        }
+        if let Some(error) = error {
+            self.leave();
+            self.error = Some(error);
+        }
+        std::mem::take(&mut self.events)
+    }
+
+    /// Sends the message to everyone that joined, only the host can.
+    fn broadcast(&mut self, message: &Message) {
+        let Some(Role::Host { clients, relay, .. }) = &mut self.role else { return };
+        for client in clients.iter_mut().filter(|c| c.joined) {
+            client.send(relay, message);
+        }
+    }
+
+    /// Sends the message to every spectator, only the host can.
+    fn send_spectators(&mut self, message: &Message) {
+        let Some(Role::Host { clients, relay, .. }) = &mut self.role else { return };
+        for client in clients.iter_mut().filter(|c| c.joined && c.spectator) {
+            client.send(relay, message);
+        }
+    }
+
+    /// Shows the moves of the player to a spectator, from the start of the race.
+    fn watch(&mut self, player: u32) {
+        self.watched = Some(player);
+        if self.coop {
+            // the board is the same for everyone, only the cursor changes
+            if let Some(&(x, y)) = self.cursors.get(&player) {
+                self.events.push(Event::Cursor(x, y));
+            }
+            return;
+        }
+        let Some(board) = self.board else { return };
+        self.events.push(Event::Start(board));
+        let steps = self.steps.get(&player).into_iter().flatten();
+        self.events.extend(steps.map(|&step| Event::Step(step)));
+        if let Some(&(x, y)) = self.cursors.get(&player) {
+            self.events.push(Event::Cursor(x, y));
+        }
+    }
+
+    /// Starts a race or co-op game on the board, only the host can.
+    pub fn start(&mut self, board: BoardSeed) {
+        self.reset_race(board, self.together);
+        for player in self.players.iter_mut() {
+            player.progress = Progress::Waiting;
+        }
+        match self.coop {
+            true => self.broadcast(&Message::Coop(board)),
+            false => self.broadcast(&Message::Start(board)),
+        }
+        self.broadcast(&Message::Players(self.players.clone()));
+    }
+
+    fn own_id(&self) -> Option<u32> {
+        match &self.role {
+            Some(Role::Host { .. }) => Some(HOST_ID),
+            Some(Role::Client {
+                id,
+                spectator: false,
+                ..
+            }) => *id,
+            _ => None,
+        }
+    }
+
+    /// Tells the other players about the progress in the race, if it changed.
+    pub fn report(&mut self, progress: Progress) {
+        let Some(own_id) = self.own_id() else { return };
+        let Some(player) = self.players.iter_mut().find(|p| p.id == own_id) else { return };
+        if player.progress == progress {
+            return;
+        }
+        player.progress = progress;
+
+        if let Some(Role::Client { connection, .. }) = &mut self.role {
+            connection.send(&Message::Progress(progress));
+        } else {
+            self.broadcast(&Message::Players(self.players.clone()));
+        }
+    }
+
+    /// Sends the moves and the cursor of the player that weren't sent yet, for spectators and
+    /// the other players in co-op. The cursor is sent at most every [`CURSOR_INTERVAL`].
+    pub fn report_moves(&mut self, ctx: &Context, steps: &[Step], cursor: (i16, i16)) {
+        let Some(own_id) = self.own_id() else { return };
+        let mut updates = Vec::new();
+        if let Some(new) = steps.get(self.sent_steps..) {
+            updates.extend(new.iter().map(|&step| Update::Step(step)));
+            self.sent_steps = steps.len();
+        }
+        if self.sent_cursor != Some(cursor) {
+            let since_sent = self.sent_cursor_at.map_or(CURSOR_INTERVAL, |t| t.elapsed());
+            if since_sent >= CURSOR_INTERVAL {
+                updates.push(Update::Cursor(cursor.0, cursor.1));
+                self.sent_cursor = Some(cursor);
+                self.sent_cursor_at = Some(Instant::now());
+            } else {
+                ctx.request_repaint_after(CURSOR_INTERVAL - since_sent);
+            }
+        }
+
+        for update in updates {
+            if let Some(Role::Client { connection, .. }) = &mut self.role {
+                connection.send(&Message::Update(update));
+            } else {
+                self.record(own_id, update);
+                let message = Message::Watched {
+                    player: own_id,
+                    update,
+                };
+                match self.coop {
+                    true => self.broadcast(&message),
+                    false => self.send_spectators(&message),
+                }
+            }
+        }
+    }
+
+    /// Sends the move to the host instead of making it, if playing in co-op as a client. The host
+    /// sends it back in the order of the moves of everyone, so every board stays the same.
+    pub fn propose(&mut self, step: Step) -> bool {
+        if !self.is_coop() {
+            return false;
+        }
+        let Some(Role::Client {
+            connection,
+            spectator: false,
+            ..
+        }) = &mut self.role
+        else {
+            return false;
+        };
+        connection.send(&Message::Update(Update::Step(step)));
+        true
+    }
+
+    /// Marks the moves up to `len` as sent, used after moves that were sequenced by the host were
+    /// played on the own board in co-op.
+    pub fn skip_moves(&mut self, len: usize) {
+        self.sent_steps = len;
+    }
+
+    /// The name, cursor and color of the other players in co-op, which are shown on the board.
+    pub fn cursors(&self) -> Vec<(&str, (i16, i16), Color32)> {
+        if !self.is_coop() {
+            return Vec::new();
+        }
+        let own_id = self.own_id();
+        self.players
+            .iter()
+            .filter(|p| Some(p.id) != own_id && Some(p.id) != self.watched)
+            .filter_map(|p| {
+                let &cursor = self.cursors.get(&p.id)?;
+                let color = CURSOR_COLORS[p.id as usize % CURSOR_COLORS.len()];
+                Some((p.name.as_str(), cursor, color))
+            })
+            .collect()
+    }
+    /// The fastest player that cleared the board, or the last one that didn't hit a mine.
+    pub fn winner(&self) -> Option<&Player> {
+        let fastest = self
+            .players
+            .iter()
+            .filter_map(|p| match p.progress {
+                Progress::Won { time_ms } => Some((time_ms, p)),
+                _ => None,
+            })
+            .min_by_key(|(time_ms, _)| *time_ms);
+        if let Some((_, player)) = fastest {
+            return Some(player);
+        }
+
+        let mut alive = self
+            .players
+            .iter()
+            .filter(|p| !matches!(p.progress, Progress::Lost { .. }));
+        match (alive.next(), alive.next()) {
+            (Some(player), None) if self.players.len() > 1 => Some(player),
+            _ => None,
+        }
+    }
+
+    /// Shows the lobby, returns whether the host wants to start a race.
+    pub fn ui(&mut self, ui: &mut Ui, tr: &Translations) -> bool {
+        let Some(role) = &self.role else {
+            ui.horizontal(|ui| {
+                ui.label(RichText::new(&*tr.player_name).font(FontId::proportional(16.0)));
+                ui.text_edit_singleline(&mut self.name);
+            });
+            if !cfg!(target_arch = "wasm32") && ui.button(&*tr.host_race).clicked() {
+                self.host(tr);
+            }
+            ui.horizontal(|ui| {
+                ui.label(RichText::new(&*tr.host_address).font(FontId::proportional(16.0)));
+                ui.text_edit_singleline(&mut self.address);
+            });
+            ui.horizontal(|ui| {
+                ui.label(RichText::new(&*tr.room).font(FontId::proportional(16.0)));
+                ui.text_edit_singleline(&mut self.room);
+            });
+            let has_address = !self.address.trim().is_empty();
+            let has_room = !self.room.trim().is_empty();
+            ui.horizontal(|ui| {
+                let button = Button::new(&*tr.host_in_room);
+                if ui.add_enabled(has_address && has_room, button).clicked() {
+                    self.host_in_room(ui.ctx(), tr);
+                }
+                ui.checkbox(&mut self.spectate, &*tr.spectate);
+                let button = Button::new(&*tr.join_race);
+                if ui.add_enabled(has_address, button).clicked() {
+                    self.join(ui.ctx(), tr);
+                }
+            });
+            if let Some(error) = &self.error {
+                ui.label(error);
+            }
+            return false;
+        };
+
+        let hosting = matches!(role, Role::Host { .. });
+        if let Some(Role::Host { clients, relay, .. }) = &self.role {
+            match relay {
+                Some(_) => ui.label(fill(&tr.hosting_in_room, self.room.trim())),
+                None => ui.label(fill(&tr.hosting_on_port, DEFAULT_PORT)),
+            };
+            let spectators = clients.iter().filter(|c| c.joined && c.spectator).count();
+            if spectators > 0 {
+                ui.label(fill(&tr.spectators, spectators));
+            }
+        }
+        let spectating = self.is_spectating();
+        let mut watch = None;
+        Grid::new("players").striped(true).show(ui, |ui| {
+            for player in self.players.iter() {
+                if spectating {
+                    let watched = self.watched == Some(player.id);
+                    let button = ui.selectable_label(watched, "👁").on_hover_text(&*tr.watch);
+                    if button.clicked() && !watched {
+                        watch = Some(player.id);
+                    }
+                }
+                ui.label(RichText::new(&player.name).font(FontId::proportional(16.0)));
+                let progress = match player.progress {
+                    Progress::Waiting => tr.race_waiting.to_string(),
+                    Progress::Playing { revealed, total } => format!("{revealed}/{total}"),
+                    Progress::Won { time_ms } => {
+                        format_duration_precise(std::time::Duration::from_millis(time_ms))
+                    }
+                    Progress::Lost { .. } => tr.race_lost.to_string(),
+                };
+                ui.label(RichText::new(progress).font(FontId::monospace(16.0)));
+                ui.end_row();
+            }
+        });
+        if let Some(player) = watch {
+            self.watch(player);
+        }
+        if self.is_coop() {
+            let won = |p: &Player| matches!(p.progress, Progress::Won { .. });
+            if self.players.iter().any(won) {
+                let text = RichText::new(&*tr.coop_cleared).font(FontId::proportional(16.0));
+                ui.label(text);
+            }
+        } else if let Some(winner) = self.winner() {
+            let text = fill(&tr.race_won_by, &winner.name);
+            ui.label(RichText::new(text).font(FontId::proportional(16.0)));
+        }
+
+        let mut start = false;
+        ui.horizontal(|ui| {
+            if hosting {
+                ui.checkbox(&mut self.together, &*tr.coop)
+                    .on_hover_text(&*tr.coop_hint);
+                let button = Button::new(&*tr.start_race);
+                start = ui.add_enabled(self.players.len() > 1, button).clicked();
+            } else if self.board.is_none() {
+                ui.label(&*tr.waiting_for_host);
+            }
+            if ui.button(&*tr.leave_race).clicked() {
+                self.leave();
+            }
+        });
+        start
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn player(id: u32, progress: Progress) -> Player {
+        Player {
+            id,
+            name: id.to_string(),
+            progress,
+        }
+    }
+
+    #[test]
+    fn winner() {
+        let playing = Progress::Playing {
+            revealed: 10,
+            total: 100,
+        };
+        let lost = Progress::Lost {
+            revealed: 20,
+            total: 100,
+        };
+        let mut multiplayer = Multiplayer {
+            players: vec![player(0, playing), player(1, playing), player(2, playing)],
+            ..Multiplayer::default()
+        };
+        assert_eq!(multiplayer.winner(), None);
+
+        multiplayer.players[0].progress = lost;
+        multiplayer.players[2].progress = lost;
+        assert_eq!(multiplayer.winner().map(|p| p.id), Some(1));
+
+        multiplayer.players[2].progress = Progress::Won { time_ms: 900 };
+        multiplayer.players[1].progress = Progress::Won { time_ms: 1200 };
+        assert_eq!(multiplayer.winner().map(|p| p.id), Some(2));
+    }
+
+    #[test]
+    fn messages_are_single_lines() {
+        let message = Message::Join {
+            name: "a\nb".to_string(),
+            version: VERSION,
+            spectator: false,
+        };
+        let line = ron::to_string(&message).unwrap();
+        assert!(!line.contains('\n'));
+        assert_eq!(ron::from_str::<Message>(&line).unwrap(), message);
+    }
+
+    #[test]
+    fn spectators_catch_up() {
+        let board = BoardSeed {
+            width: 9,
+            height: 9,
+            num_mines: 10,
+            seed: 7,
+            opening: Some((4, 4)),
+        };
+        let step = |x| Step {
+            x,
+            y: 0,
+            kind: crate::replay::StepKind::Reveal,
+            time: 0,
+        };
+        let mut multiplayer = Multiplayer::default();
+        multiplayer.reset_race(board, false);
+        multiplayer.record(1, Update::Step(step(0)));
+        multiplayer.record(1, Update::Step(step(1)));
+        multiplayer.record(2, Update::Step(step(2)));
+        multiplayer.record(1, Update::Cursor(3, 4));
+
+        let messages = multiplayer.catch_up();
+        assert_eq!(messages.len(), 5);
+        assert_eq!(messages[0], Message::Start(board));
+
+        multiplayer.watch(1);
+        let events = std::mem::take(&mut multiplayer.events);
+        let expected = [
+            Event::Start(board),
+            Event::Step(step(0)),
+            Event::Step(step(1)),
+            Event::Cursor(3, 4),
+        ];
+        assert_eq!(events, expected);
+    }
+
+    #[test]
+    fn coop_catch_up_keeps_the_order() {
+        let board = BoardSeed {
+            width: 9,
+            height: 9,
+            num_mines: 10,
+            seed: 7,
+            opening: Some((4, 4)),
+        };
+        let step = |x| Step {
+            x,
+            y: 0,
+            kind: crate::replay::StepKind::Reveal,
+            time: 0,
+        };
+        let mut multiplayer = Multiplayer::default();
+        multiplayer.reset_race(board, true);
+        multiplayer.record(1, Update::Step(step(0)));
+        multiplayer.record(2, Update::Step(step(1)));
+        multiplayer.record(1, Update::Step(step(2)));
+
+        let messages = multiplayer.catch_up();
+        let expected = [
+            Message::Coop(board),
+            Message::Watched {
+                player: 1,
+                update: Update::Step(step(0)),
+            },
+            Message::Watched {
+                player: 2,
+                update: Update::Step(step(1)),
+            },
+            Message::Watched {
+                player: 1,
+                update: Update::Step(step(2)),
+            },
+        ];
+        assert_eq!(messages, expected);
+    }
+
+    #[test]
+    fn coop_cursors() {
+        let board = BoardSeed {
+            width: 9,
+            height: 9,
+            num_mines: 10,
+            seed: 7,
+            opening: Some((4, 4)),
+        };
+        let mut multiplayer = Multiplayer {
+            players: vec![player(1, Progress::Waiting), player(2, Progress::Waiting)],
+            ..Multiplayer::default()
+        };
+        multiplayer.reset_race(board, false);
+        multiplayer.record(1, Update::Cursor(3, 4));
+        assert!(multiplayer.cursors().is_empty());
+
+        multiplayer.reset_race(board, true);
+        multiplayer.record(1, Update::Cursor(3, 4));
+        let cursors = multiplayer.cursors();
+        assert_eq!(cursors, [("1", (3, 4), CURSOR_COLORS[1])]);
+    }
+
+    #[test]
+    fn relayed_clients_close_after_their_messages() {
+        let link = Link::Relayed {
+            id: 4,
+            inbox: VecDeque::from([Message::Progress(Progress::Waiting)]),
+            closed: true,
+        };
+        let mut client = Client::new(1, link);
+        assert_eq!(client.relay_id(), Some(4));
+        assert_eq!(client.try_recv(), Ok(Message::Progress(Progress::Waiting)));
+        assert_eq!(client.try_recv(), Err(mpsc::TryRecvError::Disconnected));
+    }
+}
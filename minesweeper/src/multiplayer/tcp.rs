@@ -0,0 +1,203 @@
+//! Connections over TCP, with one message per line.
+
+use std::io::{self, BufRead, BufReader, ErrorKind, Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc;
+use std::time::Duration;
+
+use egui::Context;
+
+use super::{Message, DEFAULT_PORT, MAX_MESSAGE_LEN};
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// A connection to another player, which is opened, read and written on threads of their own so
+/// the game never waits for the network.
+pub struct Connection {
+    /// The encoded messages, which are written once connected.
+    outgoing: mpsc::Sender<String>,
+    receiver: mpsc::Receiver<Message>,
+}
+
+impl Connection {
+    /// Connects to the address, the default port is used if it has none. Messages can be sent
+    /// right away, the connection counts as closed if it can't be opened.
+    pub fn connect(address: &str, ctx: &Context) -> io::Result<Self> {
+        let address = address.to_string();
+        let (connection, lines, sender) = Self::channels();
+        let ctx = ctx.clone();
+        std::thread::spawn(move || match open(&address) {
+            Ok(stream) => serve(stream, lines, sender, ctx),
+            Err(e) => {
+                log::warn!("failed to connect to {address}: {e}");
+                ctx.request_repaint();
+            }
+        });
+        Ok(connection)
+    }
+
+    fn new(stream: TcpStream, ctx: &Context) -> Self {
+        let (connection, lines, sender) = Self::channels();
+        let ctx = ctx.clone();
+        std::thread::spawn(move || serve(stream, lines, sender, ctx));
+        connection
+    }
+
+    fn channels() -> (Self, mpsc::Receiver<String>, mpsc::Sender<Message>) {
+        let (outgoing, lines) = mpsc::channel();
+        let (sender, receiver) = mpsc::channel();
+        let connection = Self { outgoing, receiver };
+        (connection, lines, sender)
+    }
+
+    pub fn send(&mut self, message: &Message) {
+        let line = match ron::to_string(message) {
+            Ok(line) => line + "\n",
+            Err(e) => {
+                log::warn!("failed to encode message: {e}");
+                return;
+            }
+        };
+        // the thread is gone once the connection closed, which the receiver tells
+        self.outgoing.send(line).ok();
+    }
+
+    /// The next message that arrived, `Disconnected` once the connection was closed.
+    pub fn try_recv(&mut self) -> Result<Message, mpsc::TryRecvError> {
+        self.receiver.try_recv()
+    }
+}
+
+fn open(address: &str) -> io::Result<TcpStream> {
+    let mut addrs = match address.contains(':') {
+        true => address.to_socket_addrs()?,
+        false => (address, DEFAULT_PORT).to_socket_addrs()?,
+    };
+    let Some(addr) = addrs.next() else {
+        return Err(io::Error::new(ErrorKind::NotFound, "unknown address"));
+    };
+    TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT)
+}
+
+/// Reads the messages on a thread of its own, and writes the lines until the connection is
+/// dropped.
+fn serve(
+    stream: TcpStream,
+    lines: mpsc::Receiver<String>,
+    sender: mpsc::Sender<Message>,
+    ctx: Context,
+) {
+    stream.set_nodelay(true).ok();
+    let reader = match stream.try_clone() {
+        Ok(reader) => reader,
+        Err(e) => {
+            log::warn!("failed to read from connection: {e}");
+            ctx.request_repaint();
+            return;
+        }
+    };
+    std::thread::spawn(move || {
+        read(reader, &sender, &ctx);
+        // the sender is dropped, which tells the receiver that the connection was closed
+        ctx.request_repaint();
+    });
+
+    let mut writer = &stream;
+    for line in lines {
+        if let Err(e) = writer.write_all(line.as_bytes()) {
+            log::warn!("failed to send message: {e}");
+            break;
+        }
+    }
+    // the connection was dropped, which ends the reading thread as well
+    stream.shutdown(Shutdown::Both).ok();
+}
+
+fn read(stream: TcpStream, sender: &mpsc::Sender<Message>, ctx: &Context) {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    loop {
+        line.clear();
+        let limit = MAX_MESSAGE_LEN as u64 + 1;
+        match (&mut reader).take(limit).read_line(&mut line) {
+            Ok(0) | Err(_) => return,
+            // the limit was reached, or the connection closed in the middle of a line
+            Ok(_) if !line.ends_with('\n') => {
+                log::warn!("message too long");
+                return;
+            }
+            Ok(_) => (),
+        }
+        match ron::from_str(&line) {
+            Ok(message) => {
+                if sender.send(message).is_err() {
+                    return;
+                }
+            }
+            Err(e) => log::warn!("invalid message: {e}"),
+        }
+        ctx.request_repaint();
+    }
+}
+
+/// Accepts connections of joining players without blocking.
+pub struct Listener(TcpListener);
+
+impl Listener {
+    pub fn bind(port: u16) -> io::Result<Self> {
+        let listener = TcpListener::bind(("0.0.0.0", port))?;
+        listener.set_nonblocking(true)?;
+        Ok(Self(listener))
+    }
+
+    /// The connections that arrived since the last call.
+    pub fn accept(&self, ctx: &Context) -> Vec<Connection> {
+        let mut connections = Vec::new();
+        loop {
+            let stream = match self.0.accept() {
+                Ok((stream, _)) => stream,
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) => {
+                    log::warn!("failed to accept connection: {e}");
+                    break;
+                }
+            };
+            match stream.set_nonblocking(false) {
+                Ok(()) => connections.push(Connection::new(stream, ctx)),
+                Err(e) => log::warn!("failed to accept connection: {e}"),
+            }
+        }
+        connections
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn long_lines_close_the_connection() {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let mut connection = Connection::connect(&address, &Context::default()).unwrap();
+        let (mut peer, _) = listener.accept().unwrap();
+
+        connection.send(&Message::Refused);
+        let mut line = String::new();
+        BufReader::new(&peer).read_line(&mut line).unwrap();
+        assert_eq!(line, "Refused\n");
+
+        peer.write_all(b"Closed\n").unwrap();
+        peer.write_all(&[b' '; MAX_MESSAGE_LEN + 1]).unwrap();
+        let received = (0..100).find_map(|_| {
+            std::thread::sleep(Duration::from_millis(10));
+            connection.try_recv().ok()
+        });
+        assert_eq!(received, Some(Message::Closed));
+        let closed = (0..100).any(|_| {
+            std::thread::sleep(Duration::from_millis(10));
+            connection.try_recv() == Err(mpsc::TryRecvError::Disconnected)
+        });
+        assert!(closed);
+    }
+}
@@ -0,0 +1,136 @@
+//! Connections over WebSocket for the browser, which can't open TCP connections. The other
+//! players are reached through a relay, with one message per text frame.
+
+use std::cell::RefCell;
+use std::io::{self, ErrorKind};
+use std::rc::Rc;
+use std::sync::mpsc;
+
+use egui::Context;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::JsCast;
+use web_sys::{MessageEvent, WebSocket};
+
+use super::{Message, DEFAULT_PORT};
+
+/// A connection to the relay, the messages are received by callbacks of the browser.
+pub struct Connection {
+    socket: WebSocket,
+    receiver: mpsc::Receiver<Message>,
+    /// Messages sent before the connection was open.
+    pending: Vec<String>,
+    /// Kept alive as long as the socket calls them.
+    _on_open: Closure<dyn FnMut(web_sys::Event)>,
+    _on_message: Closure<dyn FnMut(MessageEvent)>,
+    _on_close: Closure<dyn FnMut(web_sys::Event)>,
+}
+
+impl Connection {
+    /// Connects to a `ws://` or `wss://` URL, a plain host name uses `ws://` and the default port.
+    pub fn connect(address: &str, ctx: &Context) -> io::Result<Self> {
+        let url = if address.contains("://") {
+            address.to_string()
+        } else if address.contains(':') {
+            format!("ws://{address}")
+        } else {
+            format!("ws://{address}:{DEFAULT_PORT}")
+        };
+        let socket = WebSocket::new(&url)
+            .map_err(|e| io::Error::new(ErrorKind::InvalidInput, format!("{e:?}")))?;
+
+        // the sender is dropped when the socket is closed, which tells the receiver
+        let (sender, receiver) = mpsc::channel();
+        let sender = Rc::new(RefCell::new(Some(sender)));
+
+        // the pending messages are sent on the next frame
+        let open_ctx = ctx.clone();
+        let on_open = Closure::<dyn FnMut(web_sys::Event)>::new(move |_| {
+            open_ctx.request_repaint();
+        });
+        socket.set_onopen(Some(on_open.as_ref().unchecked_ref()));
+
+        let message_sender = sender.clone();
+        let message_ctx = ctx.clone();
+        let on_message = Closure::<dyn FnMut(MessageEvent)>::new(move |event: MessageEvent| {
+            let Some(text) = event.data().as_string() else { return };
+            match ron::from_str(&text) {
+                Ok(message) => {
+                    if let Some(sender) = &*message_sender.borrow() {
+                        sender.send(message).ok();
+                    }
+                }
+                Err(e) => log::warn!("invalid message: {e}"),
+            }
+            message_ctx.request_repaint();
+        });
+        socket.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+
+        let close_ctx = ctx.clone();
+        let on_close = Closure::<dyn FnMut(web_sys::Event)>::new(move |_| {
+            sender.borrow_mut().take();
+            close_ctx.request_repaint();
+        });
+        socket.set_onclose(Some(on_close.as_ref().unchecked_ref()));
+        socket.set_onerror(Some(on_close.as_ref().unchecked_ref()));
+
+        Ok(Self {
+            socket,
+            receiver,
+            pending: Vec::new(),
+            _on_open: on_open,
+            _on_message: on_message,
+            _on_close: on_close,
+        })
+    }
+
+    pub fn send(&mut self, message: &Message) {
+        match ron::to_string(message) {
+            Ok(text) => self.pending.push(text),
+            Err(e) => log::warn!("failed to encode message: {e}"),
+        }
+        self.flush();
+    }
+
+    fn flush(&mut self) {
+        if self.socket.ready_state() != WebSocket::OPEN {
+            return;
+        }
+        for text in self.pending.drain(..) {
+            if let Err(e) = self.socket.send_with_str(&text) {
+                log::warn!("failed to send message: {e:?}");
+            }
+        }
+    }
+
+    /// The next message that arrived, `Disconnected` once the connection was closed.
+    pub fn try_recv(&mut self) -> Result<Message, mpsc::TryRecvError> {
+        self.flush();
+        self.receiver.try_recv()
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        self.socket.set_onopen(None);
+        self.socket.set_onmessage(None);
+        self.socket.set_onclose(None);
+        self.socket.set_onerror(None);
+        self.socket.close().ok();
+    }
+}
+
+/// The browser can't accept connections, players in it host through a relay.
+pub struct Listener;
+
+impl Listener {
+    pub fn bind(_port: u16) -> io::Result<Self> {
+        Err(io::Error::new(
+            ErrorKind::Unsupported,
+            "the browser can't host races",
+        ))
+    }
+
+    pub fn accept(&self, _ctx: &Context) -> Vec<Connection> {
+        Vec::new()
+    }
+}
@@ -0,0 +1,167 @@
+//! Submits daily challenge times to a leaderboard server and fetches the global top list.
+//!
+//! The server is expected to accept a [`Submission`] as JSON with `POST {endpoint}/daily/{day}`
+//! and to answer `GET {endpoint}/daily/{day}` with a JSON list of [`TopEntry`], fastest first.
+
+use std::sync::{Arc, Mutex};
+
+use egui::{FontId, Grid, RichText, Ui};
+use serde_derive::{Deserialize, Serialize};
+
+use crate::format_duration_precise;
+use crate::replay::Step;
+use crate::settings::OnlineSettings;
+use crate::translations::{fill, Translations};
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Submission {
+    pub name: String,
+    pub board: String,
+    pub time_ms: u64,
+    /// Identifies the recorded moves, so the server can ask for the replay of suspicious times.
+    pub replay_hash: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct TopEntry {
+    pub name: String,
+    pub time_ms: u64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+enum TopList {
+    #[default]
+    Loading,
+    Loaded(Vec<TopEntry>),
+    Failed(String),
+}
+
+/// The top list of the daily challenge, which is fetched once per day.
+#[derive(Debug, Default)]
+pub struct Client {
+    day: Option<u64>,
+    top: Arc<Mutex<TopList>>,
+}
+
+/// A FNV-1a hash of the steps, which is the same on every platform and version.
+pub fn replay_hash(steps: &[Step]) -> String {
+    let mut hash = 0xcbf2_9ce4_8422_2325_u64;
+    for step in steps {
+        let kind = [step.kind as u8];
+        let time = step.time.to_le_bytes();
+        let bytes = [step.x.to_le_bytes(), step.y.to_le_bytes()].concat();
+        let bytes = bytes.iter().chain(&kind).chain(&time);
+        for &b in bytes {
+            hash ^= b as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    format!("{hash:016x}")
+}
+
+fn daily_url(settings: &OnlineSettings, day: u64) -> String {
+    format!("{}/daily/{day}", settings.endpoint.trim_end_matches('/'))
+}
+
+pub fn submit(settings: &OnlineSettings, day: u64, submission: &Submission) {
+    let body = match serde_json::to_vec(submission) {
+        Ok(body) => body,
+        Err(e) => {
+            log::warn!("failed to encode submission: {e}");
+            return;
+        }
+    };
+    let mut request = ehttp::Request::post(daily_url(settings, day), body);
+    request
+        .headers
+        .insert("Content-Type".to_string(), "application/json".to_string());
+    ehttp::fetch(request, |res| match res {
+        Ok(res) if res.ok => log::info!("submitted daily challenge time"),
+        Ok(res) => log::warn!("failed to submit time: {} {}", res.status, res.status_text),
+        Err(e) => log::warn!("failed to submit time: {e}"),
+    });
+}
+
+impl Client {
+    fn fetch(&mut self, ctx: &egui::Context, settings: &OnlineSettings, day: u64) {
+        self.day = Some(day);
+        *self.top.lock().unwrap() = TopList::Loading;
+
+        let top = self.top.clone();
+        let ctx = ctx.clone();
+        let request = ehttp::Request::get(daily_url(settings, day));
+        ehttp::fetch(request, move |res| {
+            let list = match res {
+                Ok(res) if res.ok => match serde_json::from_slice(&res.bytes) {
+                    Ok(entries) => TopList::Loaded(entries),
+                    Err(e) => TopList::Failed(e.to_string()),
+                },
+                Ok(res) => TopList::Failed(format!("{} {}", res.status, res.status_text)),
+                Err(e) => TopList::Failed(e),
+            };
+            *top.lock().unwrap() = list;
+            ctx.request_repaint();
+        });
+    }
+
+    /// Shows the global top list of the day, which is fetched the first time it is shown.
+    pub fn ui(&mut self, ui: &mut Ui, tr: &Translations, settings: &OnlineSettings, day: u64) {
+        if self.day != Some(day) {
+            self.fetch(ui.ctx(), settings, day);
+        }
+
+        let top = self.top.lock().unwrap().clone();
+        match top {
+            TopList::Loading => {
+                ui.spinner();
+            }
+            TopList::Failed(e) => {
+                let text = fill(&tr.online_error, e);
+                ui.label(RichText::new(text).font(FontId::proportional(16.0)));
+                if ui.button(&*tr.reload).clicked() {
+                    self.fetch(ui.ctx(), settings, day);
+                }
+            }
+            TopList::Loaded(entries) => {
+                Grid::new("online_top")
+                    .num_columns(3)
+                    .spacing([20.0, 8.0])
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for (i, entry) in entries.iter().take(10).enumerate() {
+                            let time = std::time::Duration::from_millis(entry.time_ms);
+                            let cells = [
+                                format!("{}.", i + 1),
+                                entry.name.clone(),
+                                format_duration_precise(time),
+                            ];
+                            for cell in cells {
+                                ui.label(RichText::new(cell).font(FontId::proportional(16.0)));
+                            }
+                            ui.end_row();
+                        }
+                    });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::replay::StepKind;
+
+    #[test]
+    fn stable_hash() {
+        assert_eq!(replay_hash(&[]), "cbf29ce484222325");
+        let step = Step {
+            x: 1,
+            y: 2,
+            kind: StepKind::Reveal,
+            time: 300,
+        };
+        let hash = replay_hash(&[step]);
+        assert_eq!(hash, replay_hash(&[step]));
+        assert_ne!(hash, replay_hash(&[Step { time: 301, ..step }]));
+    }
+}
@@ -0,0 +1,121 @@
+use egui::{ComboBox, FontId, RichText, Ui};
+use serde_derive::{Deserialize, Serialize};
+
+use crate::storage::APP_KEY;
+use crate::translations::Translations;
+
+/// Key the list of profiles is stored under, separate from the state of any profile.
+pub const PROFILES_KEY: &str = "profiles";
+
+/// Named players sharing the app, each with their own stats, records and settings.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profiles {
+    /// The profiles besides the default one.
+    names: Vec<String>,
+    /// The active profile, `None` is the default one.
+    current: Option<String>,
+}
+
+/// The key the state of the profile is stored under. The default profile uses the key of saves
+/// from before there were profiles, so they keep working.
+pub fn key(profile: Option<&str>) -> String {
+    match profile {
+        None => APP_KEY.to_string(),
+        Some(name) => format!("{APP_KEY}.profile.{name}"),
+    }
+}
+
+impl Profiles {
+    pub fn current(&self) -> Option<&str> {
+        self.current.as_deref()
+    }
+
+    pub fn set_current(&mut self, profile: Option<String>) {
+        self.current = profile;
+    }
+
+    /// The key the state of the active profile is stored under.
+    pub fn key(&self) -> String {
+        key(self.current())
+    }
+
+    /// The keys the states of all profiles are stored under.
+    pub fn keys(&self) -> impl Iterator<Item = String> + '_ {
+        let names = self.names.iter().map(|n| Some(n.as_str()));
+        std::iter::once(None).chain(names).map(key)
+    }
+
+    /// Adds a profile, returns its name unless it's empty or already taken.
+    pub fn add(&mut self, name: &str) -> Option<String> {
+        let name = name.trim();
+        if name.is_empty() || self.names.iter().any(|n| n == name) {
+            return None;
+        }
+        self.names.push(name.to_string());
+        Some(name.to_string())
+    }
+
+    /// Shows the profile switcher, returns the profile that was selected.
+    pub fn ui(
+        &mut self,
+        ui: &mut Ui,
+        tr: &Translations,
+        scale: f32,
+        new_name: &mut String,
+    ) -> Option<Option<String>> {
+        let font = FontId::proportional(20.0 * scale);
+        let name = self.current().unwrap_or(&tr.default_profile);
+        let text = RichText::new(format!("👤 {name}")).font(font.clone());
+
+        let mut selected = None;
+        ComboBox::new("profile", "")
+            .selected_text(text)
+            .show_ui(ui, |ui| {
+                let text = RichText::new(&*tr.default_profile).font(font.clone());
+                if ui.selectable_label(self.current.is_none(), text).clicked() {
+                    selected = Some(None);
+                }
+                for name in self.names.iter() {
+                    let text = RichText::new(name).font(font.clone());
+                    let active = self.current() == Some(name.as_str());
+                    if ui.selectable_label(active, text).clicked() {
+                        selected = Some(Some(name.clone()));
+                    }
+                }
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.text_edit_singleline(new_name);
+                    if ui.button(&*tr.add_profile).clicked() {
+                        if let Some(name) = self.add(new_name) {
+                            new_name.clear();
+                            selected = Some(Some(name));
+                        }
+                    }
+                });
+            })
+            .response
+            .on_hover_text(&*tr.profile);
+        selected.filter(|s| *s != self.current)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn keys() {
+        let mut profiles = Profiles::default();
+        assert_eq!(profiles.key(), APP_KEY);
+
+        assert_eq!(profiles.add(" Alice "), Some("Alice".to_string()));
+        assert_eq!(profiles.add("Alice"), None);
+        assert_eq!(profiles.add("  "), None);
+
+        profiles.set_current(Some("Alice".to_string()));
+        assert_ne!(profiles.key(), APP_KEY);
+        assert_ne!(profiles.key(), PROFILES_KEY);
+    }
+}
@@ -0,0 +1,121 @@
+use std::collections::VecDeque;
+
+use egui::{FontId, RichText, Ui};
+use egui_plot::{Legend, Line, Plot};
+
+use crate::history::{GameResult, History};
+use crate::translations::Translations;
+use crate::Difficulty;
+
+/// Number of won games the rolling average time is taken over.
+const AVERAGE_WINDOW: usize = 5;
+/// Number of games the rolling win rate is taken over.
+const WIN_RATE_WINDOW: usize = 10;
+
+/// Points of the progress charts, the x coordinate being the number of the game.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Series {
+    /// The fastest win so far in seconds.
+    pub best: Vec<[f64; 2]>,
+    /// The rolling average of the last wins in seconds.
+    pub average: Vec<[f64; 2]>,
+    /// The rolling share of won games in percent.
+    pub win_rate: Vec<[f64; 2]>,
+}
+
+pub fn series(history: &History, difficulty: Difficulty) -> Series {
+    let mut series = Series::default();
+    let mut best = f64::INFINITY;
+    let mut times = VecDeque::new();
+    let mut results = VecDeque::new();
+
+    let games = history.entries().filter(|e| e.difficulty == difficulty);
+    for (i, entry) in games.enumerate() {
+        let x = (i + 1) as f64;
+        let won = entry.result == GameResult::Won;
+        if won {
+            let time = entry.metrics.time.as_secs_f64();
+            best = best.min(time);
+            series.best.push([x, best]);
+
+            if times.len() == AVERAGE_WINDOW {
+                times.pop_front();
+            }
+            times.push_back(time);
+            let average = times.iter().sum::<f64>() / times.len() as f64;
+            series.average.push([x, average]);
+        }
+
+        if results.len() == WIN_RATE_WINDOW {
+            results.pop_front();
+        }
+        results.push_back(won);
+        let wins = results.iter().filter(|&&w| w).count();
+        series
+            .win_rate
+            .push([x, 100.0 * wins as f64 / results.len() as f64]);
+    }
+    series
+}
+
+pub fn ui(ui: &mut Ui, tr: &Translations, history: &History, difficulty: &mut Difficulty) {
+    ui.horizontal(|ui| {
+        for d in [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard] {
+            let text = RichText::new(tr.difficulty(d)).font(FontId::proportional(16.0));
+            ui.selectable_value(difficulty, d, text);
+        }
+    });
+    ui.add_space(8.0);
+
+    let series = series(history, *difficulty);
+    Plot::new("progress_times")
+        .width(480.0)
+        .height(220.0)
+        .legend(Legend::default())
+        .x_axis_label(&*tr.games)
+        .y_axis_label(&*tr.time)
+        .include_y(0.0)
+        .allow_scroll(false)
+        .show(ui, |plot_ui| {
+            plot_ui.line(Line::new(series.best).name(&tr.best_time));
+            plot_ui.line(Line::new(series.average).name(&tr.average_time));
+        });
+    Plot::new("progress_win_rate")
+        .width(480.0)
+        .height(140.0)
+        .legend(Legend::default())
+        .x_axis_label(&*tr.games)
+        .include_y(0.0)
+        .include_y(100.0)
+        .allow_scroll(false)
+        .show(ui, |plot_ui| {
+            plot_ui.line(Line::new(series.win_rate).name(&tr.win_rate));
+        });
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::history::test::entry;
+
+    #[test]
+    fn rolling() {
+        let mut history = History::default();
+        let games = [
+            (GameResult::Won, 50),
+            (GameResult::Lost, 0),
+            (GameResult::Won, 60),
+            (GameResult::Won, 40),
+        ];
+        for (date, (result, secs)) in (0..).zip(games) {
+            history.push(entry(date, Difficulty::Easy, result, secs));
+        }
+
+        let easy = series(&history, Difficulty::Easy);
+        assert_eq!(easy.best, [[1.0, 50.0], [3.0, 50.0], [4.0, 40.0]]);
+        assert_eq!(easy.average, [[1.0, 50.0], [3.0, 55.0], [4.0, 50.0]]);
+        let rates = easy.win_rate.iter().map(|p| p[1]).collect::<Vec<_>>();
+        assert_eq!(rates, [100.0, 50.0, 200.0 / 3.0, 75.0]);
+        assert_eq!(series(&history, Difficulty::Hard), Series::default());
+    }
+}
@@ -0,0 +1,376 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use egui::{Button, FontId, Grid, RichText, Ui};
+use serde_derive::{Deserialize, Serialize};
+
+use crate::replay::Step;
+use crate::stats::Metrics;
+use crate::translations::Translations;
+use crate::{format_duration_precise, Difficulty};
+pub use minesweeper_protocol::BoardSeed;
+
+/// Number of records listed in the leaderboard.
+const LEADERBOARD_LEN: usize = 10;
+/// Number of boards whose best game is kept, the ones won longest ago are dropped.
+const MAX_SEED_BESTS: usize = 100;
+
+/// Winning games of every difficulty, with and without guessing, fastest first.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Records {
+    times: [Vec<Record>; 6],
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(from = "RecordSerde")]
+pub struct Record {
+    pub time: Duration,
+    /// When the game was won in seconds since the unix epoch.
+    pub date: Option<u64>,
+    pub board: Option<BoardSeed>,
+    pub steps: Vec<Step>,
+    pub bbbv: u32,
+    pub clicks: u32,
+    pub wasted: u32,
+    /// The fields the solver revealed, which added a time penalty.
+    pub solver_hints: u32,
+}
+
+/// The best game on every board that was won, to compare rematches on the same board.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SeedBests {
+    games: HashMap<String, Record>,
+}
+
+impl SeedBests {
+    pub fn get(&self, board: &BoardSeed) -> Option<&Record> {
+        self.games.get(&board.id())
+    }
+
+    /// Keeps the game if it is the best on the board, and returns the previous best time. Only
+    /// the [`MAX_SEED_BESTS`] boards with the latest wins are kept, since every game stores its
+    /// moves.
+    pub fn insert(&mut self, board: &BoardSeed, record: Record) -> Option<Duration> {
+        let id = board.id();
+        let previous = self.games.get(&id).map(|r| r.time);
+        if previous.is_none_or(|best| record.time < best) {
+            self.games.insert(id, record);
+        }
+        while self.games.len() > MAX_SEED_BESTS {
+            let oldest = self
+                .games
+                .iter()
+                .min_by_key(|(_, r)| r.date)
+                .map(|(id, _)| id.clone());
+            let Some(oldest) = oldest else { break };
+            self.games.remove(&oldest);
+        }
+        previous
+    }
+}
+
+/// Records used to be stored as bare times.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RecordSerde {
+    Time(Duration),
+    Record {
+        time: Duration,
+        #[serde(default)]
+        date: Option<u64>,
+        #[serde(default)]
+        board: Option<BoardSeed>,
+        #[serde(default)]
+        steps: Vec<Step>,
+        #[serde(default)]
+        bbbv: u32,
+        #[serde(default)]
+        clicks: u32,
+        #[serde(default)]
+        wasted: u32,
+        #[serde(default)]
+        solver_hints: u32,
+    },
+}
+
+impl From<RecordSerde> for Record {
+    fn from(r: RecordSerde) -> Self {
+        match r {
+            RecordSerde::Time(time) => Record::new(time),
+            RecordSerde::Record {
+                time,
+                date,
+                board,
+                steps,
+                bbbv,
+                clicks,
+                wasted,
+                solver_hints,
+            } => Self {
+                time,
+                date,
+                board,
+                steps,
+                bbbv,
+                clicks,
+                wasted,
+                solver_hints,
+            },
+        }
+    }
+}
+
+impl Record {
+    pub fn new(time: Duration) -> Self {
+        Self {
+            time,
+            date: None,
+            board: None,
+            steps: Vec::new(),
+            bbbv: 0,
+            clicks: 0,
+            wasted: 0,
+            solver_hints: 0,
+        }
+    }
+
+    /// The metrics of the game, which are zero for records from before they were kept.
+    pub fn metrics(&self) -> Metrics {
+        Metrics {
+            time: self.time,
+            bbbv: self.bbbv,
+            clicks: self.clicks,
+            wasted: self.wasted,
+            solver_hints: self.solver_hints,
+        }
+    }
+}
+
+impl Records {
+    fn idx(difficulty: Difficulty, unambigous: bool) -> usize {
+        difficulty as usize + 3 * unambigous as usize
+    }
+
+    pub fn records(&self, difficulty: Difficulty, unambigous: bool) -> &[Record] {
+        &self.times[Self::idx(difficulty, unambigous)]
+    }
+
+    pub fn best(&self, difficulty: Difficulty, unambigous: bool) -> Option<Duration> {
+        self.records(difficulty, unambigous).first().map(|r| r.time)
+    }
+
+    /// Inserts a winning game and returns its rank, 0 being a new record. Only the games on the
+    /// leaderboard are kept, since the records are stored after every move.
+    pub fn insert(&mut self, difficulty: Difficulty, unambigous: bool, record: Record) -> usize {
+        let records = &mut self.times[Self::idx(difficulty, unambigous)];
+        let rank = records.partition_point(|r| r.time <= record.time);
+        records.insert(rank, record);
+        records.truncate(LEADERBOARD_LEN);
+        rank
+    }
+
+    /// Lists the fastest games of the chosen mode, returns the index of a record and whether to
+    /// watch it, if one of its buttons was clicked.
+    pub fn ui(
+        &self,
+        ui: &mut Ui,
+        tr: &Translations,
+        (difficulty, unambigous): &mut (Difficulty, bool),
+    ) -> Option<(usize, bool)> {
+        ui.horizontal(|ui| {
+            for d in [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard] {
+                let text = RichText::new(tr.difficulty(d)).font(FontId::proportional(16.0));
+                ui.selectable_value(difficulty, d, text);
+            }
+            ui.add_space(12.0);
+            let text = RichText::new(&*tr.unambigous).font(FontId::proportional(16.0));
+            ui.checkbox(unambigous, text);
+        });
+        ui.add_space(8.0);
+
+        let mut selected = None;
+        Grid::new("leaderboard")
+            .num_columns(8)
+            .spacing([20.0, 8.0])
+            .striped(true)
+            .show(ui, |ui| {
+                let headers = [
+                    "",
+                    &*tr.time,
+                    "3BV/s",
+                    &*tr.solver_hints,
+                    &*tr.date,
+                    &*tr.seed,
+                    "",
+                    "",
+                ];
+                for header in headers {
+                    ui.label(
+                        RichText::new(header)
+                            .font(FontId::proportional(16.0))
+                            .strong(),
+                    );
+                }
+                ui.end_row();
+
+                let records = self.records(*difficulty, *unambigous);
+                for (i, record) in records.iter().take(LEADERBOARD_LEN).enumerate() {
+                    let date = record.date.map(format_date);
+                    let seed = record.board.map(|b| format!("{:016x}", b.seed));
+                    // records from before 3BV was kept have none
+                    let bbbv_per_sec = Some(record.metrics())
+                        .filter(|m| m.bbbv > 0)
+                        .and_then(|m| m.bbbv_per_sec());
+                    let cells = [
+                        format!("{}.", i + 1),
+                        format_duration_precise(record.time),
+                        bbbv_per_sec.map_or("-".to_string(), |r| format!("{r:.2}")),
+                        hints_text(record.solver_hints),
+                        date.unwrap_or_else(|| "-".to_string()),
+                        seed.unwrap_or_else(|| "-".to_string()),
+                    ];
+                    for cell in cells {
+                        ui.label(RichText::new(cell).font(FontId::proportional(16.0)));
+                    }
+
+                    let play = Button::new(&*tr.play_board);
+                    if ui.add_enabled(record.board.is_some(), play).clicked() {
+                        selected = Some((i, false));
+                    }
+                    let watch = Button::new(&*tr.watch_replay);
+                    let watchable = record.board.is_some() && !record.steps.is_empty();
+                    if ui.add_enabled(watchable, watch).clicked() {
+                        selected = Some((i, true));
+                    }
+                    ui.end_row();
+                }
+            });
+        selected
+    }
+}
+
+/// The number of solver hints, or a dash for games without any.
+pub fn hints_text(solver_hints: u32) -> String {
+    match solver_hints {
+        0 => "-".to_string(),
+        n => n.to_string(),
+    }
+}
+
+/// Days since the unix epoch, in UTC.
+pub fn today() -> Option<u64> {
+    let now = instant::SystemTime::now().duration_since(instant::SystemTime::UNIX_EPOCH);
+    now.ok().map(|d| d.as_secs() / 86400)
+}
+
+/// Formats seconds since the unix epoch as a `YYYY-MM-DD` date in UTC.
+pub fn format_date(secs: u64) -> String {
+    // days to civil date, see http://howardhinnant.github.io/date_algorithms.html
+    let z = (secs / 86400) as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + (month <= 2) as i64;
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+/// Parses a date starting with `YYYY-MM-DD` to seconds since the unix epoch, a time of day after
+/// it is ignored.
+pub fn parse_date(text: &str) -> Option<u64> {
+    let mut parts = text.get(..10)?.split('-');
+    let year = parts.next()?.parse::<i64>().ok()?;
+    let month = parts
+        .next()?
+        .parse::<i64>()
+        .ok()
+        .filter(|m| (1..=12).contains(m))?;
+    let day = parts
+        .next()?
+        .parse::<i64>()
+        .ok()
+        .filter(|d| (1..=31).contains(d))?;
+
+    // civil date to days, see http://howardhinnant.github.io/date_algorithms.html
+    let year = year - (month <= 2) as i64;
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146097 + doe - 719468;
+    u64::try_from(days).ok().map(|d| d * 86400)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ranks() {
+        let mut records = Records::default();
+        let secs = |s| Record::new(Duration::from_secs(s));
+        assert_eq!(records.insert(Difficulty::Easy, false, secs(30)), 0);
+        assert_eq!(records.insert(Difficulty::Easy, false, secs(40)), 1);
+        assert_eq!(records.insert(Difficulty::Easy, false, secs(20)), 0);
+        assert_eq!(records.insert(Difficulty::Easy, true, secs(50)), 0);
+
+        assert_eq!(
+            records.best(Difficulty::Easy, false),
+            Some(Duration::from_secs(20))
+        );
+        assert_eq!(records.records(Difficulty::Easy, true), [secs(50)]);
+        assert_eq!(records.best(Difficulty::Hard, false), None);
+    }
+
+    #[test]
+    fn seed_bests() {
+        let board = BoardSeed::parse("20x14-35-ff").unwrap();
+        let secs = Duration::from_secs;
+        let record = |s| Record::new(secs(s));
+        let mut bests = SeedBests::default();
+        assert_eq!(bests.insert(&board, record(30)), None);
+        assert_eq!(bests.insert(&board, record(40)), Some(secs(30)));
+        assert_eq!(bests.insert(&board, record(20)), Some(secs(30)));
+        assert_eq!(bests.get(&board), Some(&record(20)));
+    }
+
+    #[test]
+    fn seed_bests_are_capped() {
+        let mut bests = SeedBests::default();
+        for seed in 0..MAX_SEED_BESTS as u64 + 5 {
+            let board = BoardSeed {
+                width: 9,
+                height: 9,
+                num_mines: 10,
+                seed,
+                opening: None,
+            };
+            let record = Record {
+                date: Some(1000 + seed),
+                ..Record::new(Duration::from_secs(10))
+            };
+            bests.insert(&board, record);
+        }
+        assert_eq!(bests.games.len(), MAX_SEED_BESTS);
+        // the boards won first were dropped
+        assert!(bests.games.values().all(|r| r.date >= Some(1005)));
+    }
+
+    #[test]
+    fn dates() {
+        assert_eq!(format_date(0), "1970-01-01");
+        assert_eq!(format_date(951_782_400), "2000-02-29");
+        assert_eq!(format_date(1_700_000_000), "2023-11-14");
+
+        assert_eq!(parse_date("1970-01-01"), Some(0));
+        assert_eq!(parse_date("2000-02-29 12:00"), Some(951_782_400));
+        assert_eq!(parse_date("2023-11-14"), Some(1_699_920_000));
+        assert_eq!(parse_date("2023-13-01"), None);
+    }
+}
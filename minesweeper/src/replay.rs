@@ -0,0 +1,131 @@
+use instant::Instant;
+use std::time::Duration;
+
+pub use minesweeper_protocol::{Step, StepKind};
+
+use crate::snapshot;
+use crate::theme::Theme;
+use crate::{Game, Visibility};
+
+/// Size of the fields in replay animations in pixels.
+const CELL_SIZE: usize = 16;
+/// Bounds of the time a frame is shown in milliseconds, so quick moves can be followed and long
+/// pauses are skipped.
+const MIN_FRAME_DELAY: u32 = 80;
+const MAX_FRAME_DELAY: u32 = 1000;
+/// Time the finished board is shown in milliseconds.
+const LAST_FRAME_DELAY: u16 = 3000;
+
+/// Makes the move of the step, returns the duration if the game was won. A reveal of a field that
+/// was shown in the meantime does nothing, instead of becoming a chord.
+pub fn apply(game: &mut Game, step: Step) -> Option<Duration> {
+    let shown = game.is_in_bounds(step.x, step.y)
+        && game[(step.x, step.y)].visibility() == Visibility::Show;
+    match step.kind {
+        StepKind::Reveal if shown => None,
+        StepKind::Chord if !shown => None,
+        StepKind::Reveal | StepKind::Chord => game.click(step.x, step.y),
+        StepKind::Hint => {
+            game.hint_(step.x, step.y);
+            None
+        }
+    }
+}
+
+/// Recorded steps that are played on the board at the pace they were made.
+pub struct Playback {
+    steps: Vec<Step>,
+    next: usize,
+    start: Instant,
+}
+
+impl Playback {
+    pub fn new(steps: Vec<Step>) -> Self {
+        Self {
+            steps,
+            next: 0,
+            start: Instant::now(),
+        }
+    }
+
+    /// Returns the next step if its time has come.
+    pub fn next_step(&mut self) -> Option<Step> {
+        let step = *self.steps.get(self.next)?;
+        if self.start.elapsed() < Duration::from_millis(step.time as u64) {
+            return None;
+        }
+        self.next += 1;
+        Some(step)
+    }
+
+    /// The time until the next step is due, `None` once all steps were played.
+    pub fn next_delay(&self) -> Option<Duration> {
+        let step = self.steps.get(self.next)?;
+        let due = Duration::from_millis(step.time as u64);
+        Some(due.saturating_sub(self.start.elapsed()))
+    }
+}
+
+/// The best previous run on the same board, which is played along with the current game.
+pub struct Ghost {
+    board: Game,
+    steps: Vec<Step>,
+    next: usize,
+}
+
+impl Ghost {
+    pub fn new(game: &Game, steps: Vec<Step>) -> Self {
+        let mut board = game.clone();
+        board.retry();
+        Self {
+            board,
+            steps,
+            next: 0,
+        }
+    }
+
+    /// Makes the steps of the run until `elapsed` play time.
+    pub fn advance(&mut self, elapsed: Duration) {
+        while let Some(&step) = self.steps.get(self.next) {
+            if Duration::from_millis(step.time as u64) > elapsed {
+                break;
+            }
+            apply(&mut self.board, step);
+            self.next += 1;
+        }
+    }
+
+    /// The play time until the next step is due, `None` once all steps were made.
+    pub fn next_delay(&self, elapsed: Duration) -> Option<Duration> {
+        let step = self.steps.get(self.next)?;
+        Some(Duration::from_millis(step.time as u64).saturating_sub(elapsed))
+    }
+
+    pub fn is_shown(&self, x: i16, y: i16) -> bool {
+        self.board[(x, y)].visibility() == Visibility::Show
+    }
+}
+
+/// Replays the recorded steps of the game from the hidden board, and renders every step into a
+/// frame of an animated PNG.
+pub fn export_apng(game: &Game, theme: &Theme) -> Vec<u8> {
+    let mut delays = game
+        .steps
+        .windows(2)
+        .map(|w| w[1].time.saturating_sub(w[0].time))
+        .map(|d| d.clamp(MIN_FRAME_DELAY, MAX_FRAME_DELAY) as u16)
+        .collect::<Vec<_>>();
+    delays.push(LAST_FRAME_DELAY);
+
+    let mut board = game.clone();
+    board.retry();
+    let first = (
+        snapshot::render(&board, theme, CELL_SIZE),
+        MIN_FRAME_DELAY as u16,
+    );
+    let frames = game.steps.iter().zip(delays).map(move |(&step, delay)| {
+        apply(&mut board, step);
+        (snapshot::render(&board, theme, CELL_SIZE), delay)
+    });
+    snapshot::encode_apng(std::iter::once(first).chain(frames), game.steps.len() + 1)
+}
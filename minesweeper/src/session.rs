@@ -0,0 +1,120 @@
+use std::time::Duration;
+
+use egui::{FontId, Grid, RichText, Ui};
+
+use crate::history::{Entry, GameResult};
+use crate::records::today;
+use crate::translations::Translations;
+use crate::{format_duration_precise, Difficulty};
+
+/// Number of won games the rolling average is taken over.
+const AVERAGE_LEN: usize = 5;
+
+/// The games played since the app was started, which are not persisted, so the stats start over
+/// with every launch.
+#[derive(Debug, Default)]
+pub struct Session {
+    games: Vec<Entry>,
+}
+
+impl Session {
+    pub fn push(&mut self, entry: Entry) {
+        self.games.push(entry);
+    }
+
+    fn games(&self, difficulty: Difficulty) -> impl DoubleEndedIterator<Item = &Entry> {
+        self.games
+            .iter()
+            .filter(move |e| e.difficulty == difficulty)
+    }
+
+    /// The average time of the last won games of the difficulty.
+    fn recent_average(&self, difficulty: Difficulty) -> Option<Duration> {
+        let times = won_times(self.games(difficulty))
+            .rev()
+            .take(AVERAGE_LEN)
+            .collect::<Vec<_>>();
+        (!times.is_empty()).then(|| times.iter().sum::<Duration>() / times.len() as u32)
+    }
+
+    /// The fastest win of the difficulty in the session on the current day, in UTC.
+    fn best_today(&self, difficulty: Difficulty, today: Option<u64>) -> Option<Duration> {
+        let today = today?;
+        let games = self
+            .games(difficulty)
+            .filter(|e| e.date.map(|d| d / 86400) == Some(today));
+        won_times(games).min()
+    }
+
+    pub fn ui(&self, ui: &mut Ui, tr: &Translations, difficulty: Difficulty) {
+        ui.heading(&*tr.session);
+        ui.label(RichText::new(tr.difficulty(difficulty)).font(FontId::proportional(16.0)));
+        ui.add_space(8.0);
+
+        let played = self.games(difficulty).count();
+        let wins = won_times(self.games(difficulty)).count();
+        let format = |time: Option<Duration>| time.map_or("-".to_string(), format_duration_precise);
+        let rows = [
+            (&*tr.played, played.to_string()),
+            (&*tr.won, wins.to_string()),
+            (
+                &*tr.best_today,
+                format(self.best_today(difficulty, today())),
+            ),
+            (
+                &*tr.average_of_last_5,
+                format(self.recent_average(difficulty)),
+            ),
+        ];
+        Grid::new("session")
+            .num_columns(2)
+            .spacing([20.0, 8.0])
+            .show(ui, |ui| {
+                for (label, value) in rows {
+                    ui.label(RichText::new(label).font(FontId::proportional(16.0)));
+                    ui.label(RichText::new(value).font(FontId::proportional(16.0)));
+                    ui.end_row();
+                }
+            });
+    }
+}
+
+fn won_times<'a>(
+    entries: impl DoubleEndedIterator<Item = &'a Entry> + 'a,
+) -> impl DoubleEndedIterator<Item = Duration> + 'a {
+    entries
+        .filter(|e| e.result == GameResult::Won)
+        .map(|e| e.metrics.time)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::history::test::entry;
+
+    #[test]
+    fn averages() {
+        let mut session = Session::default();
+        assert_eq!(session.recent_average(Difficulty::Easy), None);
+        for secs in [100, 10, 20, 30, 40, 50] {
+            session.push(entry(0, Difficulty::Easy, GameResult::Won, secs));
+        }
+        session.push(entry(0, Difficulty::Easy, GameResult::Lost, 1));
+        session.push(entry(0, Difficulty::Hard, GameResult::Won, 1));
+        let average = session.recent_average(Difficulty::Easy);
+        assert_eq!(average, Some(Duration::from_secs(30)));
+        assert_eq!(session.recent_average(Difficulty::Medium), None);
+    }
+
+    #[test]
+    fn best_of_the_day() {
+        let mut session = Session::default();
+        session.push(entry(86400 - 1, Difficulty::Easy, GameResult::Won, 10));
+        session.push(entry(86400, Difficulty::Easy, GameResult::Won, 30));
+        session.push(entry(86400 + 5, Difficulty::Easy, GameResult::Won, 20));
+        session.push(entry(86400 + 9, Difficulty::Easy, GameResult::Lost, 5));
+        session.push(entry(86400 + 9, Difficulty::Hard, GameResult::Won, 5));
+        let best = session.best_today(Difficulty::Easy, Some(1));
+        assert_eq!(best, Some(Duration::from_secs(20)));
+    }
+}
@@ -0,0 +1,406 @@
+use egui::{ComboBox, FontId, RichText, Slider, Ui};
+use serde_derive::{Deserialize, Serialize};
+
+use crate::input::InputMap;
+use crate::theme::{Glyphs, Palette};
+use crate::translations::{Language, Translations};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Ask before abandoning a game that is still in progress.
+    pub confirm_restart: bool,
+    /// Reveal a random opening when a new game is started.
+    pub auto_open: bool,
+    /// Prevent placing more hints than there are mines.
+    pub cap_hints: bool,
+    /// Move the cursor to the opposite edge when moving past the board edge.
+    pub wrap_cursor: bool,
+    pub chord: ChordSettings,
+    /// Moving the mouse hides the keyboard cursor and clicking moves it to the clicked field.
+    pub mouse_moves_cursor: bool,
+    /// Milliseconds a movement key is held before the cursor keeps moving.
+    pub key_repeat_delay: u32,
+    /// Milliseconds between the moves of the cursor while a movement key is held.
+    pub key_repeat_interval: u32,
+    /// Delay between moves of the AI in milliseconds.
+    pub auto_play_interval: u32,
+    /// Number of solver hints per game, or `None` for unlimited hints.
+    pub solver_hint_limit: Option<u32>,
+    /// Seconds added to the play duration for every solver hint.
+    pub solver_hint_penalty: u32,
+    /// Seconds without input after which the timer is paused, or `None` to never pause it.
+    pub idle_timeout: Option<u32>,
+    /// Seconds after which a running game is saved, or `None` to only save it after moves.
+    pub autosave_interval: Option<u32>,
+    pub palette: Palette,
+    pub glyphs: Glyphs,
+    /// Draw mines, flags and numbers as emoji.
+    pub emoji: bool,
+    /// Mark every number with a distinct shape in addition to its color.
+    pub number_shapes: bool,
+    /// Vibrate on placing hints and on explosions, where the device supports it.
+    pub vibration: bool,
+    /// Don't play sounds, toggled from the top bar.
+    pub muted: bool,
+    /// Read the field the keyboard cursor moves to aloud.
+    pub speak_cursor: bool,
+    /// Disable animations, like the revealing mines, the shaking board, the confetti and the
+    /// moving and pulsing cursor.
+    pub reduced_motion: bool,
+    /// Show the placed hints and revealed fields as bars above the board.
+    pub progress_bar: bool,
+    /// Draw a line every few fields, to help counting on large boards.
+    pub guide_lines: bool,
+    /// Write the board id in a corner of the board, so screenshots identify the board.
+    pub show_board_id: bool,
+    /// Show the revealed and total 3BV, the clicks and the efficiency below the top bar.
+    pub bbbv_hud: bool,
+    /// Mark the fields the best run on the same board had revealed at the current play time.
+    pub ghost: bool,
+    /// Collapse the top bar to a thin strip, which expands while hovered.
+    pub collapse_top_bar: bool,
+    /// Collapse the top bar while a game is being played.
+    pub auto_hide_top_bar: bool,
+    /// Scale of the top bar, the minimum field size and the cursor.
+    pub ui_scale: f32,
+    /// Very large cells with thick outlines and numbers whatever the board size, for low vision
+    /// and touch screens. Boards that don't fit are scrolled.
+    pub large_mode: bool,
+    pub language: Language,
+    pub input_map: InputMap,
+    pub online: OnlineSettings,
+}
+
+/// Which gestures chord, the classic clients differ in this.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChordSettings {
+    /// Left click on a revealed number.
+    pub left_click: bool,
+    pub middle_click: bool,
+    /// Pressing both buttons, the chord happens when the first one is released.
+    pub both_buttons: bool,
+    /// Double click on a revealed number.
+    pub double_click: bool,
+    pub unsatisfied: UnsatisfiedChord,
+}
+
+impl Default for ChordSettings {
+    fn default() -> Self {
+        Self {
+            left_click: true,
+            middle_click: true,
+            both_buttons: true,
+            double_click: false,
+            unsatisfied: UnsatisfiedChord::default(),
+        }
+    }
+}
+
+/// What a chord on a number with fewer hints around it than its count does.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UnsatisfiedChord {
+    #[default]
+    Nothing,
+    /// The hidden neighbors are pressed while the chord is held and released without being
+    /// revealed.
+    PressAndCancel,
+}
+
+impl UnsatisfiedChord {
+    pub const ALL: [UnsatisfiedChord; 2] =
+        [UnsatisfiedChord::Nothing, UnsatisfiedChord::PressAndCancel];
+}
+
+impl ChordSettings {
+    fn ui(&mut self, ui: &mut Ui, tr: &Translations) {
+        let text = RichText::new(&*tr.chord_left_click).font(FontId::proportional(16.0));
+        ui.checkbox(&mut self.left_click, text);
+
+        let text = RichText::new(&*tr.chord_middle_click).font(FontId::proportional(16.0));
+        ui.checkbox(&mut self.middle_click, text);
+
+        let text = RichText::new(&*tr.chord_both_buttons).font(FontId::proportional(16.0));
+        ui.checkbox(&mut self.both_buttons, text);
+
+        let text = RichText::new(&*tr.chord_double_click).font(FontId::proportional(16.0));
+        ui.checkbox(&mut self.double_click, text);
+
+        ui.horizontal(|ui| {
+            ComboBox::from_id_source("unsatisfied_chord")
+                .selected_text(
+                    RichText::new(tr.unsatisfied(self.unsatisfied))
+                        .font(FontId::proportional(16.0)),
+                )
+                .show_ui(ui, |ui| {
+                    for unsatisfied in UnsatisfiedChord::ALL {
+                        let text = RichText::new(tr.unsatisfied(unsatisfied))
+                            .font(FontId::proportional(16.0));
+                        ui.selectable_value(&mut self.unsatisfied, unsatisfied, text);
+                    }
+                });
+            let text = RichText::new(&*tr.unsatisfied_chord);
+            ui.label(text.font(FontId::proportional(16.0)));
+        });
+    }
+}
+
+/// Submitting daily challenge times, which is off until the player opts in.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OnlineSettings {
+    pub enabled: bool,
+    /// Base URL of the leaderboard server.
+    pub endpoint: String,
+    /// The name shown next to submitted times.
+    pub name: String,
+}
+
+impl Default for OnlineSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            endpoint: "https://minesweeper-leaderboard.example.org/api".to_string(),
+            name: String::new(),
+        }
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            confirm_restart: true,
+            auto_open: false,
+            cap_hints: false,
+            wrap_cursor: true,
+            chord: ChordSettings::default(),
+            mouse_moves_cursor: true,
+            key_repeat_delay: 250,
+            key_repeat_interval: 50,
+            auto_play_interval: 400,
+            solver_hint_limit: Some(3),
+            solver_hint_penalty: 10,
+            idle_timeout: Some(60),
+            autosave_interval: Some(10),
+            palette: Palette::default(),
+            glyphs: Glyphs::default(),
+            emoji: false,
+            number_shapes: false,
+            vibration: true,
+            muted: false,
+            speak_cursor: false,
+            reduced_motion: false,
+            progress_bar: false,
+            guide_lines: false,
+            show_board_id: false,
+            bbbv_hud: false,
+            ghost: false,
+            collapse_top_bar: false,
+            auto_hide_top_bar: false,
+            ui_scale: 1.0,
+            large_mode: false,
+            language: Language::default(),
+            input_map: InputMap::default(),
+            online: OnlineSettings::default(),
+        }
+    }
+}
+
+impl Settings {
+    pub fn ui(&mut self, ui: &mut Ui, tr: &Translations) {
+        let text = RichText::new(&*tr.confirm_restart).font(FontId::proportional(16.0));
+        ui.checkbox(&mut self.confirm_restart, text);
+
+        let text = RichText::new(&*tr.auto_open).font(FontId::proportional(16.0));
+        ui.checkbox(&mut self.auto_open, text);
+
+        let text = RichText::new(&*tr.cap_hints).font(FontId::proportional(16.0));
+        ui.checkbox(&mut self.cap_hints, text);
+
+        let text = RichText::new(&*tr.wrap_cursor).font(FontId::proportional(16.0));
+        ui.checkbox(&mut self.wrap_cursor, text);
+
+        let text = RichText::new(&*tr.mouse_moves_cursor).font(FontId::proportional(16.0));
+        ui.checkbox(&mut self.mouse_moves_cursor, text);
+
+        let text = RichText::new(&*tr.key_repeat_delay).font(FontId::proportional(16.0));
+        let slider = Slider::new(&mut self.key_repeat_delay, 100..=1000)
+            .suffix(" ms")
+            .text(text);
+        ui.add(slider);
+
+        let text = RichText::new(&*tr.key_repeat_interval).font(FontId::proportional(16.0));
+        let slider = Slider::new(&mut self.key_repeat_interval, 10..=200)
+            .suffix(" ms")
+            .text(text);
+        ui.add(slider);
+
+        let text = RichText::new(&*tr.auto_play_interval).font(FontId::proportional(16.0));
+        let slider = Slider::new(&mut self.auto_play_interval, 50..=2000)
+            .suffix(" ms")
+            .text(text);
+        ui.add(slider);
+
+        ui.horizontal(|ui| {
+            let mut unlimited = self.solver_hint_limit.is_none();
+            let text = RichText::new(&*tr.unlimited_solver_hints).font(FontId::proportional(16.0));
+            if ui.checkbox(&mut unlimited, text).changed() {
+                self.solver_hint_limit = (!unlimited).then_some(3);
+            }
+            if let Some(limit) = &mut self.solver_hint_limit {
+                let text = RichText::new(&*tr.per_game).font(FontId::proportional(16.0));
+                ui.add(Slider::new(limit, 0..=20).text(text));
+            }
+        });
+
+        let text = RichText::new(&*tr.solver_hint_penalty).font(FontId::proportional(16.0));
+        let slider = Slider::new(&mut self.solver_hint_penalty, 0..=60)
+            .suffix(" s")
+            .text(text);
+        ui.add(slider);
+
+        ui.horizontal(|ui| {
+            let mut pause = self.idle_timeout.is_some();
+            let text = RichText::new(&*tr.pause_when_idle).font(FontId::proportional(16.0));
+            if ui.checkbox(&mut pause, text).changed() {
+                self.idle_timeout = pause.then_some(60);
+            }
+            if let Some(timeout) = &mut self.idle_timeout {
+                let text = RichText::new(&*tr.pause_after).font(FontId::proportional(16.0));
+                ui.add(Slider::new(timeout, 10..=300).suffix(" s").text(text));
+            }
+        });
+
+        ui.horizontal(|ui| {
+            let mut autosave = self.autosave_interval.is_some();
+            let text = RichText::new(&*tr.autosave).font(FontId::proportional(16.0));
+            if ui.checkbox(&mut autosave, text).changed() {
+                self.autosave_interval = autosave.then_some(10);
+            }
+            if let Some(interval) = &mut self.autosave_interval {
+                let text = RichText::new(&*tr.autosave_every).font(FontId::proportional(16.0));
+                ui.add(Slider::new(interval, 5..=120).suffix(" s").text(text));
+            }
+        });
+
+        ui.horizontal(|ui| {
+            ComboBox::from_id_source("language")
+                .selected_text(
+                    RichText::new(self.language.to_string()).font(FontId::proportional(16.0)),
+                )
+                .show_ui(ui, |ui| {
+                    for language in Language::ALL {
+                        let text =
+                            RichText::new(language.to_string()).font(FontId::proportional(16.0));
+                        ui.selectable_value(&mut self.language, language, text);
+                    }
+                });
+            ui.label(RichText::new(&*tr.language).font(FontId::proportional(16.0)));
+        });
+
+        ui.horizontal(|ui| {
+            ComboBox::from_id_source("palette")
+                .selected_text(
+                    RichText::new(tr.palette(self.palette)).font(FontId::proportional(16.0)),
+                )
+                .show_ui(ui, |ui| {
+                    for palette in Palette::ALL {
+                        let text =
+                            RichText::new(tr.palette(palette)).font(FontId::proportional(16.0));
+                        ui.selectable_value(&mut self.palette, palette, text);
+                    }
+                });
+            ui.label(RichText::new(&*tr.color_palette).font(FontId::proportional(16.0)));
+        });
+
+        ui.horizontal(|ui| {
+            ComboBox::from_id_source("glyphs")
+                .selected_text(
+                    RichText::new(tr.glyphs(self.glyphs)).font(FontId::proportional(16.0)),
+                )
+                .show_ui(ui, |ui| {
+                    for glyphs in Glyphs::ALL {
+                        let text =
+                            RichText::new(tr.glyphs(glyphs)).font(FontId::proportional(16.0));
+                        ui.selectable_value(&mut self.glyphs, glyphs, text);
+                    }
+                });
+            ui.label(RichText::new(&*tr.number_glyphs).font(FontId::proportional(16.0)));
+        });
+
+        let text = RichText::new(&*tr.draw_emoji).font(FontId::proportional(16.0));
+        ui.checkbox(&mut self.emoji, text);
+
+        let text = RichText::new(&*tr.number_shapes).font(FontId::proportional(16.0));
+        ui.checkbox(&mut self.number_shapes, text);
+
+        let text = RichText::new(&*tr.show_progress_bars).font(FontId::proportional(16.0));
+        ui.checkbox(&mut self.progress_bar, text);
+
+        let text = RichText::new(&*tr.show_guide_lines).font(FontId::proportional(16.0));
+        ui.checkbox(&mut self.guide_lines, text);
+
+        let text = RichText::new(&*tr.show_board_id).font(FontId::proportional(16.0));
+        ui.checkbox(&mut self.show_board_id, text);
+
+        let text = RichText::new(&*tr.show_bbbv).font(FontId::proportional(16.0));
+        ui.checkbox(&mut self.bbbv_hud, text);
+
+        let text = RichText::new(&*tr.race_best_run).font(FontId::proportional(16.0));
+        ui.checkbox(&mut self.ghost, text);
+
+        let text = RichText::new(&*tr.auto_hide_top_bar).font(FontId::proportional(16.0));
+        ui.checkbox(&mut self.auto_hide_top_bar, text);
+
+        let text = RichText::new(&*tr.ui_scale).font(FontId::proportional(16.0));
+        let slider = Slider::new(&mut self.ui_scale, 0.5..=2.0)
+            .step_by(0.1)
+            .text(text);
+        ui.add(slider);
+
+        let text = RichText::new(&*tr.large_cells).font(FontId::proportional(16.0));
+        ui.checkbox(&mut self.large_mode, text);
+
+        let text = RichText::new(&*tr.vibrate).font(FontId::proportional(16.0));
+        ui.checkbox(&mut self.vibration, text);
+
+        let text = RichText::new(&*tr.speak_cursor).font(FontId::proportional(16.0));
+        ui.checkbox(&mut self.speak_cursor, text);
+
+        let text = RichText::new(&*tr.reduce_motion).font(FontId::proportional(16.0));
+        ui.checkbox(&mut self.reduced_motion, text);
+
+        let text = RichText::new(&*tr.chording).font(FontId::proportional(16.0));
+        ui.collapsing(text, |ui| self.chord.ui(ui, tr));
+
+        let text = RichText::new(&*tr.key_bindings).font(FontId::proportional(16.0));
+        ui.collapsing(text, |ui| self.input_map.ui(ui, tr));
+
+        #[cfg(feature = "online")]
+        {
+            let text = RichText::new(&*tr.online_leaderboard).font(FontId::proportional(16.0));
+            ui.collapsing(text, |ui| self.online.ui(ui, tr));
+        }
+    }
+}
+
+#[cfg(feature = "online")]
+impl OnlineSettings {
+    fn ui(&mut self, ui: &mut Ui, tr: &Translations) {
+        ui.label(&*tr.online_explanation);
+        let text = RichText::new(&*tr.submit_daily_times).font(FontId::proportional(16.0));
+        ui.checkbox(&mut self.enabled, text);
+
+        ui.add_enabled_ui(self.enabled, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(RichText::new(&*tr.player_name).font(FontId::proportional(16.0)));
+                ui.text_edit_singleline(&mut self.name);
+            });
+            ui.horizontal(|ui| {
+                ui.label(RichText::new(&*tr.server).font(FontId::proportional(16.0)));
+                ui.text_edit_singleline(&mut self.endpoint);
+            });
+        });
+    }
+}
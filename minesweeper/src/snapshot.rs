@@ -0,0 +1,380 @@
+use egui::{Color32, ColorImage};
+
+use crate::theme::Theme;
+use crate::{FieldState, Game, PlayState, Visibility};
+
+/// Numbers drawn as a 3 by 5 pixel font, scaled to the cell size.
+const DIGITS: [[&str; 5]; 8] = [
+    [".#.", "##.", ".#.", ".#.", "###"],
+    ["##.", "..#", ".#.", "#..", "###"],
+    ["##.", "..#", ".#.", "..#", "##."],
+    ["#.#", "#.#", "###", "..#", "..#"],
+    ["###", "#..", "##.", "..#", "##."],
+    [".##", "#..", "###", "#.#", "###"],
+    ["###", "..#", ".#.", ".#.", ".#."],
+    ["###", "#.#", "###", "#.#", "###"],
+];
+
+/// Renders the board with flat colors into an image with square cells of `cell_size` pixels,
+/// independent of the size of the window.
+pub fn render(game: &Game, theme: &Theme, cell_size: usize) -> ColorImage {
+    let size = [
+        game.width as usize * cell_size,
+        game.height as usize * cell_size,
+    ];
+    let mut canvas = Canvas {
+        image: ColorImage::new(size, theme.background),
+    };
+    let inset = (0.5 * (theme.gap * cell_size as f32 + theme.grid.width)).round() as usize;
+    let inset = inset.min(cell_size / 4);
+
+    for y in 0..game.height {
+        for x in 0..game.width {
+            let field = game[(x, y)];
+            let min = [
+                x as usize * cell_size + inset,
+                y as usize * cell_size + inset,
+            ];
+            let cell = Cell {
+                min,
+                size: cell_size - 2 * inset,
+            };
+
+            let lost = game.loss.is_some_and(|l| (l.x, l.y) == (x, y));
+            let finished = !matches!(game.play_state, PlayState::Init | PlayState::Playing(_));
+            match (field.state(), field.visibility()) {
+                (FieldState::Free(n), Visibility::Show) => {
+                    canvas.fill_rect(cell.min, [cell.size; 2], theme.shown);
+                    canvas.number(cell, n, theme);
+                }
+                (FieldState::Free(n), _) if matches!(game.play_state, PlayState::Won(_)) => {
+                    canvas.fill_rect(cell.min, [cell.size; 2], theme.shown);
+                    canvas.number(cell, n, theme);
+                }
+                (_, Visibility::Hint) => {
+                    canvas.fill_rect(cell.min, [cell.size; 2], theme.hint);
+                    canvas.flag(cell, theme.flag);
+                }
+                (FieldState::Mine, _) if finished => {
+                    let color = if lost { theme.lose } else { theme.shown };
+                    canvas.fill_rect(cell.min, [cell.size; 2], color);
+                    canvas.fill_circle(cell, 0.3, theme.mine);
+                }
+                _ => {
+                    canvas.fill_rect(cell.min, [cell.size; 2], theme.hidden);
+                }
+            }
+        }
+    }
+
+    canvas.image
+}
+
+#[derive(Clone, Copy)]
+struct Cell {
+    min: [usize; 2],
+    size: usize,
+}
+
+struct Canvas {
+    image: ColorImage,
+}
+
+impl Canvas {
+    fn set(&mut self, x: usize, y: usize, color: Color32) {
+        let [width, height] = self.image.size;
+        if x < width && y < height {
+            self.image.pixels[y * width + x] = color;
+        }
+    }
+
+    fn fill_rect(&mut self, min: [usize; 2], size: [usize; 2], color: Color32) {
+        for y in min[1]..min[1] + size[1] {
+            for x in min[0]..min[0] + size[0] {
+                self.set(x, y, color);
+            }
+        }
+    }
+
+    /// Fills a circle in the center of the cell, with a `radius` relative to the cell size.
+    fn fill_circle(&mut self, cell: Cell, radius: f32, color: Color32) {
+        let r = radius * cell.size as f32;
+        let c = 0.5 * cell.size as f32;
+        for y in 0..cell.size {
+            for x in 0..cell.size {
+                let (dx, dy) = (x as f32 + 0.5 - c, y as f32 + 0.5 - c);
+                if dx.hypot(dy) <= r {
+                    self.set(cell.min[0] + x, cell.min[1] + y, color);
+                }
+            }
+        }
+    }
+
+    fn flag(&mut self, cell: Cell, color: Color32) {
+        let s = cell.size as f32;
+        let px = |f: f32| (f * s).round() as usize;
+        let pole = [cell.min[0] + px(0.36), cell.min[1] + px(0.18)];
+        self.fill_rect(pole, [px(0.06).max(1), px(0.62)], color);
+        let base = [cell.min[0] + px(0.24), cell.min[1] + px(0.76)];
+        self.fill_rect(base, [px(0.38), px(0.06).max(1)], color);
+
+        // the cloth is a triangle pointing right
+        let (x0, y0, y1) = (0.38 * s, 0.18 * s, 0.5 * s);
+        let tip = 0.76 * s;
+        for y in px(0.18)..px(0.5) {
+            let t = 1.0 - ((y as f32 + 0.5 - y0) / (y1 - y0) - 0.5).abs() * 2.0;
+            let x1 = x0 + t * (tip - x0);
+            for x in x0 as usize..x1.round() as usize {
+                self.set(cell.min[0] + x, cell.min[1] + y, color);
+            }
+        }
+    }
+
+    fn number(&mut self, cell: Cell, n: u8, theme: &Theme) {
+        if n == 0 {
+            return;
+        }
+        let color = theme.numbers[n as usize - 1];
+        let pixel = (cell.size / 7).max(1);
+        let min = [
+            cell.min[0] + (cell.size - 3 * pixel) / 2,
+            cell.min[1] + (cell.size - 5 * pixel) / 2,
+        ];
+        for (y, row) in DIGITS[n as usize - 1].iter().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                if c == '#' {
+                    let pos = [min[0] + x * pixel, min[1] + y * pixel];
+                    self.fill_rect(pos, [pixel; 2], color);
+                }
+            }
+        }
+    }
+}
+
+/// Encodes the image as an uncompressed PNG.
+pub fn encode_png(image: &ColorImage) -> Vec<u8> {
+    let mut png = png_header(image.size);
+    let raw = raw_rows(image, Region::full(image.size));
+    write_chunk(&mut png, b"IDAT", &zlib_stored(&raw));
+    write_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+/// Encodes the frames as an uncompressed animated PNG, which is played once. Every frame is shown
+/// for the paired number of milliseconds, and only the part that changed since the previous frame
+/// is stored.
+pub fn encode_apng(
+    frames: impl IntoIterator<Item = (ColorImage, u16)>,
+    num_frames: usize,
+) -> Vec<u8> {
+    let mut frames = frames.into_iter();
+    let Some((first, delay)) = frames.next() else { return Vec::new() };
+
+    let mut png = png_header(first.size);
+    let mut control = Vec::with_capacity(8);
+    control.extend_from_slice(&(num_frames as u32).to_be_bytes());
+    // play once
+    control.extend_from_slice(&1u32.to_be_bytes());
+    write_chunk(&mut png, b"acTL", &control);
+
+    let mut sequence = 0;
+    let region = Region::full(first.size);
+    write_frame_control(&mut png, &mut sequence, region, delay);
+    write_chunk(&mut png, b"IDAT", &zlib_stored(&raw_rows(&first, region)));
+
+    let mut prev = first;
+    for (image, delay) in frames {
+        let region = Region::changed(&prev, &image);
+        write_frame_control(&mut png, &mut sequence, region, delay);
+        let mut data = sequence.to_be_bytes().to_vec();
+        data.extend_from_slice(&zlib_stored(&raw_rows(&image, region)));
+        write_chunk(&mut png, b"fdAT", &data);
+        sequence += 1;
+        prev = image;
+    }
+
+    write_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+/// A rectangle of pixels.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Region {
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+}
+
+impl Region {
+    fn full([width, height]: [usize; 2]) -> Self {
+        Self {
+            x: 0,
+            y: 0,
+            width,
+            height,
+        }
+    }
+
+    /// The bounding box of the pixels that differ, or a single pixel if the images are equal,
+    /// because frames can't be empty.
+    fn changed(prev: &ColorImage, next: &ColorImage) -> Self {
+        let width = next.size[0];
+        let mut min = [usize::MAX; 2];
+        let mut max = [0; 2];
+        let changed = prev
+            .pixels
+            .iter()
+            .zip(next.pixels.iter())
+            .map(|(a, b)| a != b);
+        for (i, _) in changed.enumerate().filter(|(_, c)| *c) {
+            let (x, y) = (i % width, i / width);
+            min = [min[0].min(x), min[1].min(y)];
+            max = [max[0].max(x), max[1].max(y)];
+        }
+        if min[0] == usize::MAX {
+            return Self {
+                x: 0,
+                y: 0,
+                width: 1,
+                height: 1,
+            };
+        }
+        Self {
+            x: min[0],
+            y: min[1],
+            width: max[0] - min[0] + 1,
+            height: max[1] - min[1] + 1,
+        }
+    }
+}
+
+/// The signature and the header chunk of an 8 bit RGBA image.
+fn png_header([width, height]: [usize; 2]) -> Vec<u8> {
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&(width as u32).to_be_bytes());
+    header.extend_from_slice(&(height as u32).to_be_bytes());
+    // 8 bit RGBA, no interlacing
+    header.extend_from_slice(&[8, 6, 0, 0, 0]);
+
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+    write_chunk(&mut png, b"IHDR", &header);
+    png
+}
+
+fn raw_rows(image: &ColorImage, region: Region) -> Vec<u8> {
+    let width = image.size[0];
+    let mut raw = Vec::with_capacity((4 * region.width + 1) * region.height);
+    for y in region.y..region.y + region.height {
+        // no filter
+        raw.push(0);
+        let start = y * width + region.x;
+        for pixel in &image.pixels[start..start + region.width] {
+            raw.extend_from_slice(&pixel.to_srgba_unmultiplied());
+        }
+    }
+    raw
+}
+
+fn write_frame_control(png: &mut Vec<u8>, sequence: &mut u32, region: Region, delay: u16) {
+    let mut control = Vec::with_capacity(26);
+    control.extend_from_slice(&sequence.to_be_bytes());
+    for n in [region.width, region.height, region.x, region.y] {
+        control.extend_from_slice(&(n as u32).to_be_bytes());
+    }
+    control.extend_from_slice(&delay.to_be_bytes());
+    control.extend_from_slice(&1000u16.to_be_bytes());
+    // keep the previous frame and overwrite the region
+    control.extend_from_slice(&[0, 0]);
+    write_chunk(png, b"fcTL", &control);
+    *sequence += 1;
+}
+
+fn write_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// Wraps the data in a zlib stream of stored deflate blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x78, 0x01];
+    let mut blocks = data.chunks(u16::MAX as usize).peekable();
+    if blocks.peek().is_none() {
+        out.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none();
+        let len = block.len() as u16;
+        out.push(last as u8);
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&(!len).to_le_bytes());
+        out.extend_from_slice(block);
+    }
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &b in data {
+        crc ^= b as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xedb8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in data {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn checksums() {
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+    }
+
+    #[test]
+    fn png_layout() {
+        let image = ColorImage::new([3, 2], Color32::RED);
+        let png = encode_png(&image);
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        assert_eq!(&png[12..16], b"IHDR");
+        assert_eq!(&png[16..24], &[0, 0, 0, 3, 0, 0, 0, 2]);
+        assert_eq!(&png[png.len() - 8..png.len() - 4], b"IEND");
+    }
+
+    #[test]
+    fn changed_region() {
+        let prev = ColorImage::new([4, 3], Color32::RED);
+        let mut next = prev.clone();
+        assert_eq!(Region::changed(&prev, &next), Region::full([1, 1]));
+
+        next.pixels[4 + 1] = Color32::BLUE;
+        next.pixels[2 * 4 + 2] = Color32::BLUE;
+        let region = Region::changed(&prev, &next);
+        let expected = Region {
+            x: 1,
+            y: 1,
+            width: 2,
+            height: 2,
+        };
+        assert_eq!(region, expected);
+        assert_eq!(raw_rows(&next, region).len(), 2 * (1 + 2 * 4));
+    }
+}
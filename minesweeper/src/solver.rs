@@ -0,0 +1,350 @@
+use crate::stackvec::StackVec;
+use crate::{FieldState, Game, Visibility};
+
+/// Upper bound of search steps per connected frontier region, so huge frontiers don't stall.
+const MAX_STEPS: u32 = 100_000;
+
+/// What can be deduced from the currently revealed numbers. Hints are ignored, since they
+/// might be wrong.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Analysis {
+    /// Hidden cells that can't be a mine.
+    pub safe: Vec<(i16, i16)>,
+    /// Hidden cells that have to be a mine.
+    pub mines: Vec<(i16, i16)>,
+    /// Hidden cells adjacent to a revealed number, with the share of valid solutions in which
+    /// they are a mine.
+    pub guesses: Vec<((i16, i16), f32)>,
+    /// Hidden cells of regions that were too large to search, nothing is known about them.
+    pub unknown: Vec<(i16, i16)>,
+}
+
+impl Analysis {
+    /// The guess that is least likely to be a mine.
+    pub fn best_guess(&self) -> Option<((i16, i16), f32)> {
+        self.guesses
+            .iter()
+            .copied()
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+    }
+}
+
+struct Constraint {
+    vars: StackVec<8, usize>,
+    mines: u8,
+    placed: u8,
+    open: u8,
+}
+
+struct Solver {
+    constraints: Vec<Constraint>,
+    var_constraints: Vec<Vec<usize>>,
+    assignment: Vec<bool>,
+    solutions: u32,
+    mine_counts: Vec<u32>,
+    steps: u32,
+}
+
+impl Solver {
+    /// Assigns a value to a variable and returns whether all of its constraints can still be
+    /// satisfied.
+    fn assign(&mut self, var: usize, mine: bool) -> bool {
+        self.assignment[var] = mine;
+        let mut valid = true;
+        for &c in self.var_constraints[var].iter() {
+            let constraint = &mut self.constraints[c];
+            constraint.open -= 1;
+            constraint.placed += mine as u8;
+            if constraint.placed > constraint.mines
+                || constraint.placed + constraint.open < constraint.mines
+            {
+                valid = false;
+            }
+        }
+        valid
+    }
+
+    fn unassign(&mut self, var: usize, mine: bool) {
+        for &c in self.var_constraints[var].iter() {
+            let constraint = &mut self.constraints[c];
+            constraint.open += 1;
+            constraint.placed -= mine as u8;
+        }
+    }
+
+    /// Counts all valid solutions of the variables in `order`, starting at index `i`. Returns
+    /// false if the search was aborted.
+    fn search(&mut self, order: &[usize], i: usize) -> bool {
+        self.steps += 1;
+        if self.steps > MAX_STEPS {
+            return false;
+        }
+
+        let Some(&var) = order.get(i) else {
+            self.solutions += 1;
+            for &v in order.iter() {
+                self.mine_counts[v] += self.assignment[v] as u32;
+            }
+            return true;
+        };
+
+        for mine in [false, true] {
+            let valid = self.assign(var, mine);
+            let completed = !valid || self.search(order, i + 1);
+            self.unassign(var, mine);
+            if !completed {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+impl Game {
+    pub fn analyze(&self) -> Analysis {
+        let mut vars: Vec<(i16, i16)> = Vec::new();
+        let mut var_indices = vec![None; self.fields.len()];
+        let mut constraints = Vec::new();
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let field = self[(x, y)];
+                let FieldState::Free(neighbors) = field.state() else { continue };
+                if field.visibility() != Visibility::Show {
+                    continue;
+                }
+
+                let mut constraint_vars = StackVec::new();
+                for (nx, ny) in self.neighbors(x, y) {
+                    if self[(nx, ny)].visibility() == Visibility::Show {
+                        continue;
+                    }
+                    let idx = self.width as usize * ny as usize + nx as usize;
+                    let var = *var_indices[idx].get_or_insert_with(|| {
+                        vars.push((nx, ny));
+                        vars.len() - 1
+                    });
+                    constraint_vars.push(var);
+                }
+                if !constraint_vars.is_empty() {
+                    constraints.push(Constraint {
+                        open: constraint_vars.len() as u8,
+                        vars: constraint_vars,
+                        mines: neighbors,
+                        placed: 0,
+                    });
+                }
+            }
+        }
+
+        let mut var_constraints = vec![Vec::new(); vars.len()];
+        for (c, constraint) in constraints.iter().enumerate() {
+            for &v in constraint.vars.iter() {
+                var_constraints[v].push(c);
+            }
+        }
+
+        let mut solver = Solver {
+            constraints,
+            var_constraints,
+            assignment: vec![false; vars.len()],
+            solutions: 0,
+            mine_counts: vec![0; vars.len()],
+            steps: 0,
+        };
+
+        let mut analysis = Analysis::default();
+        let mut visited = vec![false; vars.len()];
+        for start in 0..vars.len() {
+            if visited[start] {
+                continue;
+            }
+
+            // collect the connected region in breadth first order, so constraints are
+            // completed early and the search can prune
+            let mut order = vec![start];
+            visited[start] = true;
+            let mut i = 0;
+            while let Some(&var) = order.get(i) {
+                for &c in solver.var_constraints[var].iter() {
+                    for &v in solver.constraints[c].vars.iter() {
+                        if !visited[v] {
+                            visited[v] = true;
+                            order.push(v);
+                        }
+                    }
+                }
+                i += 1;
+            }
+
+            solver.solutions = 0;
+            solver.steps = 0;
+            let completed = solver.search(&order, 0);
+            if !completed {
+                analysis.unknown.extend(order.iter().map(|&v| vars[v]));
+                continue;
+            }
+            if solver.solutions == 0 {
+                continue;
+            }
+
+            for &v in order.iter() {
+                let mines = solver.mine_counts[v];
+                if mines == 0 {
+                    analysis.safe.push(vars[v]);
+                } else if mines == solver.solutions {
+                    analysis.mines.push(vars[v]);
+                } else {
+                    let probability = mines as f32 / solver.solutions as f32;
+                    analysis.guesses.push((vars[v], probability));
+                }
+            }
+        }
+
+        analysis.safe.sort_unstable_by_key(|&(x, y)| (y, x));
+        analysis.mines.sort_unstable_by_key(|&(x, y)| (y, x));
+        analysis.unknown.sort_unstable_by_key(|&(x, y)| (y, x));
+        analysis
+    }
+
+    /// The minimum number of clicks needed to reveal all free fields, known as 3BV.
+    pub fn bbbv(&self) -> u32 {
+        self.bbbv_progress().1
+    }
+
+    /// The part of the 3BV that was already revealed, and the 3BV of the whole board.
+    pub fn bbbv_progress(&self) -> (u32, u32) {
+        let idx = |x: i16, y: i16| self.width as usize * y as usize + x as usize;
+        let shown = |x: i16, y: i16| self[(x, y)].visibility() == Visibility::Show;
+        let mut visited = vec![false; self.fields.len()];
+        let mut solved = 0;
+        let mut total = 0;
+
+        // every opening takes one click, which also reveals its border
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if self[(x, y)].state() != FieldState::Free(0) || visited[idx(x, y)] {
+                    continue;
+                }
+
+                let mut opened = shown(x, y);
+                visited[idx(x, y)] = true;
+                let mut stack = vec![(x, y)];
+                while let Some((x, y)) = stack.pop() {
+                    for (nx, ny) in self.neighbors(x, y) {
+                        if visited[idx(nx, ny)] {
+                            continue;
+                        }
+                        visited[idx(nx, ny)] = true;
+                        if self[(nx, ny)].state() == FieldState::Free(0) {
+                            opened |= shown(nx, ny);
+                            stack.push((nx, ny));
+                        }
+                    }
+                }
+                total += 1;
+                solved += opened as u32;
+            }
+        }
+
+        // every remaining number takes one click
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if let (FieldState::Free(_), false) = (self[(x, y)].state(), visited[idx(x, y)]) {
+                    total += 1;
+                    solved += shown(x, y) as u32;
+                }
+            }
+        }
+
+        (solved, total)
+    }
+
+    fn neighbors(&self, x: i16, y: i16) -> impl Iterator<Item = (i16, i16)> + '_ {
+        (-1..=1)
+            .flat_map(move |dy| (-1..=1).map(move |dx| (x + dx, y + dy)))
+            .filter(move |&(nx, ny)| (nx, ny) != (x, y) && self.is_in_bounds(nx, ny))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gen::test::game;
+
+    #[test]
+    fn deduce_safe_and_mine() {
+        let mut game = game(3, 2);
+        game.place_mine(0, 1);
+        game.set_visibility(0, 0, Visibility::Show);
+        game.set_visibility(1, 0, Visibility::Show);
+        game.set_visibility(2, 0, Visibility::Show);
+
+        let analysis = game.analyze();
+        assert_eq!(analysis.safe, vec![(1, 1), (2, 1)]);
+        assert_eq!(analysis.mines, vec![(0, 1)]);
+        assert!(analysis.guesses.is_empty());
+    }
+
+    #[test]
+    fn forced_guess() {
+        let mut game = game(3, 2);
+        game.place_mine(0, 1);
+        game.set_visibility(1, 0, Visibility::Show);
+
+        let analysis = game.analyze();
+        assert!(analysis.safe.is_empty());
+        assert!(analysis.mines.is_empty());
+        assert_eq!(analysis.guesses.len(), 5);
+        for (_, p) in analysis.guesses.iter() {
+            assert_eq!(*p, 0.2);
+        }
+    }
+
+    #[test]
+    fn aborted_search_is_unknown() {
+        // the revealed row between two hidden ones is one region with too many solutions to
+        // count them all
+        let mut game = game(48, 3);
+        for x in (0..48).step_by(2) {
+            game.place_mine(x, 0);
+        }
+        for x in 0..48 {
+            game.set_visibility(x, 1, Visibility::Show);
+        }
+
+        let analysis = game.analyze();
+        assert!(analysis.safe.is_empty());
+        assert!(analysis.guesses.is_empty());
+        assert_eq!(analysis.unknown.len(), 96);
+    }
+
+    #[test]
+    fn bbbv() {
+        let mut wide = game(5, 3);
+        wide.place_mine(2, 0);
+        wide.place_mine(2, 2);
+        // the middle column splits the board into two openings, (2, 1) needs its own click
+        assert_eq!(wide.bbbv(), 3);
+        wide.set_visibility(0, 0, Visibility::Show);
+        wide.set_visibility(2, 1, Visibility::Show);
+        assert_eq!(wide.bbbv_progress(), (2, 3));
+
+        let mut narrow = game(3, 1);
+        narrow.place_mine(1, 0);
+        assert_eq!(narrow.bbbv(), 2);
+    }
+
+    #[test]
+    fn hints_are_ignored() {
+        let mut game = game(3, 2);
+        game.place_mine(0, 1);
+        game.set_visibility(0, 0, Visibility::Show);
+        game.set_visibility(1, 0, Visibility::Show);
+        game.set_visibility(2, 0, Visibility::Show);
+        game.set_visibility(1, 1, Visibility::Hint);
+
+        let analysis = game.analyze();
+        assert_eq!(analysis.safe, vec![(1, 1), (2, 1)]);
+    }
+}
@@ -0,0 +1,262 @@
+use std::time::Duration;
+
+use egui::{FontId, Grid, RichText, Ui};
+use serde_derive::{Deserialize, Serialize};
+
+use crate::translations::Translations;
+use crate::{format_duration_precise, Difficulty};
+
+/// How a game ended.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Won(Metrics),
+    Lost,
+    /// A new game was started while the game was still being played.
+    Abandoned,
+}
+
+/// The clicks made in a game.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Clicks {
+    /// Reveals of hidden fields.
+    pub left: u32,
+    /// Placing or removing hints.
+    pub right: u32,
+    /// Clicks on shown fields.
+    pub chords: u32,
+    /// Clicks of any kind that changed nothing, like chords without enough hints around.
+    pub wasted: u32,
+}
+
+impl Clicks {
+    pub fn total(&self) -> u32 {
+        self.left + self.right + self.chords
+    }
+}
+
+/// Games used to count all clicks together.
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub(crate) enum ClicksSerde {
+    Total(u32),
+    Clicks(Clicks),
+}
+
+impl From<ClicksSerde> for Clicks {
+    fn from(c: ClicksSerde) -> Self {
+        match c {
+            ClicksSerde::Total(left) => Clicks {
+                left,
+                ..Clicks::default()
+            },
+            ClicksSerde::Clicks(clicks) => clicks,
+        }
+    }
+}
+
+/// The measures competitive players compare won games by.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Metrics {
+    pub time: Duration,
+    /// The minimum number of clicks needed to solve the board.
+    pub bbbv: u32,
+    pub clicks: u32,
+    /// The part of the clicks that changed nothing.
+    pub wasted: u32,
+    /// The fields the solver revealed, which added a time penalty.
+    pub solver_hints: u32,
+}
+
+impl Metrics {
+    pub fn bbbv_per_sec(&self) -> Option<f32> {
+        let secs = self.time.as_secs_f32();
+        (secs > 0.0).then(|| self.bbbv as f32 / secs)
+    }
+
+    /// The 3BV per click that changed the board in percent.
+    pub fn efficiency(&self) -> Option<f32> {
+        let effective = self.clicks.saturating_sub(self.wasted);
+        (effective > 0).then(|| 100.0 * self.bbbv as f32 / effective as f32)
+    }
+
+    /// The index of efficiency, the 3BV per click, wasted clicks included.
+    pub fn ioe(&self) -> Option<f32> {
+        (self.clicks > 0).then(|| self.bbbv as f32 / self.clicks as f32)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DifficultyStats {
+    pub won: u32,
+    pub lost: u32,
+    pub abandoned: u32,
+    /// The sum of all winning times.
+    pub won_time: Duration,
+    /// The sums over won games that were recorded with their 3BV and clicks.
+    pub measured: Metrics,
+}
+
+impl DifficultyStats {
+    pub fn played(&self) -> u32 {
+        self.won + self.lost + self.abandoned
+    }
+
+    /// The share of played games that were won, in percent.
+    pub fn win_rate(&self) -> Option<f32> {
+        let played = self.played();
+        (played > 0).then(|| 100.0 * self.won as f32 / played as f32)
+    }
+
+    pub fn average_time(&self) -> Option<Duration> {
+        (self.won > 0).then(|| self.won_time / self.won)
+    }
+
+    pub fn average_bbbv_per_sec(&self) -> Option<f32> {
+        self.measured.bbbv_per_sec()
+    }
+
+    pub fn average_ioe(&self) -> Option<f32> {
+        self.measured.ioe()
+    }
+}
+
+/// Outcomes of the games of a single player, per difficulty.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Stats {
+    difficulties: [DifficultyStats; 3],
+}
+
+impl Stats {
+    pub fn get(&self, difficulty: Difficulty) -> &DifficultyStats {
+        &self.difficulties[difficulty as usize]
+    }
+
+    pub fn record(&mut self, difficulty: Difficulty, outcome: Outcome) {
+        let stats = &mut self.difficulties[difficulty as usize];
+        match outcome {
+            Outcome::Won(metrics) => {
+                stats.won += 1;
+                stats.won_time += metrics.time;
+                stats.measured.time += metrics.time;
+                stats.measured.bbbv += metrics.bbbv;
+                stats.measured.clicks += metrics.clicks;
+                stats.measured.wasted += metrics.wasted;
+                stats.measured.solver_hints += metrics.solver_hints;
+            }
+            Outcome::Lost => stats.lost += 1,
+            Outcome::Abandoned => stats.abandoned += 1,
+        }
+    }
+
+    pub fn ui(&self, ui: &mut Ui, tr: &Translations) {
+        Grid::new("stats")
+            .num_columns(9)
+            .spacing([20.0, 8.0])
+            .striped(true)
+            .show(ui, |ui| {
+                let headers = [
+                    "",
+                    &*tr.played,
+                    &*tr.won,
+                    &*tr.lost,
+                    &*tr.abandoned,
+                    &*tr.win_rate,
+                    &*tr.average_time,
+                    "3BV/s",
+                    "IOE",
+                ];
+                for header in headers {
+                    ui.label(
+                        RichText::new(header)
+                            .font(FontId::proportional(16.0))
+                            .strong(),
+                    );
+                }
+                ui.end_row();
+
+                for difficulty in [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard] {
+                    let stats = self.get(difficulty);
+                    let win_rate = match stats.win_rate() {
+                        Some(rate) => format!("{rate:.0}%"),
+                        None => "-".to_string(),
+                    };
+                    let average_time = match stats.average_time() {
+                        Some(time) => format_duration_precise(time),
+                        None => "-".to_string(),
+                    };
+                    let bbbv_per_sec = match stats.average_bbbv_per_sec() {
+                        Some(rate) => format!("{rate:.2}"),
+                        None => "-".to_string(),
+                    };
+                    let ioe = match stats.average_ioe() {
+                        Some(ioe) => format!("{ioe:.2}"),
+                        None => "-".to_string(),
+                    };
+                    let cells = [
+                        tr.difficulty(difficulty).to_string(),
+                        stats.played().to_string(),
+                        stats.won.to_string(),
+                        stats.lost.to_string(),
+                        stats.abandoned.to_string(),
+                        win_rate,
+                        average_time,
+                        bbbv_per_sec,
+                        ioe,
+                    ];
+                    for cell in cells {
+                        ui.label(RichText::new(cell).font(FontId::proportional(16.0)));
+                    }
+                    ui.end_row();
+                }
+            });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn averages() {
+        let mut stats = Stats::default();
+        let won = |secs, bbbv, clicks| {
+            Outcome::Won(Metrics {
+                time: Duration::from_secs(secs),
+                bbbv,
+                clicks,
+                wasted: 0,
+                solver_hints: 0,
+            })
+        };
+        stats.record(Difficulty::Medium, won(60, 90, 100));
+        stats.record(Difficulty::Medium, won(90, 60, 100));
+        stats.record(Difficulty::Medium, Outcome::Lost);
+        stats.record(Difficulty::Medium, Outcome::Abandoned);
+
+        let medium = stats.get(Difficulty::Medium);
+        assert_eq!(medium.played(), 4);
+        assert_eq!(medium.win_rate(), Some(50.0));
+        assert_eq!(medium.average_time(), Some(Duration::from_secs(75)));
+        assert_eq!(medium.average_bbbv_per_sec(), Some(1.0));
+        assert_eq!(medium.average_ioe(), Some(0.75));
+        assert_eq!(stats.get(Difficulty::Easy).win_rate(), None);
+    }
+
+    #[test]
+    fn wasted_clicks() {
+        let metrics = Metrics {
+            time: Duration::from_secs(10),
+            bbbv: 30,
+            clicks: 40,
+            wasted: 10,
+            solver_hints: 0,
+        };
+        assert_eq!(metrics.ioe(), Some(0.75));
+        assert_eq!(metrics.efficiency(), Some(100.0));
+        assert_eq!(metrics.bbbv_per_sec(), Some(3.0));
+    }
+}
@@ -0,0 +1,170 @@
+//! Backends the game state is persisted to.
+//!
+//! Values are stored as RON strings, like eframe does, so saves written by either can be read by
+//! the other.
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::collections::HashMap;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::PathBuf;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Key the game state is stored under.
+pub const APP_KEY: &str = eframe::APP_KEY;
+
+/// A store of string blobs, which can be supplied by the embedding app.
+pub trait Storage {
+    fn get_string(&self, key: &str) -> Option<String>;
+
+    fn set_string(&mut self, key: &str, value: String);
+
+    /// Writes pending changes, if the backend buffers them.
+    fn flush(&mut self) {}
+}
+
+impl Storage for dyn eframe::Storage + '_ {
+    fn get_string(&self, key: &str) -> Option<String> {
+        eframe::Storage::get_string(self, key)
+    }
+
+    fn set_string(&mut self, key: &str, value: String) {
+        eframe::Storage::set_string(self, key, value)
+    }
+
+    fn flush(&mut self) {
+        eframe::Storage::flush(self)
+    }
+}
+
+pub fn get_value<T: DeserializeOwned>(storage: &(impl Storage + ?Sized), key: &str) -> Option<T> {
+    let value = storage.get_string(key)?;
+    match ron::from_str(&value) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            log::warn!("failed to read {key}: {e}");
+            None
+        }
+    }
+}
+
+pub fn set_value<T: Serialize>(storage: &mut (impl Storage + ?Sized), key: &str, value: &T) {
+    match ron::to_string(value) {
+        Ok(value) => storage.set_string(key, value),
+        Err(e) => log::warn!("failed to write {key}: {e}"),
+    }
+}
+
+/// Keeps all values in a single RON file, which is written when flushed.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct FileStorage {
+    path: PathBuf,
+    values: HashMap<String, String>,
+    dirty: bool,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl FileStorage {
+    /// Reads the values from `path`, the file is created on the first flush.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let values = match std::fs::read_to_string(&path) {
+            Ok(text) => ron::from_str(&text).unwrap_or_else(|e| {
+                log::warn!("failed to read {}: {e}", path.display());
+                HashMap::new()
+            }),
+            Err(_) => HashMap::new(),
+        };
+        Self {
+            path,
+            values,
+            dirty: false,
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Storage for FileStorage {
+    fn get_string(&self, key: &str) -> Option<String> {
+        self.values.get(key).cloned()
+    }
+
+    fn set_string(&mut self, key: &str, value: String) {
+        if self.values.get(key) != Some(&value) {
+            self.values.insert(key.to_string(), value);
+            self.dirty = true;
+        }
+    }
+
+    fn flush(&mut self) {
+        if !self.dirty {
+            return;
+        }
+        if let Some(dir) = self.path.parent() {
+            _ = std::fs::create_dir_all(dir);
+        }
+        let res = ron::to_string(&self.values)
+            .map_err(|e| e.to_string())
+            .and_then(|text| std::fs::write(&self.path, text).map_err(|e| e.to_string()));
+        match res {
+            Ok(()) => self.dirty = false,
+            Err(e) => log::warn!("failed to write {}: {e}", self.path.display()),
+        }
+    }
+}
+
+/// Writes the changes that weren't flushed yet, like the last moves before the app is closed.
+#[cfg(not(target_arch = "wasm32"))]
+impl Drop for FileStorage {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+/// Stores the values in the `localStorage` of the browser.
+#[cfg(target_arch = "wasm32")]
+#[derive(Default)]
+pub struct LocalStorage;
+
+#[cfg(target_arch = "wasm32")]
+impl LocalStorage {
+    fn storage() -> Option<web_sys::Storage> {
+        web_sys::window()?.local_storage().ok()?
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Storage for LocalStorage {
+    fn get_string(&self, key: &str) -> Option<String> {
+        Self::storage()?.get_item(key).ok()?
+    }
+
+    fn set_string(&mut self, key: &str, value: String) {
+        let Some(storage) = Self::storage() else { return };
+        if let Err(e) = storage.set_item(key, &value) {
+            log::warn!("failed to write {key}: {e:?}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn file_roundtrip() {
+        let path = std::env::temp_dir().join(format!("minesweeper-{}.ron", std::process::id()));
+        let mut storage = FileStorage::new(&path);
+        set_value(&mut storage, "value", &(3u8, "three"));
+        storage.flush();
+
+        let storage = FileStorage::new(&path);
+        _ = std::fs::remove_file(&path);
+        assert_eq!(
+            get_value(&storage, "value"),
+            Some((3u8, "three".to_string()))
+        );
+        assert_eq!(get_value::<u8>(&storage, "missing"), None);
+    }
+}
@@ -0,0 +1,533 @@
+use std::sync::Arc;
+
+use egui::{Color32, FontFamily, Painter, Rect, Stroke, TextureId, Visuals};
+use serde_derive::{Deserialize, Serialize};
+
+/// The built-in color palettes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Palette {
+    #[default]
+    Classic,
+    Ocean,
+    Forest,
+    Sunset,
+    /// Okabe-Ito colors, which stay distinguishable with the common kinds of color blindness.
+    Colorblind,
+    /// Black and white cells with thick lines, for low vision and projectors.
+    HighContrast,
+    /// Gray raised cells like the original Windows game.
+    Retro,
+}
+
+impl Palette {
+    pub const ALL: [Palette; 7] = [
+        Palette::Classic,
+        Palette::Ocean,
+        Palette::Forest,
+        Palette::Sunset,
+        Palette::Colorblind,
+        Palette::HighContrast,
+        Palette::Retro,
+    ];
+}
+
+/// How the numbers of revealed fields are written.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Glyphs {
+    #[default]
+    Digits,
+    Roman,
+    /// Pips like on the faces of a die.
+    Dice,
+    /// Pips on two halves, like on a domino tile.
+    Dominoes,
+}
+
+impl Glyphs {
+    pub const ALL: [Glyphs; 4] = [
+        Glyphs::Digits,
+        Glyphs::Roman,
+        Glyphs::Dice,
+        Glyphs::Dominoes,
+    ];
+}
+
+/// What is drawn around the board.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum Backdrop {
+    /// The background of the panel the board is shown in.
+    #[default]
+    Panel,
+    Solid(Color32),
+    /// A vertical gradient.
+    Gradient {
+        top: Color32,
+        bottom: Color32,
+    },
+}
+
+/// How cells without sprites are drawn.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CellStyle {
+    #[default]
+    Flat,
+    /// Hidden cells are raised by a bevel, shown cells are sunken.
+    Beveled,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Sprite {
+    Hidden,
+    Shown,
+    Flag,
+    Mine,
+}
+
+/// A texture atlas supplied by the embedding app, to skin the cells.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sprites {
+    pub texture: TextureId,
+    /// The uv rects of the sprites inside the texture, in the range `0.0..=1.0`.
+    pub hidden: Rect,
+    pub shown: Rect,
+    pub flag: Rect,
+    pub mine: Rect,
+}
+
+impl Sprites {
+    pub fn uv(&self, sprite: Sprite) -> Rect {
+        match sprite {
+            Sprite::Hidden => self.hidden,
+            Sprite::Shown => self.shown,
+            Sprite::Flag => self.flag,
+            Sprite::Mine => self.mine,
+        }
+    }
+}
+
+/// What a cell shows, as passed to a [`CellPainter`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CellState {
+    Hidden,
+    Flagged,
+    /// A flag on a free field, after the game was lost.
+    WrongFlag,
+    /// A free field with the number of neighboring mines.
+    Revealed(u8),
+    Mine,
+    /// The mine that was hit.
+    Exploded,
+}
+
+/// A cell of the board, as passed to a [`CellPainter`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CellView {
+    pub x: i16,
+    pub y: i16,
+    pub state: CellState,
+    /// The color of the hint, which differs per player in hot-seat games.
+    pub hint_color: Color32,
+}
+
+/// Draws cells instead of the built-in renderer, given the rect of the cell on screen. Input
+/// handling, layout and overlays like the cursor are unaffected.
+#[derive(Clone)]
+pub struct CellPainter(pub Arc<PaintCell>);
+
+pub type PaintCell = dyn Fn(&Painter, Rect, CellView) + Send + Sync;
+
+impl CellPainter {
+    pub fn new(paint: impl Fn(&Painter, Rect, CellView) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(paint))
+    }
+}
+
+impl std::fmt::Debug for CellPainter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("CellPainter")
+    }
+}
+
+impl PartialEq for CellPainter {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// Colors used to draw the board.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Theme {
+    /// Drawn behind and between the cells, the window fill of the host app by default.
+    pub background: Color32,
+    #[serde(default)]
+    pub backdrop: Backdrop,
+    /// Stretched over the area around the board, on top of the backdrop.
+    #[serde(skip)]
+    pub backdrop_texture: Option<TextureId>,
+    pub cursor: Color32,
+    pub hidden: Color32,
+    pub hint: Color32,
+    /// The flag drawn on hinted fields.
+    pub flag: Color32,
+    pub shown: Color32,
+    /// The mine that was hit.
+    pub lose: Color32,
+    pub mine: Color32,
+    /// The cross on hints that were placed on a free field.
+    pub wrong_hint: Color32,
+    /// Outlines of fields that are known to be safe.
+    pub safe: Color32,
+    /// The colors of the numbers 1 to 8.
+    pub numbers: [Color32; 8],
+    /// The lines between cells, which are hidden by a zero width.
+    pub grid: Stroke,
+    /// Width of the outlines drawn on top of cells, like the cursor.
+    pub stroke_width: f32,
+    /// Corner radius of the cells, relative to the cell size.
+    pub rounding: f32,
+    /// Space between cells, relative to the cell size.
+    pub gap: f32,
+    pub bold_numbers: bool,
+    /// The font family of the numbers, only used by glyph sets that are written as text.
+    #[serde(default = "default_number_font")]
+    pub number_font: FontFamily,
+    #[serde(default)]
+    pub glyphs: Glyphs,
+    /// Draw mines, flags and numbers as emoji, where the fonts have the glyphs.
+    #[serde(default)]
+    pub emoji: bool,
+    #[serde(default)]
+    pub cell_style: CellStyle,
+    /// Drawn instead of the flat cell colors, texture ids aren't stable so these aren't stored.
+    #[serde(skip)]
+    pub sprites: Option<Sprites>,
+    /// Replaces the sprites and the built-in cell drawing, closures can't be stored either.
+    #[serde(skip)]
+    pub cell_painter: Option<CellPainter>,
+}
+
+fn default_number_font() -> FontFamily {
+    FontFamily::Monospace
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::builtin(Palette::Classic, &Visuals::dark())
+    }
+}
+
+impl Theme {
+    /// A built-in palette, adapted to the light or dark style of the host app.
+    pub fn builtin(palette: Palette, visuals: &Visuals) -> Self {
+        let dark_mode = visuals.dark_mode;
+        let background = visuals.window_fill;
+        match palette {
+            Palette::Classic => Self {
+                background,
+                backdrop: Backdrop::Panel,
+                backdrop_texture: None,
+                cursor: visuals.selection.stroke.color,
+                hidden: if dark_mode {
+                    Color32::from_gray(0x40)
+                } else {
+                    Color32::from_gray(0xa0)
+                },
+                hint: Color32::from_rgb(0xf0, 0xc0, 0x30),
+                flag: Color32::from_rgb(0xc0, 0x20, 0x20),
+                shown: if dark_mode {
+                    Color32::from_gray(0x80)
+                } else {
+                    Color32::from_gray(0xc0)
+                },
+                lose: Color32::from_rgb(0xd0, 0x60, 0x30),
+                mine: Color32::BLACK,
+                wrong_hint: Color32::RED,
+                safe: Color32::from_rgb(0x30, 0xd0, 0x60),
+                numbers: if dark_mode {
+                    [
+                        Color32::BLUE,
+                        Color32::GREEN,
+                        Color32::RED,
+                        Color32::DARK_BLUE,
+                        Color32::DARK_RED,
+                        Color32::LIGHT_BLUE,
+                        Color32::BLACK,
+                        Color32::GRAY,
+                    ]
+                } else {
+                    // the bright colors are hard to read on the light cells
+                    [
+                        Color32::from_rgb(0x00, 0x00, 0xd0),
+                        Color32::from_rgb(0x00, 0x70, 0x00),
+                        Color32::from_rgb(0xc0, 0x00, 0x00),
+                        Color32::DARK_BLUE,
+                        Color32::DARK_RED,
+                        Color32::from_rgb(0x00, 0x70, 0x80),
+                        Color32::BLACK,
+                        Color32::from_gray(0x50),
+                    ]
+                },
+                grid: Stroke::new(1.0, background),
+                stroke_width: 1.0,
+                rounding: 0.0,
+                gap: 0.0,
+                bold_numbers: false,
+                number_font: FontFamily::Monospace,
+                glyphs: Glyphs::Digits,
+                emoji: false,
+                cell_style: CellStyle::Flat,
+                sprites: None,
+                cell_painter: None,
+            },
+            Palette::Ocean => Self {
+                background,
+                backdrop: Backdrop::Panel,
+                backdrop_texture: None,
+                cursor: if dark_mode {
+                    Color32::from_rgb(0xc0, 0xff, 0xf0)
+                } else {
+                    Color32::from_rgb(0x00, 0x40, 0x50)
+                },
+                hidden: if dark_mode {
+                    Color32::from_rgb(0x20, 0x40, 0x60)
+                } else {
+                    Color32::from_rgb(0x70, 0x98, 0xc0)
+                },
+                hint: Color32::from_rgb(0xff, 0x90, 0x60),
+                flag: Color32::from_rgb(0x10, 0x10, 0x30),
+                shown: if dark_mode {
+                    Color32::from_rgb(0x70, 0x98, 0xb0)
+                } else {
+                    Color32::from_rgb(0xc8, 0xe0, 0xf0)
+                },
+                lose: Color32::from_rgb(0xe0, 0x40, 0x50),
+                mine: Color32::from_rgb(0x10, 0x10, 0x30),
+                wrong_hint: Color32::from_rgb(0xd0, 0x00, 0x30),
+                safe: Color32::from_rgb(0x30, 0xd0, 0x60),
+                numbers: [
+                    Color32::from_rgb(0x10, 0x30, 0xa0),
+                    Color32::from_rgb(0x00, 0x70, 0x70),
+                    Color32::from_rgb(0xa0, 0x10, 0x50),
+                    Color32::from_rgb(0x20, 0x10, 0x70),
+                    Color32::from_rgb(0x70, 0x10, 0x20),
+                    Color32::from_rgb(0x00, 0x60, 0xa0),
+                    Color32::from_rgb(0x10, 0x10, 0x30),
+                    Color32::from_rgb(0x40, 0x50, 0x60),
+                ],
+                grid: Stroke::new(1.0, background),
+                stroke_width: 1.0,
+                rounding: 0.0,
+                gap: 0.0,
+                bold_numbers: false,
+                number_font: FontFamily::Monospace,
+                glyphs: Glyphs::Digits,
+                emoji: false,
+                cell_style: CellStyle::Flat,
+                sprites: None,
+                cell_painter: None,
+            },
+            Palette::Forest => Self {
+                background,
+                backdrop: Backdrop::Panel,
+                backdrop_texture: None,
+                cursor: if dark_mode {
+                    Color32::from_rgb(0xf0, 0xff, 0xc0)
+                } else {
+                    Color32::from_rgb(0x30, 0x40, 0x10)
+                },
+                hidden: if dark_mode {
+                    Color32::from_rgb(0x30, 0x48, 0x28)
+                } else {
+                    Color32::from_rgb(0x80, 0xa0, 0x70)
+                },
+                hint: Color32::from_rgb(0xe0, 0x50, 0x30),
+                flag: Color32::from_rgb(0xf8, 0xf0, 0xe0),
+                shown: if dark_mode {
+                    Color32::from_rgb(0x98, 0x88, 0x68)
+                } else {
+                    Color32::from_rgb(0xd8, 0xcc, 0xb0)
+                },
+                lose: Color32::from_rgb(0xc0, 0x30, 0x20),
+                mine: Color32::from_rgb(0x20, 0x18, 0x10),
+                wrong_hint: Color32::from_rgb(0xc0, 0x00, 0x00),
+                safe: Color32::from_rgb(0x30, 0xd0, 0x60),
+                numbers: [
+                    Color32::from_rgb(0x20, 0x40, 0x90),
+                    Color32::from_rgb(0x20, 0x60, 0x10),
+                    Color32::from_rgb(0x90, 0x20, 0x10),
+                    Color32::from_rgb(0x40, 0x20, 0x60),
+                    Color32::from_rgb(0x60, 0x30, 0x10),
+                    Color32::from_rgb(0x10, 0x60, 0x60),
+                    Color32::from_rgb(0x20, 0x18, 0x10),
+                    Color32::from_rgb(0x50, 0x50, 0x40),
+                ],
+                grid: Stroke::new(1.0, background),
+                stroke_width: 1.0,
+                rounding: 0.0,
+                gap: 0.0,
+                bold_numbers: false,
+                number_font: FontFamily::Monospace,
+                glyphs: Glyphs::Digits,
+                emoji: false,
+                cell_style: CellStyle::Flat,
+                sprites: None,
+                cell_painter: None,
+            },
+            Palette::Sunset => Self {
+                background,
+                backdrop: Backdrop::Panel,
+                backdrop_texture: None,
+                cursor: if dark_mode {
+                    Color32::from_rgb(0xff, 0xe0, 0xc0)
+                } else {
+                    Color32::from_rgb(0x50, 0x10, 0x40)
+                },
+                hidden: if dark_mode {
+                    Color32::from_rgb(0x48, 0x28, 0x48)
+                } else {
+                    Color32::from_rgb(0xb0, 0x80, 0xa8)
+                },
+                hint: Color32::from_rgb(0xff, 0xd0, 0x40),
+                flag: Color32::from_rgb(0xc0, 0x10, 0x40),
+                shown: if dark_mode {
+                    Color32::from_rgb(0xb0, 0x88, 0x80)
+                } else {
+                    Color32::from_rgb(0xf0, 0xd8, 0xc8)
+                },
+                lose: Color32::from_rgb(0xff, 0x50, 0x20),
+                mine: Color32::from_rgb(0x30, 0x08, 0x20),
+                wrong_hint: Color32::from_rgb(0xe0, 0x00, 0x20),
+                safe: Color32::from_rgb(0x30, 0xd0, 0x60),
+                numbers: [
+                    Color32::from_rgb(0x30, 0x20, 0xa0),
+                    Color32::from_rgb(0x10, 0x70, 0x40),
+                    Color32::from_rgb(0xc0, 0x20, 0x10),
+                    Color32::from_rgb(0x50, 0x10, 0x80),
+                    Color32::from_rgb(0x80, 0x20, 0x00),
+                    Color32::from_rgb(0x00, 0x60, 0x80),
+                    Color32::from_rgb(0x30, 0x08, 0x20),
+                    Color32::from_rgb(0x60, 0x40, 0x50),
+                ],
+                grid: Stroke::new(1.0, background),
+                stroke_width: 1.0,
+                rounding: 0.0,
+                gap: 0.0,
+                bold_numbers: false,
+                number_font: FontFamily::Monospace,
+                glyphs: Glyphs::Digits,
+                emoji: false,
+                cell_style: CellStyle::Flat,
+                sprites: None,
+                cell_painter: None,
+            },
+            Palette::Colorblind => Self {
+                background,
+                backdrop: Backdrop::Panel,
+                backdrop_texture: None,
+                cursor: if dark_mode {
+                    Color32::WHITE
+                } else {
+                    Color32::BLACK
+                },
+                hidden: if dark_mode {
+                    Color32::from_gray(0x40)
+                } else {
+                    Color32::from_gray(0x90)
+                },
+                hint: Color32::from_rgb(0xf0, 0xe4, 0x42),
+                flag: Color32::from_rgb(0x00, 0x72, 0xb2),
+                shown: if dark_mode {
+                    Color32::from_gray(0xb0)
+                } else {
+                    Color32::from_gray(0xe0)
+                },
+                lose: Color32::from_rgb(0xd5, 0x5e, 0x00),
+                mine: Color32::BLACK,
+                wrong_hint: Color32::from_rgb(0xd5, 0x5e, 0x00),
+                safe: Color32::from_rgb(0x56, 0xb4, 0xe9),
+                numbers: [
+                    Color32::from_rgb(0x00, 0x72, 0xb2),
+                    Color32::from_rgb(0x00, 0x7a, 0x5a),
+                    Color32::from_rgb(0xb0, 0x40, 0x00),
+                    Color32::from_rgb(0x20, 0x20, 0x60),
+                    Color32::from_rgb(0x80, 0x30, 0x60),
+                    Color32::from_rgb(0x00, 0x60, 0x80),
+                    Color32::BLACK,
+                    Color32::from_gray(0x50),
+                ],
+                grid: Stroke::new(1.0, background),
+                stroke_width: 1.0,
+                rounding: 0.0,
+                gap: 0.0,
+                bold_numbers: false,
+                number_font: FontFamily::Monospace,
+                glyphs: Glyphs::Digits,
+                emoji: false,
+                cell_style: CellStyle::Flat,
+                sprites: None,
+                cell_painter: None,
+            },
+            Palette::HighContrast => Self {
+                background: Color32::from_gray(0x80),
+                backdrop: Backdrop::Panel,
+                backdrop_texture: None,
+                cursor: Color32::from_rgb(0xff, 0x00, 0xff),
+                hidden: Color32::BLACK,
+                hint: Color32::from_rgb(0xff, 0xff, 0x00),
+                flag: Color32::BLACK,
+                shown: Color32::WHITE,
+                lose: Color32::from_rgb(0xff, 0x00, 0x00),
+                mine: Color32::BLACK,
+                wrong_hint: Color32::from_rgb(0xff, 0x00, 0x00),
+                safe: Color32::from_rgb(0x00, 0xff, 0x00),
+                numbers: [Color32::BLACK; 8],
+                grid: Stroke::new(3.0, Color32::from_gray(0x80)),
+                stroke_width: 3.0,
+                rounding: 0.0,
+                gap: 0.0,
+                bold_numbers: true,
+                number_font: FontFamily::Monospace,
+                glyphs: Glyphs::Digits,
+                emoji: false,
+                cell_style: CellStyle::Flat,
+                sprites: None,
+                cell_painter: None,
+            },
+            Palette::Retro => Self {
+                background,
+                backdrop: Backdrop::Panel,
+                backdrop_texture: None,
+                cursor: Color32::from_rgb(0x00, 0x00, 0x80),
+                hidden: Color32::from_gray(0xc0),
+                hint: Color32::from_gray(0xc0),
+                flag: Color32::from_rgb(0xff, 0x00, 0x00),
+                shown: Color32::from_gray(0xc0),
+                lose: Color32::from_rgb(0xff, 0x00, 0x00),
+                mine: Color32::BLACK,
+                wrong_hint: Color32::from_rgb(0xff, 0x00, 0x00),
+                safe: Color32::from_rgb(0x00, 0x80, 0x00),
+                numbers: [
+                    Color32::from_rgb(0x00, 0x00, 0xff),
+                    Color32::from_rgb(0x00, 0x80, 0x00),
+                    Color32::from_rgb(0xff, 0x00, 0x00),
+                    Color32::from_rgb(0x00, 0x00, 0x80),
+                    Color32::from_rgb(0x80, 0x00, 0x00),
+                    Color32::from_rgb(0x00, 0x80, 0x80),
+                    Color32::BLACK,
+                    Color32::from_gray(0x80),
+                ],
+                grid: Stroke::new(1.0, Color32::from_gray(0x80)),
+                stroke_width: 2.0,
+                rounding: 0.0,
+                gap: 0.0,
+                bold_numbers: true,
+                number_font: FontFamily::Monospace,
+                glyphs: Glyphs::Digits,
+                emoji: false,
+                cell_style: CellStyle::Beveled,
+                sprites: None,
+                cell_painter: None,
+            },
+        }
+    }
+}
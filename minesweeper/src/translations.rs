@@ -0,0 +1,948 @@
+use std::borrow::Cow;
+use std::fmt::Display;
+
+use serde_derive::{Deserialize, Serialize};
+
+use crate::input::{Action, Preset};
+use crate::settings::UnsatisfiedChord;
+use crate::theme::{Glyphs, Palette};
+use crate::Difficulty;
+
+/// The built-in translations.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Language {
+    #[default]
+    English,
+    German,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::English, Language::German];
+}
+
+impl Display for Language {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Language::English => write!(f, "English"),
+            Language::German => write!(f, "Deutsch"),
+        }
+    }
+}
+
+/// The texts shown in the game. A `{}` in a text is replaced by a value, like a count.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Translations {
+    pub easy: Cow<'static, str>,
+    pub medium: Cow<'static, str>,
+    pub hard: Cow<'static, str>,
+    pub unambigous: Cow<'static, str>,
+    pub ambigous: Cow<'static, str>,
+    pub two_players: Cow<'static, str>,
+
+    // top bar
+    pub solver_hint: Cow<'static, str>,
+    /// `{}` is the number of the player.
+    pub player_turn: Cow<'static, str>,
+    pub both_survived: Cow<'static, str>,
+    /// `{}` is the number of the player.
+    pub player_lost: Cow<'static, str>,
+    pub light_mode: Cow<'static, str>,
+    pub dark_mode: Cow<'static, str>,
+    pub paused_while_idle: Cow<'static, str>,
+    pub best_time: Cow<'static, str>,
+    pub keep_top_bar_open: Cow<'static, str>,
+    pub collapse_top_bar: Cow<'static, str>,
+    pub toggle_fullscreen: Cow<'static, str>,
+    pub mute: Cow<'static, str>,
+    pub unmute: Cow<'static, str>,
+    pub save_board_image: Cow<'static, str>,
+    pub copy_board_image: Cow<'static, str>,
+    pub copied_board_image: Cow<'static, str>,
+    /// `{}` is the error.
+    pub copy_failed: Cow<'static, str>,
+    /// `{}` is the path of the file.
+    pub saved_file: Cow<'static, str>,
+    /// `{}` is the error.
+    pub save_failed: Cow<'static, str>,
+    pub settings: Cow<'static, str>,
+    pub statistics: Cow<'static, str>,
+    pub leaderboard: Cow<'static, str>,
+    pub history: Cow<'static, str>,
+    pub session: Cow<'static, str>,
+    pub progress: Cow<'static, str>,
+    pub watch_ai: Cow<'static, str>,
+    pub clicks: Cow<'static, str>,
+    pub efficiency: Cow<'static, str>,
+    /// `{}` is the number of boards.
+    pub checking_boards: Cow<'static, str>,
+
+    // dialogs
+    pub abandon_game: Cow<'static, str>,
+    /// `{}` is the difficulty of the new game.
+    pub abandon_for_difficulty: Cow<'static, str>,
+    pub abandon: Cow<'static, str>,
+    pub cancel: Cow<'static, str>,
+    /// `{}` is the play time of the game.
+    pub resume_game: Cow<'static, str>,
+    pub resume: Cow<'static, str>,
+
+    // statistics
+    pub played: Cow<'static, str>,
+    pub won: Cow<'static, str>,
+    pub lost: Cow<'static, str>,
+    pub abandoned: Cow<'static, str>,
+    pub win_rate: Cow<'static, str>,
+    pub average_time: Cow<'static, str>,
+    pub export_csv: Cow<'static, str>,
+    pub export_json: Cow<'static, str>,
+
+    // settings
+    pub export_bundle: Cow<'static, str>,
+    pub import_bundle_hint: Cow<'static, str>,
+    pub bundle_imported: Cow<'static, str>,
+    pub bundle_too_new: Cow<'static, str>,
+    /// `{}` is the error.
+    pub bundle_invalid: Cow<'static, str>,
+    pub restore_bundle: Cow<'static, str>,
+    pub replace: Cow<'static, str>,
+    pub confirm_restart: Cow<'static, str>,
+    pub auto_open: Cow<'static, str>,
+    pub cap_hints: Cow<'static, str>,
+    pub wrap_cursor: Cow<'static, str>,
+    pub mouse_moves_cursor: Cow<'static, str>,
+    pub key_repeat_delay: Cow<'static, str>,
+    pub key_repeat_interval: Cow<'static, str>,
+    pub auto_play_interval: Cow<'static, str>,
+    pub unlimited_solver_hints: Cow<'static, str>,
+    pub per_game: Cow<'static, str>,
+    pub solver_hint_penalty: Cow<'static, str>,
+    pub pause_when_idle: Cow<'static, str>,
+    pub pause_after: Cow<'static, str>,
+    pub autosave: Cow<'static, str>,
+    pub autosave_every: Cow<'static, str>,
+    pub language: Cow<'static, str>,
+    pub color_palette: Cow<'static, str>,
+    pub number_glyphs: Cow<'static, str>,
+    pub draw_emoji: Cow<'static, str>,
+    pub number_shapes: Cow<'static, str>,
+    pub show_progress_bars: Cow<'static, str>,
+    pub show_guide_lines: Cow<'static, str>,
+    pub show_board_id: Cow<'static, str>,
+    pub show_bbbv: Cow<'static, str>,
+    pub race_best_run: Cow<'static, str>,
+    pub auto_hide_top_bar: Cow<'static, str>,
+    pub ui_scale: Cow<'static, str>,
+    pub large_cells: Cow<'static, str>,
+    pub vibrate: Cow<'static, str>,
+    pub speak_cursor: Cow<'static, str>,
+    pub reduce_motion: Cow<'static, str>,
+    pub chording: Cow<'static, str>,
+    pub key_bindings: Cow<'static, str>,
+    pub online_leaderboard: Cow<'static, str>,
+
+    // palettes and glyphs
+    pub palette_classic: Cow<'static, str>,
+    pub palette_ocean: Cow<'static, str>,
+    pub palette_forest: Cow<'static, str>,
+    pub palette_sunset: Cow<'static, str>,
+    pub palette_colorblind: Cow<'static, str>,
+    pub palette_high_contrast: Cow<'static, str>,
+    pub palette_retro: Cow<'static, str>,
+    pub glyphs_digits: Cow<'static, str>,
+    pub glyphs_roman: Cow<'static, str>,
+    pub glyphs_dice: Cow<'static, str>,
+    pub glyphs_dominoes: Cow<'static, str>,
+
+    // chording
+    pub chord_left_click: Cow<'static, str>,
+    pub chord_middle_click: Cow<'static, str>,
+    pub chord_both_buttons: Cow<'static, str>,
+    pub chord_double_click: Cow<'static, str>,
+    pub unsatisfied_chord: Cow<'static, str>,
+    pub unsatisfied_nothing: Cow<'static, str>,
+    pub unsatisfied_press: Cow<'static, str>,
+
+    // key bindings
+    pub move_up: Cow<'static, str>,
+    pub move_right: Cow<'static, str>,
+    pub move_down: Cow<'static, str>,
+    pub move_left: Cow<'static, str>,
+    pub move_up_left: Cow<'static, str>,
+    pub move_up_right: Cow<'static, str>,
+    pub move_down_left: Cow<'static, str>,
+    pub move_down_right: Cow<'static, str>,
+    pub jump_left: Cow<'static, str>,
+    pub jump_right: Cow<'static, str>,
+    pub jump_top: Cow<'static, str>,
+    pub jump_bottom: Cow<'static, str>,
+    pub jump_center: Cow<'static, str>,
+    pub reveal: Cow<'static, str>,
+    pub hint: Cow<'static, str>,
+    pub chord: Cow<'static, str>,
+    pub easy_difficulty: Cow<'static, str>,
+    pub medium_difficulty: Cow<'static, str>,
+    pub hard_difficulty: Cow<'static, str>,
+    pub remove_binding: Cow<'static, str>,
+    pub press_a_key: Cow<'static, str>,
+    pub reset_key_bindings: Cow<'static, str>,
+    pub load_preset: Cow<'static, str>,
+    pub preset_arrows: Cow<'static, str>,
+    pub preset_numpad: Cow<'static, str>,
+    pub preset_left_hand: Cow<'static, str>,
+    pub preset_azerty: Cow<'static, str>,
+    pub preset_dvorak: Cow<'static, str>,
+    pub preset_arrows_keys: Cow<'static, str>,
+    pub preset_ijkl_keys: Cow<'static, str>,
+    pub preset_numpad_keys: Cow<'static, str>,
+    pub preset_left_hand_keys: Cow<'static, str>,
+    pub preset_azerty_keys: Cow<'static, str>,
+    pub preset_dvorak_keys: Cow<'static, str>,
+
+    // online leaderboard
+    pub online_explanation: Cow<'static, str>,
+    pub submit_daily_times: Cow<'static, str>,
+    pub server: Cow<'static, str>,
+
+    // leaderboard
+    pub time: Cow<'static, str>,
+    pub date: Cow<'static, str>,
+    pub seed: Cow<'static, str>,
+    pub play_board: Cow<'static, str>,
+    pub watch_replay: Cow<'static, str>,
+    pub board_id: Cow<'static, str>,
+    pub daily_challenge: Cow<'static, str>,
+    /// `{}` is the error.
+    pub online_error: Cow<'static, str>,
+    pub reload: Cow<'static, str>,
+    pub import_hint: Cow<'static, str>,
+    /// `{}` is the number of records.
+    pub imported_records: Cow<'static, str>,
+
+    // history
+    pub all: Cow<'static, str>,
+    pub result: Cow<'static, str>,
+
+    // session
+    pub best_today: Cow<'static, str>,
+    pub average_of_last_5: Cow<'static, str>,
+
+    // progress
+    pub games: Cow<'static, str>,
+
+    // profiles
+    pub profile: Cow<'static, str>,
+    pub default_profile: Cow<'static, str>,
+    pub add_profile: Cow<'static, str>,
+
+    // multiplayer
+    pub multiplayer: Cow<'static, str>,
+    pub player_name: Cow<'static, str>,
+    pub host_race: Cow<'static, str>,
+    /// `{}` is the port.
+    pub hosting_on_port: Cow<'static, str>,
+    pub host_address: Cow<'static, str>,
+    pub room: Cow<'static, str>,
+    pub host_in_room: Cow<'static, str>,
+    /// `{}` is the code of the room.
+    pub hosting_in_room: Cow<'static, str>,
+    pub room_taken: Cow<'static, str>,
+    pub room_not_found: Cow<'static, str>,
+    pub join_race: Cow<'static, str>,
+    pub start_race: Cow<'static, str>,
+    pub leave_race: Cow<'static, str>,
+    pub waiting_for_host: Cow<'static, str>,
+    pub spectate: Cow<'static, str>,
+    pub watch: Cow<'static, str>,
+    /// `{}` is the number of spectators.
+    pub spectators: Cow<'static, str>,
+    pub coop: Cow<'static, str>,
+    pub coop_hint: Cow<'static, str>,
+    pub coop_cleared: Cow<'static, str>,
+    pub race_waiting: Cow<'static, str>,
+    pub race_lost: Cow<'static, str>,
+    /// `{}` is the name of the player.
+    pub race_won_by: Cow<'static, str>,
+    /// `{}` is the error.
+    pub connection_failed: Cow<'static, str>,
+    pub connection_lost: Cow<'static, str>,
+
+    // summary
+    pub show_summary: Cow<'static, str>,
+    /// `{}` is the efficiency in percent.
+    pub efficiency_percent: Cow<'static, str>,
+    pub left_clicks: Cow<'static, str>,
+    pub right_clicks: Cow<'static, str>,
+    pub chords: Cow<'static, str>,
+    pub wasted_clicks: Cow<'static, str>,
+    pub new_personal_best: Cow<'static, str>,
+    /// `{}` is the difference to the previous best time.
+    pub faster_than_before: Cow<'static, str>,
+    /// `{}` is the best time on the board.
+    pub best_on_board: Cow<'static, str>,
+    /// `{}` is the difference to the best time on the board.
+    pub beat_best_on_board: Cow<'static, str>,
+    /// `{}` is the number of hints.
+    pub solver_hints_used: Cow<'static, str>,
+    pub solver_hints: Cow<'static, str>,
+    pub lost_to_chord: Cow<'static, str>,
+    pub lost_to_click: Cow<'static, str>,
+    pub forced_guess: Cow<'static, str>,
+    pub verdict_unknown: Cow<'static, str>,
+    /// `{}` is the number of safe fields.
+    pub had_information: Cow<'static, str>,
+    pub new_game: Cow<'static, str>,
+    pub retry_board: Cow<'static, str>,
+    pub view_analysis: Cow<'static, str>,
+    pub save_replay: Cow<'static, str>,
+
+    // screen reader
+    pub board: Cow<'static, str>,
+    /// The first `{}` is the row, the second the column.
+    pub cell_position: Cow<'static, str>,
+    pub cell_hidden: Cow<'static, str>,
+    pub cell_flagged: Cow<'static, str>,
+    pub cell_mine: Cow<'static, str>,
+    /// `{}` is the number of neighboring mines.
+    pub cell_revealed: Cow<'static, str>,
+    /// `{}` is the play time.
+    pub announce_won: Cow<'static, str>,
+    pub announce_lost: Cow<'static, str>,
+    pub announce_opened_one: Cow<'static, str>,
+    /// `{}` is the number of fields.
+    pub announce_opened: Cow<'static, str>,
+    /// `{}` is the number of mines without a hint.
+    pub announce_mine_counter: Cow<'static, str>,
+    pub one_minute: Cow<'static, str>,
+    /// `{}` is the number of minutes.
+    pub minutes: Cow<'static, str>,
+    pub one_second: Cow<'static, str>,
+    /// `{}` is the number of seconds.
+    pub seconds: Cow<'static, str>,
+}
+
+impl Default for Translations {
+    fn default() -> Self {
+        Self::builtin(Language::English)
+    }
+}
+
+impl Translations {
+    pub fn builtin(language: Language) -> Self {
+        match language {
+            Language::English => Self {
+                easy: "Easy".into(),
+                medium: "Medium".into(),
+                hard: "Hard".into(),
+                unambigous: "unambigous".into(),
+                ambigous: "ambigous".into(),
+                two_players: "2 players".into(),
+
+                solver_hint: "Solver hint".into(),
+                player_turn: "P{}'s turn".into(),
+                both_survived: "Both survived".into(),
+                player_lost: "P{} lost".into(),
+                light_mode: "Switch to light mode".into(),
+                dark_mode: "Switch to dark mode".into(),
+                paused_while_idle: "Paused while idle".into(),
+                best_time: "Best time".into(),
+                keep_top_bar_open: "Keep the top bar open".into(),
+                collapse_top_bar: "Collapse the top bar".into(),
+                toggle_fullscreen: "Toggle fullscreen".into(),
+                mute: "Mute sounds".into(),
+                unmute: "Unmute sounds".into(),
+                save_board_image: "Save board image".into(),
+                copy_board_image: "Copy board image".into(),
+                copied_board_image: "Copied the board image".into(),
+                copy_failed: "Could not copy: {}".into(),
+                saved_file: "Saved {}".into(),
+                save_failed: "Could not save: {}".into(),
+                settings: "Settings".into(),
+                statistics: "Statistics".into(),
+                leaderboard: "Leaderboard".into(),
+                history: "History".into(),
+                session: "Session".into(),
+                progress: "Progress".into(),
+                watch_ai: "Watch the AI play".into(),
+                clicks: "Clicks".into(),
+                efficiency: "Efficiency".into(),
+                checking_boards: "Checked {} boards for a guess-free one".into(),
+
+                abandon_game: "Abandon current game?".into(),
+                abandon_for_difficulty: "Abandon current game and start a new {} game?".into(),
+                abandon: "Abandon".into(),
+                cancel: "Cancel".into(),
+                resume_game: "Resume the game at {}?".into(),
+                resume: "Resume".into(),
+
+                played: "Played".into(),
+                won: "Won".into(),
+                lost: "Lost".into(),
+                abandoned: "Abandoned".into(),
+                win_rate: "Win rate".into(),
+                average_time: "Average time".into(),
+                export_csv: "Export CSV".into(),
+                export_json: "Export JSON".into(),
+
+                export_bundle: "Export everything".into(),
+                import_bundle_hint: "Drop an exported file here to import it".into(),
+                bundle_imported: "Imported the settings, stats, records and games".into(),
+                bundle_too_new: "The file was exported by a newer version".into(),
+                bundle_invalid: "The file can't be imported: {}".into(),
+                restore_bundle: "Replace all profiles with the ones of the file?".into(),
+                replace: "Replace".into(),
+                confirm_restart: "Confirm abandoning a running game".into(),
+                auto_open: "Reveal an opening on new games".into(),
+                cap_hints: "Cap hints at the mine count".into(),
+                wrap_cursor: "Wrap cursor around board edges".into(),
+                mouse_moves_cursor: "Mouse moves the keyboard cursor".into(),
+                key_repeat_delay: "Key repeat delay".into(),
+                key_repeat_interval: "Key repeat interval".into(),
+                auto_play_interval: "AI move interval".into(),
+                unlimited_solver_hints: "Unlimited solver hints".into(),
+                per_game: "per game".into(),
+                solver_hint_penalty: "Solver hint penalty".into(),
+                pause_when_idle: "Pause the timer when idle".into(),
+                pause_after: "after".into(),
+                autosave: "Save the running game".into(),
+                autosave_every: "every".into(),
+                language: "Language".into(),
+                color_palette: "Color palette".into(),
+                number_glyphs: "Number glyphs".into(),
+                draw_emoji: "Draw with emoji".into(),
+                number_shapes: "Mark numbers with shapes".into(),
+                show_progress_bars: "Show progress bars".into(),
+                show_guide_lines: "Show guide lines every 5 fields".into(),
+                show_board_id: "Show the board id".into(),
+                show_bbbv: "Show 3BV and efficiency".into(),
+                race_best_run: "Race against your best run on the same board".into(),
+                auto_hide_top_bar: "Hide the top bar during games".into(),
+                ui_scale: "UI scale".into(),
+                large_cells: "Large cells".into(),
+                vibrate: "Vibrate".into(),
+                speak_cursor: "Read the field under the cursor aloud".into(),
+                reduce_motion: "Reduce motion".into(),
+                chording: "Chording".into(),
+                key_bindings: "Key bindings".into(),
+                online_leaderboard: "Online leaderboard".into(),
+
+                palette_classic: "Classic".into(),
+                palette_ocean: "Ocean".into(),
+                palette_forest: "Forest".into(),
+                palette_sunset: "Sunset".into(),
+                palette_colorblind: "Colorblind".into(),
+                palette_high_contrast: "High contrast".into(),
+                palette_retro: "Retro".into(),
+                glyphs_digits: "Digits".into(),
+                glyphs_roman: "Roman numerals".into(),
+                glyphs_dice: "Dice".into(),
+                glyphs_dominoes: "Dominoes".into(),
+
+                chord_left_click: "Left click on a number".into(),
+                chord_middle_click: "Middle click".into(),
+                chord_both_buttons: "Both buttons".into(),
+                chord_double_click: "Double click on a number".into(),
+                unsatisfied_chord: "Chording on an unsatisfied number".into(),
+                unsatisfied_nothing: "Does nothing".into(),
+                unsatisfied_press: "Presses the neighbors".into(),
+
+                move_up: "Move up".into(),
+                move_right: "Move right".into(),
+                move_down: "Move down".into(),
+                move_left: "Move left".into(),
+                move_up_left: "Move up left".into(),
+                move_up_right: "Move up right".into(),
+                move_down_left: "Move down left".into(),
+                move_down_right: "Move down right".into(),
+                jump_left: "Jump to left edge".into(),
+                jump_right: "Jump to right edge".into(),
+                jump_top: "Jump to top edge".into(),
+                jump_bottom: "Jump to bottom edge".into(),
+                jump_center: "Jump to center".into(),
+                reveal: "Reveal".into(),
+                hint: "Hint".into(),
+                chord: "Chord".into(),
+                easy_difficulty: "Easy difficulty".into(),
+                medium_difficulty: "Medium difficulty".into(),
+                hard_difficulty: "Hard difficulty".into(),
+                remove_binding: "Remove binding".into(),
+                press_a_key: "Press a key…".into(),
+                reset_key_bindings: "Reset key bindings".into(),
+                load_preset: "Load preset".into(),
+                preset_arrows: "Arrow keys".into(),
+                preset_numpad: "Numpad".into(),
+                preset_left_hand: "Left hand".into(),
+                preset_azerty: "Left hand (AZERTY)".into(),
+                preset_dvorak: "IJKL (Dvorak)".into(),
+                preset_arrows_keys: "Arrows move, Enter reveals, Insert places hints, Delete chords"
+                    .into(),
+                preset_ijkl_keys:
+                    "IJKL and U, O, N, M move, Space reveals, F places hints, D chords".into(),
+                // egui can't tell the numpad apart from the digits above the letters
+                preset_numpad_keys: "1 to 9 move, 5 reveals, 0 places hints, - chords".into(),
+                preset_left_hand_keys: "WASD move, Q reveals, E places hints, Shift+Q chords"
+                    .into(),
+                preset_azerty_keys: "ZQSD move, A reveals, E places hints, Shift+A chords".into(),
+                preset_dvorak_keys:
+                    "CHTN and G, R, B, M move, Space reveals, U places hints, E chords".into(),
+
+                online_explanation: "When enabled, your name, the board and your time and a hash \
+                    of your moves are sent to the server below after your first win of every \
+                    daily challenge."
+                    .into(),
+                submit_daily_times: "Submit daily challenge times".into(),
+                server: "Server".into(),
+
+                time: "Time".into(),
+                date: "Date".into(),
+                seed: "Seed".into(),
+                play_board: "Play board".into(),
+                watch_replay: "Watch replay".into(),
+                board_id: "Board ID".into(),
+                daily_challenge: "Daily challenge".into(),
+                online_error: "Could not load the online leaderboard: {}".into(),
+                reload: "Reload".into(),
+                import_hint: "Drop stats or replays of Arbiter or Viennasweeper here to import them"
+                    .into(),
+                imported_records: "{} records imported".into(),
+
+                all: "All".into(),
+                result: "Result".into(),
+
+                best_today: "Best today".into(),
+                average_of_last_5: "Average of last 5".into(),
+
+                games: "Games".into(),
+
+                profile: "Profile".into(),
+                default_profile: "Default".into(),
+                add_profile: "Add".into(),
+
+                multiplayer: "Multiplayer".into(),
+                player_name: "Name".into(),
+                host_race: "Host a race".into(),
+                hosting_on_port: "Hosting on port {}".into(),
+                host_address: "Address of the host or relay".into(),
+                room: "Room".into(),
+                host_in_room: "Host in the room".into(),
+                hosting_in_room: "Hosting in room {}".into(),
+                room_taken: "The room is already taken".into(),
+                room_not_found: "There is no such room".into(),
+                join_race: "Join".into(),
+                start_race: "Start race".into(),
+                leave_race: "Leave".into(),
+                waiting_for_host: "Waiting for the host to start".into(),
+                spectate: "Only watch".into(),
+                watch: "Watch".into(),
+                spectators: "{} watching".into(),
+                coop: "Co-op".into(),
+                coop_hint: "Play the board together and see where the others point".into(),
+                coop_cleared: "The board was cleared together".into(),
+                race_waiting: "Waiting".into(),
+                race_lost: "Lost".into(),
+                race_won_by: "{} won the race".into(),
+                connection_failed: "Could not connect: {}".into(),
+                connection_lost: "The connection to the host was lost".into(),
+
+                show_summary: "Show summary".into(),
+                efficiency_percent: "{}% efficiency".into(),
+                left_clicks: "Left".into(),
+                right_clicks: "Right".into(),
+                chords: "Chords".into(),
+                wasted_clicks: "Wasted".into(),
+                new_personal_best: "New personal best!".into(),
+                faster_than_before: "{} faster than before".into(),
+                best_on_board: "Your best on this board: {}".into(),
+                beat_best_on_board: "Beat your best on this board by {}".into(),
+                solver_hints_used: "{} solver hints used".into(),
+                solver_hints: "Solver hints".into(),
+                lost_to_chord: "Lost to a misflag chord".into(),
+                lost_to_click: "Clicked an unflagged mine".into(),
+                forced_guess: "It was a forced guess".into(),
+                verdict_unknown: "Too many possibilities to tell whether it was a forced guess"
+                    .into(),
+                had_information: "You had information! {} safe cells".into(),
+                new_game: "New game".into(),
+                retry_board: "Retry board".into(),
+                view_analysis: "View analysis".into(),
+                save_replay: "Save replay".into(),
+
+                board: "Board".into(),
+                cell_position: "row {}, column {}".into(),
+                cell_hidden: "hidden".into(),
+                cell_flagged: "flagged".into(),
+                cell_mine: "mine".into(),
+                cell_revealed: "revealed, {} adjacent mines".into(),
+                announce_won: "Game won in {}".into(),
+                announce_lost: "Game lost".into(),
+                announce_opened_one: "Opened 1 field".into(),
+                announce_opened: "Opened {} fields".into(),
+                announce_mine_counter: "Mine counter {}".into(),
+                one_minute: "1 minute".into(),
+                minutes: "{} minutes".into(),
+                one_second: "1 second".into(),
+                seconds: "{} seconds".into(),
+            },
+            Language::German => Self {
+                easy: "Leicht".into(),
+                medium: "Mittel".into(),
+                hard: "Schwer".into(),
+                unambigous: "eindeutig".into(),
+                ambigous: "mehrdeutig".into(),
+                two_players: "2 Spieler".into(),
+
+                solver_hint: "Lösungshinweis".into(),
+                player_turn: "S{} ist am Zug".into(),
+                both_survived: "Beide haben überlebt".into(),
+                player_lost: "S{} hat verloren".into(),
+                light_mode: "Zum hellen Modus wechseln".into(),
+                dark_mode: "Zum dunklen Modus wechseln".into(),
+                paused_while_idle: "Pausiert, weil nichts passiert".into(),
+                best_time: "Bestzeit".into(),
+                keep_top_bar_open: "Obere Leiste offen lassen".into(),
+                collapse_top_bar: "Obere Leiste einklappen".into(),
+                toggle_fullscreen: "Vollbild umschalten".into(),
+                mute: "Töne ausschalten".into(),
+                unmute: "Töne einschalten".into(),
+                save_board_image: "Bild des Spielfelds speichern".into(),
+                copy_board_image: "Bild des Spielfelds kopieren".into(),
+                copied_board_image: "Bild des Spielfelds kopiert".into(),
+                copy_failed: "Kopieren fehlgeschlagen: {}".into(),
+                saved_file: "{} gespeichert".into(),
+                save_failed: "Speichern fehlgeschlagen: {}".into(),
+                settings: "Einstellungen".into(),
+                statistics: "Statistik".into(),
+                leaderboard: "Bestenliste".into(),
+                history: "Verlauf".into(),
+                session: "Sitzung".into(),
+                progress: "Fortschritt".into(),
+                watch_ai: "Der KI beim Spielen zusehen".into(),
+                clicks: "Klicks".into(),
+                efficiency: "Effizienz".into(),
+                checking_boards: "{} Spielfelder auf ein ratefreies geprüft".into(),
+
+                abandon_game: "Laufendes Spiel aufgeben?".into(),
+                abandon_for_difficulty:
+                    "Laufendes Spiel aufgeben und ein neues Spiel ({}) starten?".into(),
+                abandon: "Aufgeben".into(),
+                cancel: "Abbrechen".into(),
+                resume_game: "Das Spiel bei {} fortsetzen?".into(),
+                resume: "Fortsetzen".into(),
+
+                played: "Gespielt".into(),
+                won: "Gewonnen".into(),
+                lost: "Verloren".into(),
+                abandoned: "Aufgegeben".into(),
+                win_rate: "Gewinnrate".into(),
+                average_time: "Durchschnittszeit".into(),
+                export_csv: "Als CSV exportieren".into(),
+                export_json: "Als JSON exportieren".into(),
+
+                export_bundle: "Alles exportieren".into(),
+                import_bundle_hint: "Exportierte Datei hierher ziehen, um sie zu importieren".into(),
+                bundle_imported: "Einstellungen, Statistiken, Rekorde und Spiele importiert".into(),
+                bundle_too_new: "Die Datei wurde von einer neueren Version exportiert".into(),
+                bundle_invalid: "Die Datei kann nicht importiert werden: {}".into(),
+                restore_bundle: "Alle Profile durch die der Datei ersetzen?".into(),
+                replace: "Ersetzen".into(),
+                confirm_restart: "Aufgeben eines laufenden Spiels bestätigen".into(),
+                auto_open: "Bei neuen Spielen eine Öffnung aufdecken".into(),
+                cap_hints: "Nicht mehr Markierungen als Minen erlauben".into(),
+                wrap_cursor: "Cursor über die Ränder hinweg bewegen".into(),
+                mouse_moves_cursor: "Die Maus bewegt den Tastatur-Cursor".into(),
+                key_repeat_delay: "Verzögerung der Tastenwiederholung".into(),
+                key_repeat_interval: "Abstand der Tastenwiederholung".into(),
+                auto_play_interval: "Abstand der KI-Züge".into(),
+                unlimited_solver_hints: "Unbegrenzte Lösungshinweise".into(),
+                per_game: "pro Spiel".into(),
+                solver_hint_penalty: "Zeitstrafe für Lösungshinweise".into(),
+                pause_when_idle: "Die Zeit anhalten, wenn nichts passiert".into(),
+                pause_after: "nach".into(),
+                autosave: "Das laufende Spiel speichern".into(),
+                autosave_every: "alle".into(),
+                language: "Sprache".into(),
+                color_palette: "Farbpalette".into(),
+                number_glyphs: "Darstellung der Zahlen".into(),
+                draw_emoji: "Mit Emoji zeichnen".into(),
+                number_shapes: "Zahlen mit Formen kennzeichnen".into(),
+                show_progress_bars: "Fortschrittsbalken zeigen".into(),
+                show_guide_lines: "Alle 5 Felder Hilfslinien zeigen".into(),
+                show_board_id: "Die Spielfeld-ID zeigen".into(),
+                show_bbbv: "3BV und Effizienz zeigen".into(),
+                race_best_run: "Gegen den besten Lauf auf demselben Spielfeld antreten".into(),
+                auto_hide_top_bar: "Die obere Leiste während des Spiels ausblenden".into(),
+                ui_scale: "Skalierung".into(),
+                large_cells: "Große Felder".into(),
+                vibrate: "Vibrieren".into(),
+                speak_cursor: "Das Feld unter dem Cursor vorlesen".into(),
+                reduce_motion: "Bewegungen reduzieren".into(),
+                chording: "Akkorde".into(),
+                key_bindings: "Tastenbelegung".into(),
+                online_leaderboard: "Online-Bestenliste".into(),
+
+                palette_classic: "Klassisch".into(),
+                palette_ocean: "Ozean".into(),
+                palette_forest: "Wald".into(),
+                palette_sunset: "Sonnenuntergang".into(),
+                palette_colorblind: "Farbenblind".into(),
+                palette_high_contrast: "Hoher Kontrast".into(),
+                palette_retro: "Retro".into(),
+                glyphs_digits: "Ziffern".into(),
+                glyphs_roman: "Römische Zahlen".into(),
+                glyphs_dice: "Würfel".into(),
+                glyphs_dominoes: "Dominosteine".into(),
+
+                chord_left_click: "Linksklick auf eine Zahl".into(),
+                chord_middle_click: "Mittelklick".into(),
+                chord_both_buttons: "Beide Tasten".into(),
+                chord_double_click: "Doppelklick auf eine Zahl".into(),
+                unsatisfied_chord: "Akkord auf einer nicht erfüllten Zahl".into(),
+                unsatisfied_nothing: "Tut nichts".into(),
+                unsatisfied_press: "Drückt die Nachbarn".into(),
+
+                move_up: "Nach oben".into(),
+                move_right: "Nach rechts".into(),
+                move_down: "Nach unten".into(),
+                move_left: "Nach links".into(),
+                move_up_left: "Nach oben links".into(),
+                move_up_right: "Nach oben rechts".into(),
+                move_down_left: "Nach unten links".into(),
+                move_down_right: "Nach unten rechts".into(),
+                jump_left: "Zum linken Rand springen".into(),
+                jump_right: "Zum rechten Rand springen".into(),
+                jump_top: "Zum oberen Rand springen".into(),
+                jump_bottom: "Zum unteren Rand springen".into(),
+                jump_center: "Zur Mitte springen".into(),
+                reveal: "Aufdecken".into(),
+                hint: "Markieren".into(),
+                chord: "Akkord".into(),
+                easy_difficulty: "Schwierigkeit leicht".into(),
+                medium_difficulty: "Schwierigkeit mittel".into(),
+                hard_difficulty: "Schwierigkeit schwer".into(),
+                remove_binding: "Belegung entfernen".into(),
+                press_a_key: "Taste drücken…".into(),
+                reset_key_bindings: "Tastenbelegung zurücksetzen".into(),
+                load_preset: "Vorlage laden".into(),
+                preset_arrows: "Pfeiltasten".into(),
+                preset_numpad: "Ziffernblock".into(),
+                preset_left_hand: "Linke Hand".into(),
+                preset_azerty: "Linke Hand (AZERTY)".into(),
+                preset_dvorak: "IJKL (Dvorak)".into(),
+                preset_arrows_keys:
+                    "Pfeiltasten bewegen, Enter deckt auf, Einfg markiert, Entf spielt Akkorde"
+                        .into(),
+                preset_ijkl_keys:
+                    "IJKL und U, O, N, M bewegen, Leertaste deckt auf, F markiert, D spielt Akkorde"
+                        .into(),
+                preset_numpad_keys: "1 bis 9 bewegen, 5 deckt auf, 0 markiert, - spielt Akkorde"
+                    .into(),
+                preset_left_hand_keys:
+                    "WASD bewegen, Q deckt auf, E markiert, Umschalt+Q spielt Akkorde".into(),
+                preset_azerty_keys:
+                    "ZQSD bewegen, A deckt auf, E markiert, Umschalt+A spielt Akkorde".into(),
+                preset_dvorak_keys:
+                    "CHTN und G, R, B, M bewegen, Leertaste deckt auf, U markiert, E spielt Akkorde"
+                        .into(),
+
+                online_explanation: "Wenn aktiviert, werden nach deinem ersten Sieg jeder \
+                    täglichen Herausforderung dein Name, das Spielfeld, deine Zeit und ein Hash \
+                    deiner Züge an den Server unten gesendet."
+                    .into(),
+                submit_daily_times: "Zeiten der täglichen Herausforderung einreichen".into(),
+                server: "Server".into(),
+
+                time: "Zeit".into(),
+                date: "Datum".into(),
+                seed: "Seed".into(),
+                play_board: "Spielfeld spielen".into(),
+                watch_replay: "Wiederholung ansehen".into(),
+                board_id: "Spielfeld-ID".into(),
+                daily_challenge: "Tägliche Herausforderung".into(),
+                online_error: "Die Online-Bestenliste konnte nicht geladen werden: {}".into(),
+                reload: "Neu laden".into(),
+                import_hint:
+                    "Statistiken oder Replays von Arbiter oder Viennasweeper hierher ziehen, um sie zu importieren"
+                        .into(),
+                imported_records: "{} Rekorde importiert".into(),
+
+                all: "Alle".into(),
+                result: "Ergebnis".into(),
+
+                best_today: "Heute am besten".into(),
+                average_of_last_5: "Durchschnitt der letzten 5".into(),
+
+                games: "Spiele".into(),
+
+                profile: "Profil".into(),
+                default_profile: "Standard".into(),
+                add_profile: "Hinzufügen".into(),
+
+                multiplayer: "Mehrspieler".into(),
+                player_name: "Name".into(),
+                host_race: "Rennen eröffnen".into(),
+                hosting_on_port: "Offen auf Port {}".into(),
+                host_address: "Adresse des Gastgebers oder Relays".into(),
+                room: "Raum".into(),
+                host_in_room: "Im Raum eröffnen".into(),
+                hosting_in_room: "Offen im Raum {}".into(),
+                room_taken: "Der Raum ist schon vergeben".into(),
+                room_not_found: "Diesen Raum gibt es nicht".into(),
+                join_race: "Beitreten".into(),
+                start_race: "Rennen starten".into(),
+                leave_race: "Verlassen".into(),
+                waiting_for_host: "Warte auf den Start durch den Gastgeber".into(),
+                spectate: "Nur zuschauen".into(),
+                watch: "Zuschauen".into(),
+                spectators: "{} schauen zu".into(),
+                coop: "Koop".into(),
+                coop_hint: "Gemeinsam spielen und sehen, wohin die anderen zeigen".into(),
+                coop_cleared: "Das Feld wurde gemeinsam geräumt".into(),
+                race_waiting: "Wartet".into(),
+                race_lost: "Verloren".into(),
+                race_won_by: "{} hat das Rennen gewonnen".into(),
+                connection_failed: "Verbindung fehlgeschlagen: {}".into(),
+                connection_lost: "Die Verbindung zum Gastgeber wurde getrennt".into(),
+
+                show_summary: "Zusammenfassung zeigen".into(),
+                efficiency_percent: "{}% Effizienz".into(),
+                left_clicks: "Links".into(),
+                right_clicks: "Rechts".into(),
+                chords: "Akkorde".into(),
+                wasted_clicks: "Verschwendet".into(),
+                new_personal_best: "Neue persönliche Bestzeit!".into(),
+                faster_than_before: "{} schneller als zuvor".into(),
+                best_on_board: "Deine Bestzeit auf diesem Spielfeld: {}".into(),
+                beat_best_on_board: "Bestzeit auf diesem Spielfeld um {} verbessert".into(),
+                solver_hints_used: "{} Lösungshinweise genutzt".into(),
+                solver_hints: "Lösungshinweise".into(),
+                lost_to_chord: "Verloren durch einen Akkord mit falscher Flagge".into(),
+                lost_to_click: "Auf eine Mine ohne Flagge geklickt".into(),
+                forced_guess: "Es musste geraten werden".into(),
+                verdict_unknown: "Zu viele Möglichkeiten, um zu sagen, ob geraten werden musste"
+                    .into(),
+                had_information: "Es gab Hinweise! {} sichere Felder".into(),
+                new_game: "Neues Spiel".into(),
+                retry_board: "Nochmal versuchen".into(),
+                view_analysis: "Analyse ansehen".into(),
+                save_replay: "Wiederholung speichern".into(),
+
+                board: "Spielfeld".into(),
+                cell_position: "Zeile {}, Spalte {}".into(),
+                cell_hidden: "verdeckt".into(),
+                cell_flagged: "markiert".into(),
+                cell_mine: "Mine".into(),
+                cell_revealed: "aufgedeckt, {} benachbarte Minen".into(),
+                announce_won: "Spiel in {} gewonnen".into(),
+                announce_lost: "Spiel verloren".into(),
+                announce_opened_one: "1 Feld aufgedeckt".into(),
+                announce_opened: "{} Felder aufgedeckt".into(),
+                announce_mine_counter: "Minenzähler {}".into(),
+                one_minute: "1 Minute".into(),
+                minutes: "{} Minuten".into(),
+                one_second: "1 Sekunde".into(),
+                seconds: "{} Sekunden".into(),
+            },
+        }
+    }
+
+    pub(crate) fn difficulty(&self, difficulty: Difficulty) -> &str {
+        match difficulty {
+            Difficulty::Easy => &self.easy,
+            Difficulty::Medium => &self.medium,
+            Difficulty::Hard => &self.hard,
+        }
+    }
+
+    pub(crate) fn action(&self, action: Action) -> &str {
+        match action {
+            Action::MoveUp => &self.move_up,
+            Action::MoveRight => &self.move_right,
+            Action::MoveDown => &self.move_down,
+            Action::MoveLeft => &self.move_left,
+            Action::MoveUpLeft => &self.move_up_left,
+            Action::MoveUpRight => &self.move_up_right,
+            Action::MoveDownLeft => &self.move_down_left,
+            Action::MoveDownRight => &self.move_down_right,
+            Action::JumpLeft => &self.jump_left,
+            Action::JumpRight => &self.jump_right,
+            Action::JumpTop => &self.jump_top,
+            Action::JumpBottom => &self.jump_bottom,
+            Action::JumpCenter => &self.jump_center,
+            Action::Reveal => &self.reveal,
+            Action::Hint => &self.hint,
+            Action::Chord => &self.chord,
+            Action::Restart => &self.new_game,
+            Action::Fullscreen => &self.toggle_fullscreen,
+            Action::Easy => &self.easy_difficulty,
+            Action::Medium => &self.medium_difficulty,
+            Action::Hard => &self.hard_difficulty,
+        }
+    }
+
+    pub(crate) fn preset(&self, preset: Preset) -> &str {
+        match preset {
+            Preset::Arrows => &self.preset_arrows,
+            Preset::Ijkl => "IJKL",
+            Preset::Numpad => &self.preset_numpad,
+            Preset::LeftHand => &self.preset_left_hand,
+            Preset::Zqsd => &self.preset_azerty,
+            Preset::Dvorak => &self.preset_dvorak,
+        }
+    }
+
+    /// The keys a preset binds.
+    pub(crate) fn preset_keys(&self, preset: Preset) -> &str {
+        match preset {
+            Preset::Arrows => &self.preset_arrows_keys,
+            Preset::Ijkl => &self.preset_ijkl_keys,
+            Preset::Numpad => &self.preset_numpad_keys,
+            Preset::LeftHand => &self.preset_left_hand_keys,
+            Preset::Zqsd => &self.preset_azerty_keys,
+            Preset::Dvorak => &self.preset_dvorak_keys,
+        }
+    }
+
+    pub(crate) fn unsatisfied(&self, unsatisfied: UnsatisfiedChord) -> &str {
+        match unsatisfied {
+            UnsatisfiedChord::Nothing => &self.unsatisfied_nothing,
+            UnsatisfiedChord::PressAndCancel => &self.unsatisfied_press,
+        }
+    }
+
+    pub(crate) fn palette(&self, palette: Palette) -> &str {
+        match palette {
+            Palette::Classic => &self.palette_classic,
+            Palette::Ocean => &self.palette_ocean,
+            Palette::Forest => &self.palette_forest,
+            Palette::Sunset => &self.palette_sunset,
+            Palette::Colorblind => &self.palette_colorblind,
+            Palette::HighContrast => &self.palette_high_contrast,
+            Palette::Retro => &self.palette_retro,
+        }
+    }
+
+    pub(crate) fn glyphs(&self, glyphs: Glyphs) -> &str {
+        match glyphs {
+            Glyphs::Digits => &self.glyphs_digits,
+            Glyphs::Roman => &self.glyphs_roman,
+            Glyphs::Dice => &self.glyphs_dice,
+            Glyphs::Dominoes => &self.glyphs_dominoes,
+        }
+    }
+}
+
+/// Replaces the placeholder in `text` with `value`.
+pub fn fill(text: &str, value: impl Display) -> String {
+    text.replacen("{}", &value.to_string(), 1)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn fill_placeholder() {
+        let tr = Translations::builtin(Language::German);
+        assert_eq!(fill(&tr.player_lost, 2), "S2 hat verloren");
+        assert_eq!(fill(&tr.easy, 2), "Leicht");
+    }
+}
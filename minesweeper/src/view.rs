@@ -0,0 +1,87 @@
+use egui::{Pos2, Rect, Vec2};
+
+pub const MAX_ZOOM: f32 = 8.0;
+
+/// Zoom and pan of the board inside the area that is available to it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct View {
+    pub zoom: f32,
+    /// Offset of the board center from the viewport center.
+    pub pan: Vec2,
+}
+
+impl Default for View {
+    fn default() -> Self {
+        Self {
+            zoom: 1.0,
+            pan: Vec2::ZERO,
+        }
+    }
+}
+
+impl View {
+    /// Position of the top left corner of the board, which is `board_size` large.
+    pub fn board_offset(&self, viewport: Rect, board_size: Vec2) -> Pos2 {
+        viewport.center() - 0.5 * board_size + self.pan
+    }
+
+    /// Zooms by `factor` while keeping the part of the board under `pos` in place. `fit_size` is
+    /// the size of the board without zoom.
+    pub fn zoom_around(&mut self, factor: f32, pos: Pos2, viewport: Rect, fit_size: Vec2) {
+        let offset = self.board_offset(viewport, fit_size * self.zoom);
+        let zoom = (self.zoom * factor).clamp(1.0, MAX_ZOOM);
+        let offset = pos - (pos - offset) * (zoom / self.zoom);
+        self.zoom = zoom;
+        self.pan = offset - (viewport.center() - 0.5 * fit_size * zoom);
+        self.clamp(viewport, fit_size);
+    }
+
+    pub fn pan_by(&mut self, delta: Vec2, viewport: Rect, fit_size: Vec2) {
+        self.pan += delta;
+        self.clamp(viewport, fit_size);
+    }
+
+    /// Pans as little as possible to move `rect` inside the viewport.
+    pub fn pan_to(&mut self, rect: Rect, viewport: Rect, fit_size: Vec2) {
+        let delta =
+            (viewport.min - rect.min).max(Vec2::ZERO) + (viewport.max - rect.max).min(Vec2::ZERO);
+        self.pan_by(delta, viewport, fit_size);
+    }
+
+    /// Pans so the point at `fraction` of the board size is in the center of the viewport.
+    pub fn center_on(&mut self, fraction: Vec2, viewport: Rect, fit_size: Vec2) {
+        self.pan = (Vec2::splat(0.5) - fraction) * fit_size * self.zoom;
+        self.clamp(viewport, fit_size);
+    }
+
+    /// Keeps the viewport covered by the board, along the axes where the board is larger.
+    pub fn clamp(&mut self, viewport: Rect, fit_size: Vec2) {
+        let overflow = ((fit_size * self.zoom - viewport.size()) * 0.5).max(Vec2::ZERO);
+        self.pan = self.pan.clamp(-overflow, overflow);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn zoom_keeps_pointer_in_place() {
+        let viewport = Rect::from_min_size(Pos2::ZERO, Vec2::new(200.0, 100.0));
+        let fit_size = Vec2::new(200.0, 100.0);
+        let mut view = View::default();
+
+        let pos = Pos2::new(50.0, 25.0);
+        view.zoom_around(2.0, pos, viewport, fit_size);
+        let offset = view.board_offset(viewport, fit_size * view.zoom);
+        // the pointer was at a quarter of the board and still is
+        assert_eq!((pos - offset) / (fit_size * view.zoom), Vec2::splat(0.25));
+
+        let rect = Rect::from_min_size(Pos2::new(-40.0, 20.0), Vec2::splat(10.0));
+        view.pan_to(rect, viewport, fit_size);
+        assert_eq!(view.pan, Vec2::new(90.0, 25.0));
+
+        view.zoom_around(0.1, pos, viewport, fit_size);
+        assert_eq!(view, View::default());
+    }
+}
@@ -0,0 +1,217 @@
+//! The messages between the players of a multiplayer race and the relay, and the boards and moves
+//! they carry. They are kept apart from the game, so the relay is built without the GUI.
+
+use serde_derive::{Deserialize, Serialize};
+
+pub const DEFAULT_PORT: u16 = 7845;
+/// Incremented when messages change in a way older versions can't read.
+pub const VERSION: u32 = 4;
+/// The longest message a connection may send, which is far more than any message needs.
+pub const MAX_MESSAGE_LEN: usize = 64 * 1024;
+/// The most fields a board from somewhere else may have.
+const MAX_FIELDS: i32 = 100_000;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Progress {
+    /// Hasn't started the race yet.
+    #[default]
+    Waiting,
+    Playing {
+        revealed: u32,
+        total: u32,
+    },
+    Won {
+        time_ms: u64,
+    },
+    Lost {
+        revealed: u32,
+        total: u32,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Player {
+    pub id: u32,
+    pub name: String,
+    pub progress: Progress,
+}
+
+/// What a player did on the board, which is shown to spectators.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Update {
+    Step(Step),
+    Cursor(i16, i16),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Message {
+    /// Sent by a player or spectator after connecting to the host.
+    Join {
+        name: String,
+        version: u32,
+        #[serde(default)]
+        spectator: bool,
+    },
+    /// Sent by the host to a player that joined.
+    Welcome { id: u32 },
+    /// Sent by the host whenever players join, leave or make progress.
+    Players(Vec<Player>),
+    /// Sent by the host to start a race.
+    Start(BoardSeed),
+    /// Sent by the host to start playing the board together.
+    Coop(BoardSeed),
+    /// Sent by a player to the host whenever it made progress.
+    Progress(Progress),
+    /// Sent by a player to the host for every move and movement of the cursor. In co-op the moves
+    /// are only made once the host sends them back.
+    Update(Update),
+    /// Sent by the host to spectators, and in co-op to every player, for every update of a player.
+    Watched { player: u32, update: Update },
+    /// Sent by a host to the relay, to open a room that the others enter with its code.
+    Open { room: String },
+    /// Sent by a player to the relay before joining, to be connected to the host of the room.
+    Enter { room: String },
+    /// Sent by the relay if the room to open is taken or the room to enter doesn't exist.
+    Refused,
+    /// Wraps the messages between the relay and the host, with the id the relay gave the player.
+    Relayed { client: u32, message: Box<Message> },
+    /// Sent by the relay to the host when a player disconnected.
+    Closed,
+}
+
+/// Everything needed to generate the same board again.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BoardSeed {
+    pub width: i16,
+    pub height: i16,
+    pub num_mines: u16,
+    pub seed: u64,
+    pub opening: Option<(i16, i16)>,
+}
+
+impl BoardSeed {
+    /// Identifies the board by the size, mine count, seed and the field the mines were moved away
+    /// from, like `20x14-35-00000000000000ff-3x4`.
+    pub fn id(&self) -> String {
+        let (w, h, mines, seed) = (self.width, self.height, self.num_mines, self.seed);
+        match self.opening {
+            Some((x, y)) => format!("{w}x{h}-{mines}-{seed:016x}-{x}x{y}"),
+            None => format!("{w}x{h}-{mines}-{seed:016x}"),
+        }
+    }
+
+    /// The daily challenge, the same medium board for everyone on a day.
+    pub fn daily(day: u64) -> Self {
+        // splitmix64, so the seeds of consecutive days are unrelated
+        let mut z = day.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        Self {
+            width: 30,
+            height: 18,
+            num_mines: 88,
+            seed: z ^ (z >> 31),
+            opening: Some((15, 9)),
+        }
+    }
+
+    pub fn parse(id: &str) -> Option<Self> {
+        fn pair(text: &str) -> Option<(i16, i16)> {
+            let (a, b) = text.split_once('x')?;
+            Some((a.parse().ok()?, b.parse().ok()?))
+        }
+
+        let mut parts = id.trim().split('-');
+        let (width, height) = pair(parts.next()?)?;
+        let num_mines = parts.next()?.parse().ok()?;
+        let seed = u64::from_str_radix(parts.next()?, 16).ok()?;
+        let opening = match parts.next() {
+            Some(part) => Some(pair(part)?),
+            None => None,
+        };
+        if parts.next().is_some() {
+            return None;
+        }
+        let board = Self {
+            width,
+            height,
+            num_mines,
+            seed,
+            opening,
+        };
+        board.is_valid().then_some(board)
+    }
+
+    /// Whether a board can be generated, which boards from other players, files or the network
+    /// have to be checked for.
+    pub fn is_valid(&self) -> bool {
+        let (width, height) = (self.width, self.height);
+        if width <= 0 || height <= 0 || width as i32 * height as i32 > MAX_FIELDS {
+            return false;
+        }
+        if self.num_mines as i32 >= width as i32 * height as i32 {
+            return false;
+        }
+        self.opening
+            .is_none_or(|(x, y)| (0..width).contains(&x) && (0..height).contains(&y))
+    }
+}
+
+/// A move of the player, recorded to replay the game.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Step {
+    pub x: i16,
+    pub y: i16,
+    pub kind: StepKind,
+    /// Play time when the move was made in milliseconds.
+    pub time: u32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StepKind {
+    /// Revealing a hidden field.
+    Reveal,
+    /// Placing or removing a hint.
+    Hint,
+    /// Revealing the neighbors of a shown field.
+    Chord,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn board_ids() {
+        let board = BoardSeed {
+            width: 20,
+            height: 14,
+            num_mines: 35,
+            seed: 0xff,
+            opening: Some((3, 4)),
+        };
+        assert_eq!(board.id(), "20x14-35-00000000000000ff-3x4");
+        assert_eq!(BoardSeed::parse(&board.id()), Some(board));
+        let board = BoardSeed {
+            opening: None,
+            ..board
+        };
+        assert_eq!(BoardSeed::parse(" 20x14-35-ff "), Some(board));
+        assert_eq!(BoardSeed::parse("20x14-35-ff-20x4"), None);
+        assert_eq!(BoardSeed::parse("2x2-4-ff"), None);
+        assert_eq!(BoardSeed::parse("20x14-35"), None);
+
+        let too_many_mines = BoardSeed {
+            num_mines: 280,
+            ..board
+        };
+        assert!(!too_many_mines.is_valid());
+        assert!(!BoardSeed { width: 0, ..board }.is_valid());
+        assert!(!BoardSeed {
+            width: 1000,
+            height: 1000,
+            ..board
+        }
+        .is_valid());
+    }
+}
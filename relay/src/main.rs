@@ -0,0 +1,352 @@
+//! Forwards the messages between the host of a race and the players in its room, for players
+//! that can't reach each other directly, like the browser which can't accept connections.
+//!
+//! The host opens a room with a code and the others enter it with the same code. Native clients
+//! connect over TCP and the browser over WebSocket, both on the same port.
+//!
+//! Usage: `cargo run --release --bin minesweeper-relay -- [port]`
+
+use std::collections::{HashMap, HashSet};
+use std::io::{self, BufRead, BufReader, ErrorKind, Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::time::Duration;
+
+use minesweeper_protocol::{Message, DEFAULT_PORT, MAX_MESSAGE_LEN};
+use tungstenite::protocol::WebSocketConfig;
+use tungstenite::Message as WsMessage;
+
+/// How long a new connection waits for more of its first bytes.
+const PEEK_INTERVAL: Duration = Duration::from_millis(5);
+/// How long a WebSocket connection waits for messages before sending the queued ones.
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+enum Incoming {
+    Connected(u32, Sender<Message>),
+    Message(u32, Message),
+    Closed(u32),
+}
+
+struct Room {
+    host: u32,
+    clients: HashSet<u32>,
+}
+
+#[derive(Default)]
+struct Relay {
+    connections: HashMap<u32, Sender<Message>>,
+    rooms: HashMap<String, Room>,
+    /// The room of every connection that opened or entered one.
+    members: HashMap<u32, String>,
+}
+
+impl Relay {
+    fn send(&self, id: u32, message: Message) {
+        if let Some(sender) = self.connections.get(&id) {
+            sender.send(message).ok();
+        }
+    }
+
+    fn handle(&mut self, incoming: Incoming) {
+        match incoming {
+            Incoming::Connected(id, sender) => {
+                self.connections.insert(id, sender);
+            }
+            Incoming::Message(id, message) => self.route(id, message),
+            Incoming::Closed(id) => self.close(id),
+        }
+    }
+
+    fn route(&mut self, id: u32, message: Message) {
+        let room = self.members.get(&id).and_then(|code| self.rooms.get(code));
+        match (message, room) {
+            (Message::Open { room: code }, None) => {
+                if code.is_empty() || self.rooms.contains_key(&code) {
+                    self.send(id, Message::Refused);
+                    return;
+                }
+                eprintln!("{id} opened room {code}");
+                let room = Room {
+                    host: id,
+                    clients: HashSet::new(),
+                };
+                self.rooms.insert(code.clone(), room);
+                self.members.insert(id, code);
+            }
+            (Message::Enter { room: code }, None) => {
+                let Some(room) = self.rooms.get_mut(&code) else {
+                    self.send(id, Message::Refused);
+                    return;
+                };
+                eprintln!("{id} entered room {code}");
+                room.clients.insert(id);
+                self.members.insert(id, code);
+            }
+            (Message::Relayed { client, message }, Some(room)) if room.host == id => {
+                if !room.clients.contains(&client) {
+                    return;
+                }
+                if let Message::Start(board) | Message::Coop(board) = &*message {
+                    if !board.is_valid() {
+                        eprintln!("{id} started a race on an invalid board: {}", board.id());
+                        return;
+                    }
+                }
+                self.send(client, *message);
+            }
+            (message, Some(room)) if room.host != id => {
+                let message = Message::Relayed {
+                    client: id,
+                    message: Box::new(message),
+                };
+                self.send(room.host, message);
+            }
+            _ => (),
+        }
+    }
+
+    /// Closes the room of a host that left, or tells the host that a player left.
+    fn close(&mut self, id: u32) {
+        self.connections.remove(&id);
+        let Some(code) = self.members.remove(&id) else { return };
+        let Some(room) = self.rooms.get_mut(&code) else { return };
+        if room.host == id {
+            eprintln!("{id} closed room {code}");
+            if let Some(room) = self.rooms.remove(&code) {
+                for client in room.clients {
+                    // dropping the sender closes the connection
+                    self.connections.remove(&client);
+                    self.members.remove(&client);
+                }
+            }
+        } else {
+            room.clients.remove(&id);
+            let host = room.host;
+            let message = Message::Relayed {
+                client: id,
+                message: Box::new(Message::Closed),
+            };
+            self.send(host, message);
+        }
+    }
+}
+
+fn main() {
+    let port = match std::env::args().nth(1) {
+        None => DEFAULT_PORT,
+        Some(arg) => match arg.parse() {
+            Ok(port) => port,
+            Err(_) => {
+                eprintln!("usage: minesweeper-relay [port]");
+                std::process::exit(1);
+            }
+        },
+    };
+    let listener = match TcpListener::bind(("0.0.0.0", port)) {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("failed to listen on port {port}: {e}");
+            std::process::exit(1);
+        }
+    };
+    eprintln!("relaying on port {port}");
+
+    let (events, receiver) = mpsc::channel();
+    std::thread::spawn(move || {
+        for (id, stream) in (0..).zip(listener.incoming()) {
+            let Ok(stream) = stream else { continue };
+            let events = events.clone();
+            std::thread::spawn(move || {
+                if let Err(e) = serve(id, stream, &events) {
+                    eprintln!("{id} failed: {e}");
+                }
+                events.send(Incoming::Closed(id)).ok();
+            });
+        }
+    });
+
+    let mut relay = Relay::default();
+    for incoming in receiver {
+        relay.handle(incoming);
+    }
+}
+
+/// Reads the messages of the connection until it is closed, the browser is told apart by the
+/// HTTP request that starts a WebSocket.
+fn serve(id: u32, stream: TcpStream, events: &Sender<Incoming>) -> io::Result<()> {
+    stream.set_nodelay(true)?;
+    // the start may arrive in pieces, peeking returns right away once any of it did
+    let mut start = [0; 4];
+    let len = loop {
+        let len = stream.peek(&mut start)?;
+        if len == 0 || len == start.len() {
+            break len;
+        }
+        std::thread::sleep(PEEK_INTERVAL);
+    };
+    let (sender, receiver) = mpsc::channel();
+    events.send(Incoming::Connected(id, sender)).ok();
+
+    if &start[..len] == b"GET " {
+        serve_ws(id, stream, receiver, events)
+    } else {
+        serve_tcp(id, stream, receiver, events)
+    }
+}
+
+/// One message per line, written on a thread of its own.
+fn serve_tcp(
+    id: u32,
+    stream: TcpStream,
+    receiver: Receiver<Message>,
+    events: &Sender<Incoming>,
+) -> io::Result<()> {
+    let mut writer = stream.try_clone()?;
+    std::thread::spawn(move || {
+        for message in receiver {
+            let Ok(line) = ron::to_string(&message) else { continue };
+            if writer.write_all((line + "\n").as_bytes()).is_err() {
+                break;
+            }
+        }
+        // the relay dropped the connection, which ends the reading loop as well
+        writer.shutdown(Shutdown::Both).ok();
+    });
+
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    loop {
+        line.clear();
+        let limit = MAX_MESSAGE_LEN as u64 + 1;
+        if (&mut reader).take(limit).read_line(&mut line)? == 0 {
+            return Ok(());
+        }
+        if !line.ends_with('\n') {
+            // the limit was reached, or the connection closed in the middle of a line
+            return Err(io::Error::new(ErrorKind::InvalidData, "message too long"));
+        }
+        match ron::from_str(&line) {
+            Ok(message) => {
+                events.send(Incoming::Message(id, message)).ok();
+            }
+            Err(e) => eprintln!("{id} sent an invalid message: {e}"),
+        }
+    }
+}
+
+/// One message per text frame, reading and writing take turns on the same thread.
+fn serve_ws(
+    id: u32,
+    stream: TcpStream,
+    receiver: Receiver<Message>,
+    events: &Sender<Incoming>,
+) -> io::Result<()> {
+    let config = WebSocketConfig {
+        max_message_size: Some(MAX_MESSAGE_LEN),
+        max_frame_size: Some(MAX_MESSAGE_LEN),
+        ..WebSocketConfig::default()
+    };
+    let mut socket = tungstenite::accept_with_config(stream, Some(config))
+        .map_err(|e| io::Error::other(e.to_string()))?;
+    socket.get_ref().set_read_timeout(Some(POLL_INTERVAL))?;
+    loop {
+        loop {
+            match receiver.try_recv() {
+                Ok(message) => {
+                    let Ok(text) = ron::to_string(&message) else { continue };
+                    if socket.send(WsMessage::Text(text)).is_err() {
+                        return Ok(());
+                    }
+                }
+                Err(mpsc::TryRecvError::Empty) => break,
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    socket.close(None).ok();
+                    return Ok(());
+                }
+            }
+        }
+
+        match socket.read() {
+            Ok(WsMessage::Text(text)) => match ron::from_str(&text) {
+                Ok(message) => {
+                    events.send(Incoming::Message(id, message)).ok();
+                }
+                Err(e) => eprintln!("{id} sent an invalid message: {e}"),
+            },
+            Ok(WsMessage::Close(_)) => return Ok(()),
+            Ok(_) => (),
+            Err(tungstenite::Error::Io(e))
+                if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+            Err(tungstenite::Error::ConnectionClosed) => return Ok(()),
+            Err(e) => return Err(io::Error::other(e.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use minesweeper_protocol::BoardSeed;
+
+    use super::*;
+
+    fn connect(relay: &mut Relay, id: u32) -> Receiver<Message> {
+        let (sender, receiver) = mpsc::channel();
+        relay.handle(Incoming::Connected(id, sender));
+        receiver
+    }
+
+    fn send(relay: &mut Relay, id: u32, message: Message) {
+        relay.handle(Incoming::Message(id, message));
+    }
+
+    #[test]
+    fn routes_messages_in_rooms() {
+        let mut relay = Relay::default();
+        let host = connect(&mut relay, 0);
+        let player = connect(&mut relay, 1);
+        let room = || "abc".to_string();
+
+        send(&mut relay, 0, Message::Open { room: room() });
+        send(&mut relay, 1, Message::Enter { room: "xyz".into() });
+        assert_eq!(player.try_recv(), Ok(Message::Refused));
+        send(&mut relay, 1, Message::Enter { room: room() });
+
+        send(&mut relay, 1, Message::Progress(Default::default()));
+        let relayed = Message::Relayed {
+            client: 1,
+            message: Box::new(Message::Progress(Default::default())),
+        };
+        assert_eq!(host.try_recv(), Ok(relayed));
+
+        let board = BoardSeed {
+            width: 9,
+            height: 9,
+            num_mines: 10,
+            seed: 3,
+            opening: Some((4, 4)),
+        };
+        let invalid = BoardSeed {
+            num_mines: 81,
+            ..board
+        };
+        for board in [invalid, board] {
+            let message = Message::Relayed {
+                client: 1,
+                message: Box::new(Message::Start(board)),
+            };
+            send(&mut relay, 0, message);
+        }
+        assert_eq!(player.try_recv(), Ok(Message::Start(board)));
+        assert!(player.try_recv().is_err());
+
+        relay.handle(Incoming::Closed(1));
+        let closed = Message::Relayed {
+            client: 1,
+            message: Box::new(Message::Closed),
+        };
+        assert_eq!(host.try_recv(), Ok(closed));
+
+        relay.handle(Incoming::Closed(0));
+        assert!(relay.rooms.is_empty());
+    }
+}
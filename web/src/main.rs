@@ -8,10 +8,7 @@ struct MinesweeperApp {
 
 impl MinesweeperApp {
     fn new(cc: &CreationContext) -> Self {
-        let minesweeper = cc
-            .storage
-            .and_then(|s| eframe::get_value(s, eframe::APP_KEY))
-            .unwrap_or_default();
+        let minesweeper = cc.storage.map(|s| Minesweeper::load(s)).unwrap_or_default();
         Self { minesweeper }
     }
 }
@@ -26,7 +23,7 @@ impl App for MinesweeperApp {
     }
 
     fn save(&mut self, storage: &mut dyn eframe::Storage) {
-        eframe::set_value(storage, eframe::APP_KEY, &self.minesweeper);
+        self.minesweeper.save(storage);
     }
 }
 